@@ -5,10 +5,11 @@ declare license     "BSD";
 
 import("stdfaust.lib");
 
-// Precompute midi note to frequency
+// Midi note to frequency, computed exactly so that fine tunings are kept
+// (a lookup table rounds notes to steps of about 6 cents)
 // Clamped to the midi range since the dsp is not strict on that
 // It could be checked with the latest -ct 1 Faust option instead, but let's keep the dsp "correct".
-midikey2hz(mk) = ba.tabulate(1, ba.midikey2hz, 2048, 0, 127, mk).val;
+midikey2hz(mk) = mk : max(0) : min(127) : ba.midikey2hz;
 
 // Filter used in each voice
 filter(res, note, cutoffNote) = ve.moog_vcf_2b(res, cutoffFreq)
@@ -70,15 +71,20 @@ with {
     offset = trumpet;
 };
 
-echo(s) = s <: ef.echo(10.0, duration, feedback) * mix, s * (1-mix) :> _
+// Echo of each side
+echo = par(i, 2, echoSide)
 with {
+    echoSide(s) = s <: ef.echo(10.0, duration, feedback) * mix, s * (1-mix) :> _;
     mix = hslider("[0]mix", 1.0, 0, 1, 0.001) : si.smoo;
     duration = hslider("[0]duration[scale:log]", 0.3, 0.01, 3.0, 0.001) : si.smoo;
     feedback = hslider("[1]feedback", 0.3, 0, 1, 0.001);
 };
 
-reverb(s) = s <: re.jpverb(t60, damp, size, earlyDiff, modDepth, modFreq, 1, 1, 1, 440, 8000) :> _ <: _ * mix, s * (1-mix) :> _
+// Stereo reverb, mixed with the dry sound of each side
+reverb = _, _ <: re.jpverb(t60, damp, size, earlyDiff, modDepth, modFreq, 1, 1, 1, 440, 8000), _, _
+    : ro.interleave(2, 2) : par(i, 2, mixSide)
 with {
+    mixSide(wet, dry) = wet * mix + dry * (1-mix);
     mix = hslider("[0]mix", 0.11, 0, 1, 0.001);
     t60 = hslider("[1]time", 3.5, 0.1, 60, 0.001);
     damp = hslider("[2]damp", 0.88, 0, 1, 0.001);
@@ -90,20 +96,32 @@ with {
 
 fx = vgroup("[0]echo", echo) : vgroup("[1]reverb", reverb);
 
-// Mix
-process = hgroup("[2]drone", drone) * drone_volume
-    + vgroup("[0]lead", leadChord)(pitchBend, res, cutoffNote) * lead_volume
-    + hgroup("[1]pluck", guitar)(pitchBend, res, cutoffNote) * pluck_volume
-    : hgroup("[2]fx", fx)
-    : co.compressor_mono(12,-4,800/1000000,0.5)
-    : _ * master_volume
-    <: _, _
+// Constant power panning from the left (-1) to the right (1), -3 dB in the center
+pan(p) = _ <: _ * cos((p + 1) * ma.PI / 4), _ * sin((p + 1) * ma.PI / 4);
+
+// Mix of the audio input and the instrument, then the lead carrying the vocoder
+process = _, (lead <: _, _) : mix, (_ * vocoder : pan(lead_pan) : par(i, 2, _ * master_volume))
 with {
+    lead = vgroup("[0]lead", leadChord)(pitchBend, res, cutoffNote) * lead_volume;
+    // The vocoded lead replaces the lead in the mix
+    mix(input, lead) = (input * input_volume <: _, _),
+        (hgroup("[2]drone", drone) * drone_volume : pan(drone_pan)),
+        (lead * (1 - vocoder) : pan(lead_pan)),
+        (hgroup("[1]pluck", guitar)(pitchBend, res, cutoffNote) * pluck_volume : pan(pluck_pan))
+        :> _, _
+        : hgroup("[2]fx", fx)
+        : par(i, 2, co.compressor_mono(12,-4,800/1000000,0.5) * master_volume);
+
     mixGroup(x) = vgroup("[3]mix", x);
     master_volume = mixGroup(hslider("[0]master", 1, 0, 1, 0.001)) : si.smoo;
     drone_volume = mixGroup(hslider("[1]drone", 1, 0, 1, 0.001)) : si.smoo;
     lead_volume = mixGroup(hslider("[2]lead", 1, 0, 1, 0.001)) : si.smoo;
     pluck_volume = mixGroup(hslider("[3]pluck", 1, 0, 1, 0.001)) : si.smoo;
+    drone_pan = mixGroup(hslider("[4]drone_pan", 0, -1, 1, 0.001)) : si.smoo;
+    lead_pan = mixGroup(hslider("[5]lead_pan", 0, -1, 1, 0.001)) : si.smoo;
+    pluck_pan = mixGroup(hslider("[6]pluck_pan", 0, -1, 1, 0.001)) : si.smoo;
+    input_volume = mixGroup(hslider("[7]input", 0, 0, 1, 0.001)) : si.smoo;
+    vocoder = mixGroup(checkbox("[8]vocoder")) : si.smoo;
     
     filterGroup(x) = vgroup("[4]filter", x);
     cutoffNote = filterGroup(hslider("[1]cutoffNote", 0, -20, 50, 0.001)) : si.smoo;
@@ -20,42 +20,6 @@ use faust_types::*;
 
 
 
-#[derive(Debug,Clone)]
-pub struct mydspSIG0 {
-	iRec4: [i32;2],
-}
-
-impl mydspSIG0 {
-	
-	fn get_num_inputsmydspSIG0(&self) -> i32 {
-		return 0;
-	}
-	fn get_num_outputsmydspSIG0(&self) -> i32 {
-		return 1;
-	}
-	
-	fn instance_initmydspSIG0(&mut self, sample_rate: i32) {
-		for l2 in 0..2 {
-			self.iRec4[l2 as usize] = 0;
-		}
-	}
-	
-	fn fillmydspSIG0(&mut self, count: i32, table: &mut[F32]) {
-		for i1 in 0..count {
-			self.iRec4[0] = i32::wrapping_add(self.iRec4[1], 1);
-			table[i1 as usize] = 4.4e+02 * F32::powf(2.0, 0.083333336 * (0.062042013 * (i32::wrapping_add(self.iRec4[0], -1)) as F32 + -69.0));
-			self.iRec4[1] = self.iRec4[0];
-		}
-	}
-
-}
-
-
-pub fn newmydspSIG0() -> mydspSIG0 { 
-	mydspSIG0 {
-		iRec4: [0;2],
-	}
-}
 static mut imydspSIG1Wave0: [i32;1302] = [2,3,5,7,11,13,17,19,23,29,31,37,41,43,47,53,59,61,67,71,73,79,83,89,97,101,103,107,109,113,127,131,137,139,149,151,157,163,167,173,179,181,191,193,197,199,211,223,227,229,233,239,241,251,257,263,269,271,277,281,283,293,307,311,313,317,331,337,347,349,353,359,367,373,379,383,389,397,401,409,419,421,431,433,439,443,449,457,461,463,467,479,487,491,499,503,509,521,523,541,547,557,563,569,571,577,587,593,599,601,607,613,617,619,631,641,643,647,653,659,661,673,677,683,691,701,709,719,727,733,739,743,751,757,761,769,773,787,797,809,811,821,823,827,829,839,853,857,859,863,877,881,883,887,907,911,919,929,937,941,947,953,967,971,977,983,991,997,1009,1013,1019,1021,1031,1033,1039,1049,1051,1061,1063,1069,1087,1091,1093,1097,1103,1109,1117,1123,1129,1151,1153,1163,1171,1181,1187,1193,1201,1213,1217,1223,1229,1231,1237,1249,1259,1277,1279,1283,1289,1291,1297,1301,1303,1307,1319,1321,1327,1361,1367,1373,1381,1399,1409,1423,1427,1429,1433,1439,1447,1451,1453,1459,1471,1481,1483,1487,1489,1493,1499,1511,1523,1531,1543,1549,1553,1559,1567,1571,1579,1583,1597,1601,1607,1609,1613,1619,1621,1627,1637,1657,1663,1667,1669,1693,1697,1699,1709,1721,1723,1733,1741,1747,1753,1759,1777,1783,1787,1789,1801,1811,1823,1831,1847,1861,1867,1871,1873,1877,1879,1889,1901,1907,1913,1931,1933,1949,1951,1973,1979,1987,1993,1997,1999,2003,2011,2017,2027,2029,2039,2053,2063,2069,2081,2083,2087,2089,2099,2111,2113,2129,2131,2137,2141,2143,2153,2161,2179,2203,2207,2213,2221,2237,2239,2243,2251,2267,2269,2273,2281,2287,2293,2297,2309,2311,2333,2339,2341,2347,2351,2357,2371,2377,2381,2383,2389,2393,2399,2411,2417,2423,2437,2441,2447,2459,2467,2473,2477,2503,2521,2531,2539,2543,2549,2551,2557,2579,2591,2593,2609,2617,2621,2633,2647,2657,2659,2663,2671,2677,2683,2687,2689,2693,2699,2707,2711,2713,2719,2729,2731,2741,2749,2753,2767,2777,2789,2791,2797,2801,2803,2819,2833,2837,2843,2851,2857,2861,2879,2887,2897,2903,2909,2917,2927,2939,2953,2957,2963,2969,2971,2999,3001,3011,3019,3023,3037,3041,3049,3061,3067,3079,3083,3089,3109,3119,3121,3137,3163,3167,3169,3181,3187,3191,3203,3209,3217,3221,3229,3251,3253,3257,3259,3271,3299,3301,3307,3313,3319,3323,3329,3331,3343,3347,3359,3361,3371,3373,3389,3391,3407,3413,3433,3449,3457,3461,3463,3467,3469,3491,3499,3511,3517,3527,3529,3533,3539,3541,3547,3557,3559,3571,3581,3583,3593,3607,3613,3617,3623,3631,3637,3643,3659,3671,3673,3677,3691,3697,3701,3709,3719,3727,3733,3739,3761,3767,3769,3779,3793,3797,3803,3821,3823,3833,3847,3851,3853,3863,3877,3881,3889,3907,3911,3917,3919,3923,3929,3931,3943,3947,3967,3989,4001,4003,4007,4013,4019,4021,4027,4049,4051,4057,4073,4079,4091,4093,4099,4111,4127,4129,4133,4139,4153,4157,4159,4177,4201,4211,4217,4219,4229,4231,4241,4243,4253,4259,4261,4271,4273,4283,4289,4297,4327,4337,4339,4349,4357,4363,4373,4391,4397,4409,4421,4423,4441,4447,4451,4457,4463,4481,4483,4493,4507,4513,4517,4519,4523,4547,4549,4561,4567,4583,4591,4597,4603,4621,4637,4639,4643,4649,4651,4657,4663,4673,4679,4691,4703,4721,4723,4729,4733,4751,4759,4783,4787,4789,4793,4799,4801,4813,4817,4831,4861,4871,4877,4889,4903,4909,4919,4931,4933,4937,4943,4951,4957,4967,4969,4973,4987,4993,4999,5003,5009,5011,5021,5023,5039,5051,5059,5077,5081,5087,5099,5101,5107,5113,5119,5147,5153,5167,5171,5179,5189,5197,5209,5227,5231,5233,5237,5261,5273,5279,5281,5297,5303,5309,5323,5333,5347,5351,5381,5387,5393,5399,5407,5413,5417,5419,5431,5437,5441,5443,5449,5471,5477,5479,5483,5501,5503,5507,5519,5521,5527,5531,5557,5563,5569,5573,5581,5591,5623,5639,5641,5647,5651,5653,5657,5659,5669,5683,5689,5693,5701,5711,5717,5737,5741,5743,5749,5779,5783,5791,5801,5807,5813,5821,5827,5839,5843,5849,5851,5857,5861,5867,5869,5879,5881,5897,5903,5923,5927,5939,5953,5981,5987,6007,6011,6029,6037,6043,6047,6053,6067,6073,6079,6089,6091,6101,6113,6121,6131,6133,6143,6151,6163,6173,6197,6199,6203,6211,6217,6221,6229,6247,6257,6263,6269,6271,6277,6287,6299,6301,6311,6317,6323,6329,6337,6343,6353,6359,6361,6367,6373,6379,6389,6397,6421,6427,6449,6451,6469,6473,6481,6491,6521,6529,6547,6551,6553,6563,6569,6571,6577,6581,6599,6607,6619,6637,6653,6659,6661,6673,6679,6689,6691,6701,6703,6709,6719,6733,6737,6761,6763,6779,6781,6791,6793,6803,6823,6827,6829,6833,6841,6857,6863,6869,6871,6883,6899,6907,6911,6917,6947,6949,6959,6961,6967,6971,6977,6983,6991,6997,7001,7013,7019,7027,7039,7043,7057,7069,7079,7103,7109,7121,7127,7129,7151,7159,7177,7187,7193,7207,7211,7213,7219,7229,7237,7243,7247,7253,7283,7297,7307,7309,7321,7331,7333,7349,7351,7369,7393,7411,7417,7433,7451,7457,7459,7477,7481,7487,7489,7499,7507,7517,7523,7529,7537,7541,7547,7549,7559,7561,7573,7577,7583,7589,7591,7603,7607,7621,7639,7643,7649,7669,7673,7681,7687,7691,7699,7703,7717,7723,7727,7741,7753,7757,7759,7789,7793,7817,7823,7829,7841,7853,7867,7873,7877,7879,7883,7901,7907,7919,7927,7933,7937,7949,7951,7963,7993,8009,8011,8017,8039,8053,8059,8069,8081,8087,8089,8093,8101,8111,8117,8123,8147,8161,8167,8171,8179,8191,8209,8219,8221,8231,8233,8237,8243,8263,8269,8273,8287,8291,8293,8297,8311,8317,8329,8353,8363,8369,8377,8387,8389,8419,8423,8429,8431,8443,8447,8461,8467,8501,8513,8521,8527,8537,8539,8543,8563,8573,8581,8597,8599,8609,8623,8627,8629,8641,8647,8663,8669,8677,8681,8689,8693,8699,8707,8713,8719,8731,8737,8741,8747,8753,8761,8779,8783,8803,8807,8819,8821,8831,8837,8839,8849,8861,8863,8867,8887,8893,8923,8929,8933,8941,8951,8963,8969,8971,8999,9001,9007,9011,9013,9029,9041,9043,9049,9059,9067,9091,9103,9109,9127,9133,9137,9151,9157,9161,9173,9181,9187,9199,9203,9209,9221,9227,9239,9241,9257,9277,9281,9283,9293,9311,9319,9323,9337,9341,9343,9349,9371,9377,9391,9397,9403,9413,9419,9421,9431,9433,9437,9439,9461,9463,9467,9473,9479,9491,9497,9511,9521,9533,9539,9547,9551,9587,9601,9613,9619,9623,9629,9631,9643,9649,9661,9677,9679,9689,9697,9719,9721,9733,9739,9743,9749,9767,9769,9781,9787,9791,9803,9811,9817,9829,9833,9839,9851,9857,9859,9871,9883,9887,9901,9907,9923,9929,9931,9941,9949,9967,9973,10007,10009,10037,10039,10061,10067,10069,10079,10091,10093,10099,10103,10111,10133,10139,10141,10151,10159,10163,10169,10177,10181,10193,10211,10223,10243,10247,10253,10259,10267,10271,10273,10289,10301,10303,10313,10321,10331,10333,10337,10343,10357,10369,10391,10399,10427,10429,10433,10453,10457,10459,10463,10477,10487,10499,10501,10513,10529,10531,10559,10567,10589,10597,10601,10607,10613,10627,10631,10639,10651,10657,10663,10667];
 
 #[derive(Debug,Clone)]
@@ -91,7 +55,6 @@ pub fn newmydspSIG1() -> mydspSIG1 {
 		imydspSIG1Wave0_idx: 0,
 	}
 }
-static mut ftbl0mydspSIG0: [F32;2048] = [0.0;2048];
 fn mydsp_faustpower2_f(value: F32) -> F32 {
 	return value * value;
 }
@@ -633,6 +596,19 @@ pub struct mydsp {
 	fRec0: [F32;2],
 	fHslider41: F32,
 	fRec427: [F32;2],
+	fHslider42: F32,
+	fRec428: [F32;2],
+	fHslider43: F32,
+	fRec429: [F32;2],
+	fHslider44: F32,
+	fRec430: [F32;2],
+	fRec431: [F32;2097152],
+	fRec433: [F32;2],
+	fRec432: [F32;2],
+	fHslider45: F32,
+	fRec434: [F32;2],
+	fCheckbox0: F32,
+	fRec435: [F32;2],
 }
 
 impl FaustDsp for mydsp {
@@ -1172,6 +1148,19 @@ impl FaustDsp for mydsp {
 			fRec0: [0.0;2],
 			fHslider41: 0.0,
 			fRec427: [0.0;2],
+			fHslider42: 0.0,
+			fRec428: [0.0;2],
+			fHslider43: 0.0,
+			fRec429: [0.0;2],
+			fHslider44: 0.0,
+			fRec430: [0.0;2],
+			fRec431: [0.0;2097152],
+			fRec433: [0.0;2],
+			fRec432: [0.0;2],
+			fHslider45: 0.0,
+			fRec434: [0.0;2],
+			fCheckbox0: 0.0,
+			fRec435: [0.0;2],
 		}
 	}
 	fn metadata(&self, m: &mut dyn Meta) { 
@@ -1292,16 +1281,13 @@ impl FaustDsp for mydsp {
 		return self.fSampleRate;
 	}
 	fn get_num_inputs(&self) -> i32 {
-		return 0;
+		return 1;
 	}
 	fn get_num_outputs(&self) -> i32 {
-		return 2;
+		return 4;
 	}
 	
 	fn class_init(sample_rate: i32) {
-		let mut sig0: mydspSIG0 = newmydspSIG0();
-		sig0.instance_initmydspSIG0(sample_rate);
-		sig0.fillmydspSIG0(2048, unsafe { &mut ftbl0mydspSIG0 });
 		let mut sig1: mydspSIG1 = newmydspSIG1();
 		sig1.instance_initmydspSIG1(sample_rate);
 		sig1.fillmydspSIG1(1302, unsafe { &mut itbl1mydspSIG1 });
@@ -1354,6 +1340,11 @@ impl FaustDsp for mydsp {
 		self.fHslider39 = 3.5;
 		self.fHslider40 = 0.75;
 		self.fHslider41 = 1.0;
+		self.fHslider42 = 0.0;
+		self.fHslider43 = 0.0;
+		self.fHslider44 = 0.0;
+		self.fHslider45 = 0.0;
+		self.fCheckbox0 = 0.0;
 	}
 	fn instance_clear(&mut self) {
 		for l0 in 0..2 {
@@ -2662,6 +2653,30 @@ impl FaustDsp for mydsp {
 		for l435 in 0..2 {
 			self.fRec427[l435 as usize] = 0.0;
 		}
+		for l436 in 0..2 {
+			self.fRec428[l436 as usize] = 0.0;
+		}
+		for l437 in 0..2 {
+			self.fRec429[l437 as usize] = 0.0;
+		}
+		for l438 in 0..2 {
+			self.fRec430[l438 as usize] = 0.0;
+		}
+		for l439 in 0..2097152 {
+			self.fRec431[l439 as usize] = 0.0;
+		}
+		for l440 in 0..2 {
+			self.fRec433[l440 as usize] = 0.0;
+		}
+		for l441 in 0..2 {
+			self.fRec432[l441 as usize] = 0.0;
+		}
+		for l442 in 0..2 {
+			self.fRec434[l442 as usize] = 0.0;
+		}
+		for l443 in 0..2 {
+			self.fRec435[l443 as usize] = 0.0;
+		}
 	}
 	fn instance_constants(&mut self, sample_rate: i32) {
 		self.fSampleRate = sample_rate;
@@ -2885,16 +2900,26 @@ impl FaustDsp for mydsp {
 		ui_interface.add_horizontal_slider("lead", ParamIndex(42), 1.0, 0.0, 1.0, 0.001);
 		ui_interface.declare(Some(ParamIndex(43)), "3", "");
 		ui_interface.add_horizontal_slider("pluck", ParamIndex(43), 1.0, 0.0, 1.0, 0.001);
+		ui_interface.declare(Some(ParamIndex(44)), "4", "");
+		ui_interface.add_horizontal_slider("drone_pan", ParamIndex(44), 0.0, -1.0, 1.0, 0.001);
+		ui_interface.declare(Some(ParamIndex(45)), "5", "");
+		ui_interface.add_horizontal_slider("lead_pan", ParamIndex(45), 0.0, -1.0, 1.0, 0.001);
+		ui_interface.declare(Some(ParamIndex(46)), "6", "");
+		ui_interface.add_horizontal_slider("pluck_pan", ParamIndex(46), 0.0, -1.0, 1.0, 0.001);
+		ui_interface.declare(Some(ParamIndex(47)), "7", "");
+		ui_interface.add_horizontal_slider("input", ParamIndex(47), 0.0, 0.0, 1.0, 0.001);
+		ui_interface.declare(Some(ParamIndex(48)), "8", "");
+		ui_interface.add_check_button("vocoder", ParamIndex(48));
 		ui_interface.close_box();
 		ui_interface.declare(None, "4", "");
 		ui_interface.open_vertical_box("filter");
-		ui_interface.declare(Some(ParamIndex(44)), "1", "");
-		ui_interface.add_horizontal_slider("cutoffNote", ParamIndex(44), 0.0, -2e+01, 5e+01, 0.001);
-		ui_interface.declare(Some(ParamIndex(45)), "2", "");
-		ui_interface.add_horizontal_slider("res", ParamIndex(45), 0.0, 0.0, 0.99, 0.001);
+		ui_interface.declare(Some(ParamIndex(49)), "1", "");
+		ui_interface.add_horizontal_slider("cutoffNote", ParamIndex(49), 0.0, -2e+01, 5e+01, 0.001);
+		ui_interface.declare(Some(ParamIndex(50)), "2", "");
+		ui_interface.add_horizontal_slider("res", ParamIndex(50), 0.0, 0.0, 0.99, 0.001);
 		ui_interface.close_box();
-		ui_interface.declare(Some(ParamIndex(46)), "5", "");
-		ui_interface.add_horizontal_slider("pitchBend", ParamIndex(46), 0.0, -1.0, 1.0, 0.001);
+		ui_interface.declare(Some(ParamIndex(51)), "5", "");
+		ui_interface.add_horizontal_slider("pitchBend", ParamIndex(51), 0.0, -1.0, 1.0, 0.001);
 		ui_interface.close_box();
 	}
 	
@@ -2905,8 +2930,8 @@ impl FaustDsp for mydsp {
 			14 => Some(self.fButton2),
 			12 => Some(self.fButton3),
 			10 => Some(self.fButton4),
-			45 => Some(self.fHslider0),
-			44 => Some(self.fHslider1),
+			50 => Some(self.fHslider0),
+			49 => Some(self.fHslider1),
 			7 => Some(self.fHslider10),
 			8 => Some(self.fHslider11),
 			5 => Some(self.fHslider12),
@@ -2928,7 +2953,7 @@ impl FaustDsp for mydsp {
 			23 => Some(self.fHslider27),
 			22 => Some(self.fHslider28),
 			21 => Some(self.fHslider29),
-			46 => Some(self.fHslider3),
+			51 => Some(self.fHslider3),
 			41 => Some(self.fHslider30),
 			31 => Some(self.fHslider31),
 			30 => Some(self.fHslider32),
@@ -2942,6 +2967,11 @@ impl FaustDsp for mydsp {
 			9 => Some(self.fHslider4),
 			37 => Some(self.fHslider40),
 			40 => Some(self.fHslider41),
+			44 => Some(self.fHslider42),
+			45 => Some(self.fHslider43),
+			46 => Some(self.fHslider44),
+			47 => Some(self.fHslider45),
+			48 => Some(self.fCheckbox0),
 			17 => Some(self.fHslider5),
 			15 => Some(self.fHslider6),
 			13 => Some(self.fHslider7),
@@ -2958,8 +2988,8 @@ impl FaustDsp for mydsp {
 			14 => { self.fButton2 = value }
 			12 => { self.fButton3 = value }
 			10 => { self.fButton4 = value }
-			45 => { self.fHslider0 = value }
-			44 => { self.fHslider1 = value }
+			50 => { self.fHslider0 = value }
+			49 => { self.fHslider1 = value }
 			7 => { self.fHslider10 = value }
 			8 => { self.fHslider11 = value }
 			5 => { self.fHslider12 = value }
@@ -2981,7 +3011,7 @@ impl FaustDsp for mydsp {
 			23 => { self.fHslider27 = value }
 			22 => { self.fHslider28 = value }
 			21 => { self.fHslider29 = value }
-			46 => { self.fHslider3 = value }
+			51 => { self.fHslider3 = value }
 			41 => { self.fHslider30 = value }
 			31 => { self.fHslider31 = value }
 			30 => { self.fHslider32 = value }
@@ -2995,6 +3025,11 @@ impl FaustDsp for mydsp {
 			9 => { self.fHslider4 = value }
 			37 => { self.fHslider40 = value }
 			40 => { self.fHslider41 = value }
+			44 => { self.fHslider42 = value }
+			45 => { self.fHslider43 = value }
+			46 => { self.fHslider44 = value }
+			47 => { self.fHslider45 = value }
+			48 => { self.fCheckbox0 = value }
 			17 => { self.fHslider5 = value }
 			15 => { self.fHslider6 = value }
 			13 => { self.fHslider7 = value }
@@ -3005,10 +3040,18 @@ impl FaustDsp for mydsp {
 	}
 	
 	fn compute(&mut self, count: i32, inputs: &[&[Self::T]], outputs: &mut[&mut[Self::T]]) {
-		let (outputs0, outputs1) = if let [outputs0, outputs1, ..] = outputs {
+		let inputs0 = if let [inputs0, ..] = inputs {
+			let inputs0 = inputs0[..count as usize].iter();
+			inputs0
+		} else {
+			panic!("wrong number of inputs");
+		};
+		let (outputs0, outputs1, outputs2, outputs3) = if let [outputs0, outputs1, outputs2, outputs3, ..] = outputs {
 			let outputs0 = outputs0[..count as usize].iter_mut();
 			let outputs1 = outputs1[..count as usize].iter_mut();
-			(outputs0, outputs1)
+			let outputs2 = outputs2[..count as usize].iter_mut();
+			let outputs3 = outputs3[..count as usize].iter_mut();
+			(outputs0, outputs1, outputs2, outputs3)
 		} else {
 			panic!("wrong number of outputs");
 		};
@@ -3040,24 +3083,24 @@ impl FaustDsp for mydsp {
 		let mut fSlow25: F32 = self.fConst2 * self.fHslider20;
 		let mut fSlow26: F32 = self.fHslider21;
 		let mut fSlow27: F32 = self.fHslider22;
-		let mut fSlow28: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow27 - fSlow26) + 0.5) as i32, 2047))) as usize] };
-		let mut fSlow29: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow26 + fSlow27) + 0.5) as i32, 2047))) as usize] };
-		let mut fSlow30: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * fSlow27 + 0.5) as i32, 2047))) as usize] };
+		let mut fSlow28: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow27 - fSlow26)) + -69.0));
+		let mut fSlow29: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow26 + fSlow27)) + -69.0));
+		let mut fSlow30: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow27)) + -69.0));
 		let mut fSlow31: F32 = self.fConst2 * self.fHslider23;
 		let mut fSlow32: F32 = self.fHslider24;
-		let mut fSlow33: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow32 - fSlow26) + 0.5) as i32, 2047))) as usize] };
-		let mut fSlow34: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow26 + fSlow32) + 0.5) as i32, 2047))) as usize] };
-		let mut fSlow35: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * fSlow32 + 0.5) as i32, 2047))) as usize] };
+		let mut fSlow33: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow32 - fSlow26)) + -69.0));
+		let mut fSlow34: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow26 + fSlow32)) + -69.0));
+		let mut fSlow35: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow32)) + -69.0));
 		let mut fSlow36: F32 = self.fConst2 * self.fHslider25;
 		let mut fSlow37: F32 = self.fHslider26;
-		let mut fSlow38: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow37 - fSlow26) + 0.5) as i32, 2047))) as usize] };
-		let mut fSlow39: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow26 + fSlow37) + 0.5) as i32, 2047))) as usize] };
-		let mut fSlow40: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * fSlow37 + 0.5) as i32, 2047))) as usize] };
+		let mut fSlow38: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow37 - fSlow26)) + -69.0));
+		let mut fSlow39: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow26 + fSlow37)) + -69.0));
+		let mut fSlow40: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow37)) + -69.0));
 		let mut fSlow41: F32 = self.fConst2 * self.fHslider27;
 		let mut fSlow42: F32 = self.fHslider28;
-		let mut fSlow43: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow42 - fSlow26) + 0.5) as i32, 2047))) as usize] };
-		let mut fSlow44: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow26 + fSlow42) + 0.5) as i32, 2047))) as usize] };
-		let mut fSlow45: F32 = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * fSlow42 + 0.5) as i32, 2047))) as usize] };
+		let mut fSlow43: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow42 - fSlow26)) + -69.0));
+		let mut fSlow44: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow26 + fSlow42)) + -69.0));
+		let mut fSlow45: F32 = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow42)) + -69.0));
 		let mut fSlow46: F32 = self.fConst2 * self.fHslider29;
 		let mut fSlow47: F32 = self.fConst2 * self.fHslider30;
 		let mut fSlow48: F32 = self.fConst2 * self.fHslider31;
@@ -3132,15 +3175,20 @@ impl FaustDsp for mydsp {
 		let mut iSlow117: i32 = unsafe { itbl1mydspSIG1[((175.0 * fSlow54) as i32) as usize] };
 		let mut fSlow118: F32 = 0.0001 * (iSlow117) as F32;
 		let mut fSlow119: F32 = self.fConst2 * self.fHslider41;
-		let zipped_iterators = outputs0.zip(outputs1);
-		for (output0, output1) in zipped_iterators {
+		let mut fSlow120: F32 = self.fConst2 * self.fHslider42;
+		let mut fSlow121: F32 = self.fConst2 * self.fHslider43;
+		let mut fSlow122: F32 = self.fConst2 * self.fHslider44;
+		let mut fSlow123: F32 = self.fConst2 * self.fHslider45;
+		let mut fSlow124: F32 = self.fConst2 * self.fCheckbox0;
+		let zipped_iterators = inputs0.zip(outputs0).zip(outputs1).zip(outputs2).zip(outputs3);
+		for ((((input0, output0), output1), output2), output3) in zipped_iterators {
 			self.iVec0[0] = 1;
 			self.fRec2[0] = fSlow0 + self.fConst3 * self.fRec2[1];
 			let mut fTemp0: F32 = F32::min(1.4141995, 1.4142135 * self.fRec2[0]);
 			let mut fTemp1: F32 = 1.4142135 * fTemp0;
 			let mut fTemp2: F32 = 1.0 - fTemp1;
 			self.fRec5[0] = fSlow1 + self.fConst3 * self.fRec5[1];
-			self.fRec3[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow2 + self.fRec5[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec3[1];
+			self.fRec3[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow2 + self.fRec5[0])) + -69.0)) + self.fConst3 * self.fRec3[1];
 			let mut fTemp3: F32 = F32::tan(self.fConst4 * F32::max(2e+01, F32::min(1e+04, self.fRec3[0])));
 			let mut fTemp4: F32 = 1.0 / fTemp3;
 			let mut fTemp5: F32 = 2.0 - fTemp1;
@@ -3152,7 +3200,7 @@ impl FaustDsp for mydsp {
 			let mut fTemp11: F32 = fTemp10 + (fTemp9 + fTemp4) / fTemp3 + 1.0;
 			let mut fRec21: F32 = -1.0 * 0.9973053 * (0.9 * self.fRec22[2] + 0.05 * (self.fRec22[1] + self.fRec22[3]));
 			self.fRec41[0] = fSlow3 + self.fConst3 * self.fRec41[1];
-			self.fRec40[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow2 + self.fRec41[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec40[1];
+			self.fRec40[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow2 + self.fRec41[0])) + -69.0)) + self.fConst3 * self.fRec40[1];
 			let mut fTemp12: F32 = self.fConst6 * (3.4e+02 / self.fRec40[0] + -0.11);
 			let mut fTemp13: F32 = fTemp12 + -1.499995;
 			let mut iTemp14: i32 = (fTemp13) as i32;
@@ -3248,14 +3296,14 @@ impl FaustDsp for mydsp {
 			self.fRec8[0] = 1.5 * fRec12 * F32::powf(1e+01, 0.05 * fRec9);
 			self.fRec7[0] = self.fRec8[0] - (self.fRec7[2] * (fTemp10 + (fTemp4 - fTemp9) / fTemp3 + 1.0) + 2.0 * self.fRec7[1] * (fTemp10 + (1.0 - fTemp8))) / fTemp11;
 			self.fRec6[0] = (self.fRec7[2] + self.fRec7[0] + 2.0 * self.fRec7[1]) / fTemp11 - (self.fRec6[2] * (fTemp6 + (fTemp4 - fTemp5) / fTemp3 + fTemp2) + 2.0 * self.fRec6[1] * (fTemp6 + (1.0 - (fTemp1 + fTemp8)))) / fTemp7;
-			self.fRec52[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow6 + self.fRec5[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec52[1];
+			self.fRec52[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow6 + self.fRec5[0])) + -69.0)) + self.fConst3 * self.fRec52[1];
 			let mut fTemp60: F32 = F32::tan(self.fConst4 * F32::max(2e+01, F32::min(1e+04, self.fRec52[0])));
 			let mut fTemp61: F32 = 1.0 / fTemp60;
 			let mut fTemp62: F32 = fTemp6 + (fTemp5 + fTemp61) / fTemp60 + fTemp2;
 			let mut fTemp63: F32 = 1.0 / mydsp_faustpower2_f(fTemp60);
 			let mut fTemp64: F32 = fTemp10 + (fTemp9 + fTemp61) / fTemp60 + 1.0;
 			let mut fRec68: F32 = -1.0 * 0.9973053 * (0.9 * self.fRec69[2] + 0.05 * (self.fRec69[1] + self.fRec69[3]));
-			self.fRec87[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow6 + self.fRec41[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec87[1];
+			self.fRec87[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow6 + self.fRec41[0])) + -69.0)) + self.fConst3 * self.fRec87[1];
 			let mut fTemp65: F32 = self.fConst6 * (3.4e+02 / self.fRec87[0] + -0.11);
 			let mut fTemp66: F32 = fTemp65 + -1.499995;
 			let mut iTemp67: i32 = (fTemp66) as i32;
@@ -3349,14 +3397,14 @@ impl FaustDsp for mydsp {
 			self.fRec55[0] = 1.5 * fRec59 * F32::powf(1e+01, 0.05 * fRec56);
 			self.fRec54[0] = self.fRec55[0] - (self.fRec54[2] * (fTemp10 + (fTemp61 - fTemp9) / fTemp60 + 1.0) + 2.0 * self.fRec54[1] * (fTemp10 + (1.0 - fTemp63))) / fTemp64;
 			self.fRec53[0] = (self.fRec54[2] + self.fRec54[0] + 2.0 * self.fRec54[1]) / fTemp64 - (self.fRec53[2] * (fTemp6 + (fTemp61 - fTemp5) / fTemp60 + fTemp2) + 2.0 * self.fRec53[1] * (fTemp6 + (1.0 - (fTemp1 + fTemp63)))) / fTemp62;
-			self.fRec97[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow8 + self.fRec5[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec97[1];
+			self.fRec97[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow8 + self.fRec5[0])) + -69.0)) + self.fConst3 * self.fRec97[1];
 			let mut fTemp112: F32 = F32::tan(self.fConst4 * F32::max(2e+01, F32::min(1e+04, self.fRec97[0])));
 			let mut fTemp113: F32 = 1.0 / fTemp112;
 			let mut fTemp114: F32 = fTemp6 + (fTemp5 + fTemp113) / fTemp112 + fTemp2;
 			let mut fTemp115: F32 = 1.0 / mydsp_faustpower2_f(fTemp112);
 			let mut fTemp116: F32 = fTemp10 + (fTemp9 + fTemp113) / fTemp112 + 1.0;
 			let mut fRec113: F32 = -1.0 * 0.9973053 * (0.9 * self.fRec114[2] + 0.05 * (self.fRec114[1] + self.fRec114[3]));
-			self.fRec132[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow8 + self.fRec41[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec132[1];
+			self.fRec132[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow8 + self.fRec41[0])) + -69.0)) + self.fConst3 * self.fRec132[1];
 			let mut fTemp117: F32 = self.fConst6 * (3.4e+02 / self.fRec132[0] + -0.11);
 			let mut fTemp118: F32 = fTemp117 + -1.499995;
 			let mut iTemp119: i32 = (fTemp118) as i32;
@@ -3450,14 +3498,14 @@ impl FaustDsp for mydsp {
 			self.fRec100[0] = 1.5 * fRec104 * F32::powf(1e+01, 0.05 * fRec101);
 			self.fRec99[0] = self.fRec100[0] - (self.fRec99[2] * (fTemp10 + (fTemp113 - fTemp9) / fTemp112 + 1.0) + 2.0 * self.fRec99[1] * (fTemp10 + (1.0 - fTemp115))) / fTemp116;
 			self.fRec98[0] = (self.fRec99[2] + self.fRec99[0] + 2.0 * self.fRec99[1]) / fTemp116 - (self.fRec98[2] * (fTemp6 + (fTemp113 - fTemp5) / fTemp112 + fTemp2) + 2.0 * self.fRec98[1] * (fTemp6 + (1.0 - (fTemp1 + fTemp115)))) / fTemp114;
-			self.fRec142[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow10 + self.fRec5[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec142[1];
+			self.fRec142[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow10 + self.fRec5[0])) + -69.0)) + self.fConst3 * self.fRec142[1];
 			let mut fTemp164: F32 = F32::tan(self.fConst4 * F32::max(2e+01, F32::min(1e+04, self.fRec142[0])));
 			let mut fTemp165: F32 = 1.0 / fTemp164;
 			let mut fTemp166: F32 = fTemp6 + (fTemp5 + fTemp165) / fTemp164 + fTemp2;
 			let mut fTemp167: F32 = 1.0 / mydsp_faustpower2_f(fTemp164);
 			let mut fTemp168: F32 = fTemp10 + (fTemp9 + fTemp165) / fTemp164 + 1.0;
 			let mut fRec158: F32 = -1.0 * 0.9973053 * (0.9 * self.fRec159[2] + 0.05 * (self.fRec159[1] + self.fRec159[3]));
-			self.fRec177[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow10 + self.fRec41[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec177[1];
+			self.fRec177[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow10 + self.fRec41[0])) + -69.0)) + self.fConst3 * self.fRec177[1];
 			let mut fTemp169: F32 = self.fConst6 * (3.4e+02 / self.fRec177[0] + -0.11);
 			let mut fTemp170: F32 = fTemp169 + -1.499995;
 			let mut iTemp171: i32 = (fTemp170) as i32;
@@ -3551,14 +3599,14 @@ impl FaustDsp for mydsp {
 			self.fRec145[0] = 1.5 * fRec149 * F32::powf(1e+01, 0.05 * fRec146);
 			self.fRec144[0] = self.fRec145[0] - (self.fRec144[2] * (fTemp10 + (fTemp165 - fTemp9) / fTemp164 + 1.0) + 2.0 * self.fRec144[1] * (fTemp10 + (1.0 - fTemp167))) / fTemp168;
 			self.fRec143[0] = (self.fRec144[2] + self.fRec144[0] + 2.0 * self.fRec144[1]) / fTemp168 - (self.fRec143[2] * (fTemp6 + (fTemp165 - fTemp5) / fTemp164 + fTemp2) + 2.0 * self.fRec143[1] * (fTemp6 + (1.0 - (fTemp1 + fTemp167)))) / fTemp166;
-			self.fRec187[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow12 + self.fRec5[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec187[1];
+			self.fRec187[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow12 + self.fRec5[0])) + -69.0)) + self.fConst3 * self.fRec187[1];
 			let mut fTemp216: F32 = F32::tan(self.fConst4 * F32::max(2e+01, F32::min(1e+04, self.fRec187[0])));
 			let mut fTemp217: F32 = 1.0 / fTemp216;
 			let mut fTemp218: F32 = fTemp6 + (fTemp5 + fTemp217) / fTemp216 + fTemp2;
 			let mut fTemp219: F32 = 1.0 / mydsp_faustpower2_f(fTemp216);
 			let mut fTemp220: F32 = fTemp10 + (fTemp9 + fTemp217) / fTemp216 + 1.0;
 			let mut fRec203: F32 = -1.0 * 0.9973053 * (0.9 * self.fRec204[2] + 0.05 * (self.fRec204[1] + self.fRec204[3]));
-			self.fRec222[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow12 + self.fRec41[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec222[1];
+			self.fRec222[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow12 + self.fRec41[0])) + -69.0)) + self.fConst3 * self.fRec222[1];
 			let mut fTemp221: F32 = self.fConst6 * (3.4e+02 / self.fRec222[0] + -0.11);
 			let mut fTemp222: F32 = fTemp221 + -1.499995;
 			let mut iTemp223: i32 = (fTemp222) as i32;
@@ -3655,13 +3703,13 @@ impl FaustDsp for mydsp {
 			self.fRec232[0] = fSlow14 + self.fConst3 * self.fRec232[1];
 			let mut fTemp268: F32 = self.fRec232[0] * ((self.fRec188[2] + self.fRec188[0] + 2.0 * self.fRec188[1]) / fTemp218 + (self.fRec143[2] + self.fRec143[0] + 2.0 * self.fRec143[1]) / fTemp166 + (self.fRec98[2] + self.fRec98[0] + 2.0 * self.fRec98[1]) / fTemp114 + (self.fRec53[2] + self.fRec53[0] + 2.0 * self.fRec53[1]) / fTemp62 + (self.fRec6[2] + self.fRec6[0] + 2.0 * self.fRec6[1]) / fTemp7);
 			let mut fTemp269: F32 = self.fRec41[0] + self.fRec5[0];
-			self.fRec233[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow15 + fTemp269) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec233[1];
+			self.fRec233[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow15 + fTemp269)) + -69.0)) + self.fConst3 * self.fRec233[1];
 			let mut fTemp270: F32 = F32::tan(self.fConst4 * F32::max(2e+01, F32::min(1e+04, self.fRec233[0])));
 			let mut fTemp271: F32 = 1.0 / fTemp270;
 			let mut fTemp272: F32 = fTemp6 + (fTemp5 + fTemp271) / fTemp270 + fTemp2;
 			let mut fTemp273: F32 = 1.0 / mydsp_faustpower2_f(fTemp270);
 			let mut fTemp274: F32 = fTemp10 + (fTemp9 + fTemp271) / fTemp270 + 1.0;
-			self.fRec238[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow15 + self.fRec41[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec238[1];
+			self.fRec238[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow15 + self.fRec41[0])) + -69.0)) + self.fConst3 * self.fRec238[1];
 			let mut fTemp275: F32 = F32::max(1.1920929e-07, F32::abs(self.fRec238[0]));
 			let mut fTemp276: F32 = self.fRec236[1] + self.fConst14 * fTemp275;
 			let mut fTemp277: F32 = fTemp276 + -1.0;
@@ -3671,13 +3719,13 @@ impl FaustDsp for mydsp {
 			self.fRec239[0] = fSlow16 + self.fConst3 * self.fRec239[1];
 			self.fRec235[0] = self.fRec239[0] * (2.0 * fRec237 + -1.0) - (self.fRec235[2] * (fTemp10 + (fTemp271 - fTemp9) / fTemp270 + 1.0) + 2.0 * self.fRec235[1] * (fTemp10 + (1.0 - fTemp273))) / fTemp274;
 			self.fRec234[0] = (self.fRec235[2] + self.fRec235[0] + 2.0 * self.fRec235[1]) / fTemp274 - (self.fRec234[2] * (fTemp6 + (fTemp271 - fTemp5) / fTemp270 + fTemp2) + 2.0 * self.fRec234[1] * (fTemp6 + (1.0 - (fTemp1 + fTemp273)))) / fTemp272;
-			self.fRec240[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow17 + fTemp269) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec240[1];
+			self.fRec240[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow17 + fTemp269)) + -69.0)) + self.fConst3 * self.fRec240[1];
 			let mut fTemp279: F32 = F32::tan(self.fConst4 * F32::max(2e+01, F32::min(1e+04, self.fRec240[0])));
 			let mut fTemp280: F32 = 1.0 / fTemp279;
 			let mut fTemp281: F32 = fTemp6 + (fTemp5 + fTemp280) / fTemp279 + fTemp2;
 			let mut fTemp282: F32 = 1.0 / mydsp_faustpower2_f(fTemp279);
 			let mut fTemp283: F32 = fTemp10 + (fTemp9 + fTemp280) / fTemp279 + 1.0;
-			self.fRec245[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow17 + self.fRec41[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec245[1];
+			self.fRec245[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow17 + self.fRec41[0])) + -69.0)) + self.fConst3 * self.fRec245[1];
 			let mut fTemp284: F32 = F32::max(1.1920929e-07, F32::abs(self.fRec245[0]));
 			let mut fTemp285: F32 = self.fRec243[1] + self.fConst14 * fTemp284;
 			let mut fTemp286: F32 = fTemp285 + -1.0;
@@ -3687,13 +3735,13 @@ impl FaustDsp for mydsp {
 			self.fRec246[0] = fSlow18 + self.fConst3 * self.fRec246[1];
 			self.fRec242[0] = self.fRec246[0] * (2.0 * fRec244 + -1.0) - (self.fRec242[2] * (fTemp10 + (fTemp280 - fTemp9) / fTemp279 + 1.0) + 2.0 * self.fRec242[1] * (fTemp10 + (1.0 - fTemp282))) / fTemp283;
 			self.fRec241[0] = (self.fRec242[2] + self.fRec242[0] + 2.0 * self.fRec242[1]) / fTemp283 - (self.fRec241[2] * (fTemp6 + (fTemp280 - fTemp5) / fTemp279 + fTemp2) + 2.0 * self.fRec241[1] * (fTemp6 + (1.0 - (fTemp1 + fTemp282)))) / fTemp281;
-			self.fRec247[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow19 + fTemp269) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec247[1];
+			self.fRec247[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow19 + fTemp269)) + -69.0)) + self.fConst3 * self.fRec247[1];
 			let mut fTemp288: F32 = F32::tan(self.fConst4 * F32::max(2e+01, F32::min(1e+04, self.fRec247[0])));
 			let mut fTemp289: F32 = 1.0 / fTemp288;
 			let mut fTemp290: F32 = fTemp6 + (fTemp5 + fTemp289) / fTemp288 + fTemp2;
 			let mut fTemp291: F32 = 1.0 / mydsp_faustpower2_f(fTemp288);
 			let mut fTemp292: F32 = fTemp10 + (fTemp9 + fTemp289) / fTemp288 + 1.0;
-			self.fRec252[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow19 + self.fRec41[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec252[1];
+			self.fRec252[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow19 + self.fRec41[0])) + -69.0)) + self.fConst3 * self.fRec252[1];
 			let mut fTemp293: F32 = F32::max(1.1920929e-07, F32::abs(self.fRec252[0]));
 			let mut fTemp294: F32 = self.fRec250[1] + self.fConst14 * fTemp293;
 			let mut fTemp295: F32 = fTemp294 + -1.0;
@@ -3703,13 +3751,13 @@ impl FaustDsp for mydsp {
 			self.fRec253[0] = fSlow20 + self.fConst3 * self.fRec253[1];
 			self.fRec249[0] = self.fRec253[0] * (2.0 * fRec251 + -1.0) - (self.fRec249[2] * (fTemp10 + (fTemp289 - fTemp9) / fTemp288 + 1.0) + 2.0 * self.fRec249[1] * (fTemp10 + (1.0 - fTemp291))) / fTemp292;
 			self.fRec248[0] = (self.fRec249[2] + self.fRec249[0] + 2.0 * self.fRec249[1]) / fTemp292 - (self.fRec248[2] * (fTemp6 + (fTemp289 - fTemp5) / fTemp288 + fTemp2) + 2.0 * self.fRec248[1] * (fTemp6 + (1.0 - (fTemp1 + fTemp291)))) / fTemp290;
-			self.fRec254[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow21 + fTemp269) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec254[1];
+			self.fRec254[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow21 + fTemp269)) + -69.0)) + self.fConst3 * self.fRec254[1];
 			let mut fTemp297: F32 = F32::tan(self.fConst4 * F32::max(2e+01, F32::min(1e+04, self.fRec254[0])));
 			let mut fTemp298: F32 = 1.0 / fTemp297;
 			let mut fTemp299: F32 = fTemp6 + (fTemp5 + fTemp298) / fTemp297 + fTemp2;
 			let mut fTemp300: F32 = 1.0 / mydsp_faustpower2_f(fTemp297);
 			let mut fTemp301: F32 = fTemp10 + (fTemp298 + fTemp9) / fTemp297 + 1.0;
-			self.fRec259[0] = self.fConst2 * unsafe { ftbl0mydspSIG0[(std::cmp::max(0, std::cmp::min((16.11811 * (fSlow21 + self.fRec41[0]) + 0.5) as i32, 2047))) as usize] } + self.fConst3 * self.fRec259[1];
+			self.fRec259[0] = self.fConst2 * 4.4e+02 * F32::powf(2.0, 0.083333336 * (F32::min(127.0, F32::max(0.0, fSlow21 + self.fRec41[0])) + -69.0)) + self.fConst3 * self.fRec259[1];
 			let mut fTemp302: F32 = F32::max(1.1920929e-07, F32::abs(self.fRec259[0]));
 			let mut fTemp303: F32 = self.fRec257[1] + self.fConst14 * fTemp302;
 			let mut fTemp304: F32 = fTemp303 + -1.0;
@@ -3888,12 +3936,23 @@ impl FaustDsp for mydsp {
 			let mut fTemp405: F32 = F32::max(-1.0, F32::min(1.0, self.fRec263[0] + self.fConst17 * (self.fRec303[0] * (self.fRec301[0] * self.fRec300[0] + self.fRec298[0] * self.fRec297[0] + self.fRec295[0] * self.fRec294[0]) + self.fRec293[0] * (self.fRec291[0] * self.fRec290[0] + self.fRec288[0] * self.fRec287[0] + self.fRec285[0] * self.fRec284[0]) + self.fRec283[0] * (self.fRec281[0] * self.fRec280[0] + self.fRec278[0] * self.fRec277[0] + self.fRec275[0] * self.fRec274[0]) + self.fRec273[0] * (self.fRec271[0] * self.fRec270[0] + self.fRec268[0] * self.fRec267[0] + self.fRec265[0] * self.fRec264[0])) * F32::powf(1e+01, 0.6666667 * self.fRec263[0])));
 			self.fRec304[0] = fSlow47 + self.fConst3 * self.fRec304[1];
 			let mut fTemp406: F32 = self.fRec304[0] * fTemp405 * (1.0 - 0.33333334 * mydsp_faustpower2_f(fTemp405));
+			self.fRec428[0] = fSlow120 + self.fConst3 * self.fRec428[1];
+			self.fRec429[0] = fSlow121 + self.fConst3 * self.fRec429[1];
+			self.fRec430[0] = fSlow122 + self.fConst3 * self.fRec430[1];
+			self.fRec434[0] = fSlow123 + self.fConst3 * self.fRec434[1];
+			let mut fTemp650: F32 = *input0 * self.fRec434[0];
+			self.fRec435[0] = fSlow124 + self.fConst3 * self.fRec435[1];
+			let mut fTemp651: F32 = fTemp306 * (1.0 - self.fRec435[0]);
+			let mut fTemp645: F32 = fTemp650 + fTemp406 * F32::cos(0.7853982 * (self.fRec428[0] + 1.0)) + fTemp651 * F32::cos(0.7853982 * (self.fRec429[0] + 1.0)) + fTemp268 * F32::cos(0.7853982 * (self.fRec430[0] + 1.0));
+			let mut fTemp646: F32 = fTemp650 + fTemp406 * F32::sin(0.7853982 * (self.fRec428[0] + 1.0)) + fTemp651 * F32::sin(0.7853982 * (self.fRec429[0] + 1.0)) + fTemp268 * F32::sin(0.7853982 * (self.fRec430[0] + 1.0));
 			self.fRec305[0] = fSlow48 + self.fConst3 * self.fRec305[1];
-			let mut fTemp407: F32 = (1.0 - self.fRec305[0]) * (fTemp406 + fTemp306 + fTemp268);
+			let mut fTemp407: F32 = 1.0 - self.fRec305[0];
 			self.fRec307[0] = fSlow49 + self.fConst3 * self.fRec307[1];
-			self.fRec306[(self.IOTA0 & 2097151) as usize] = fTemp268 + fTemp306 + fSlow50 * self.fRec306[((i32::wrapping_sub(self.IOTA0, i32::wrapping_add((F32::min(self.fConst18, F32::max(0.0, self.fConst0 * self.fRec307[0]))) as i32, 1))) & 2097151) as usize] + fTemp406;
-			let mut fTemp408: F32 = self.fRec306[(self.IOTA0 & 2097151) as usize] * self.fRec305[0];
-			let mut fTemp409: F32 = fTemp408 + fTemp407;
+			let mut iTemp647: i32 = i32::wrapping_add((F32::min(self.fConst18, F32::max(0.0, self.fConst0 * self.fRec307[0]))) as i32, 1);
+			self.fRec306[(self.IOTA0 & 2097151) as usize] = fTemp645 + fSlow50 * self.fRec306[((i32::wrapping_sub(self.IOTA0, iTemp647)) & 2097151) as usize];
+			let mut fTemp408: F32 = fTemp407 * fTemp645 + self.fRec306[(self.IOTA0 & 2097151) as usize] * self.fRec305[0];
+			self.fRec431[(self.IOTA0 & 2097151) as usize] = fTemp646 + fSlow50 * self.fRec431[((i32::wrapping_sub(self.IOTA0, iTemp647)) & 2097151) as usize];
+			let mut fTemp409: F32 = fTemp407 * fTemp646 + self.fRec431[(self.IOTA0 & 2097151) as usize] * self.fRec305[0];
 			self.fRec318[0] = 0.995 * (self.fRec318[1] + (i32::wrapping_mul(iTemp309, iSlow55)) as F32) + fSlow56;
 			let mut fTemp410: F32 = self.fRec318[0] + -1.49999;
 			let mut fTemp411: F32 = F32::floor(fTemp410);
@@ -4211,7 +4270,7 @@ impl FaustDsp for mydsp {
 			self.fRec390[0] = self.fRec391[0] - self.fConst36 * (self.fConst35 * self.fRec390[2] + self.fConst31 * self.fRec390[1]);
 			self.fRec389[0] = self.fConst36 * (self.fConst30 * self.fRec390[0] + self.fConst51 * self.fRec390[1] + self.fConst30 * self.fRec390[2]) - self.fConst34 * (self.fConst33 * self.fRec389[2] + self.fConst31 * self.fRec389[1]);
 			self.fRec388[0] = self.fConst34 * (self.fConst30 * self.fRec389[0] + self.fConst51 * self.fRec389[1] + self.fConst30 * self.fRec389[2]) - self.fConst49 * (self.fConst48 * self.fRec388[2] + fTemp595);
-			let mut fTemp596: F32 = fTemp407 + fTemp408 + fSlow106 * (self.fRec388[2] + self.fConst49 * (fTemp595 + self.fConst48 * self.fRec388[0]) + self.fConst25 * (self.fConst21 * self.fRec385[0] + self.fConst47 * self.fRec385[1] + self.fConst21 * self.fRec385[2] + self.fRec311[2] + self.fRec311[0] + 2.0 * self.fRec311[1]));
+			let mut fTemp596: F32 = fTemp408 + fSlow106 * (self.fRec388[2] + self.fConst49 * (fTemp595 + self.fConst48 * self.fRec388[0]) + self.fConst25 * (self.fConst21 * self.fRec385[0] + self.fConst47 * self.fRec385[1] + self.fConst21 * self.fRec385[2] + self.fRec311[2] + self.fRec311[0] + 2.0 * self.fRec311[1]));
 			self.fVec95[(self.IOTA0 & 1023) as usize] = fTemp596;
 			self.fRec310[0] = fSlow107 * (fTemp591 * fTemp590 * fTemp589 * fTemp588 * self.fVec95[((i32::wrapping_sub(self.IOTA0, std::cmp::min(512, iTemp592))) & 1023) as usize] + fTemp587 * (fTemp585 * fTemp584 * fTemp583 * self.fVec95[((i32::wrapping_sub(self.IOTA0, std::cmp::min(512, iTemp586))) & 1023) as usize] + 0.5 * fTemp574 * fTemp581 * fTemp580 * self.fVec95[((i32::wrapping_sub(self.IOTA0, std::cmp::min(512, iTemp582))) & 1023) as usize] + 0.16666667 * fTemp575 * fTemp578 * self.fVec95[((i32::wrapping_sub(self.IOTA0, std::cmp::min(512, iTemp579))) & 1023) as usize] + 0.041666668 * fTemp576 * self.fVec95[((i32::wrapping_sub(self.IOTA0, std::cmp::min(512, iTemp570))) & 1023) as usize])) + fSlow53 * self.fRec310[1];
 			self.fRec403[0] = 0.995 * (self.fRec403[1] + (i32::wrapping_mul(iTemp309, iSlow110)) as F32) + fSlow111;
@@ -4316,15 +4375,22 @@ impl FaustDsp for mydsp {
 			self.fRec423[0] = self.fRec426[0];
 			self.fRec308[0] = fSlow112 * self.fRec422[1] + fSlow109 * fTemp633;
 			self.fRec309[0] = fSlow112 * self.fRec423[1] + fSlow109 * fTemp636;
-			let mut fTemp641: F32 = fSlow51 * (self.fRec308[0] + self.fRec309[0]) + fSlow52 * fTemp409;
+			let mut fTemp641: F32 = fSlow51 * self.fRec308[0] + fSlow52 * fTemp408;
 			let mut fTemp642: F32 = F32::abs(fTemp641);
 			let mut fTemp643: F32 = if (fTemp642 > self.fRec1[1]) as i32 != 0 {self.fConst53} else {self.fConst52};
 			self.fRec1[0] = fTemp642 * (1.0 - fTemp643) + self.fRec1[1] * fTemp643;
 			self.fRec0[0] = self.fConst54 * (0.0 - 0.9166667 * F32::max(2e+01 * F32::log10(F32::max(1.1754944e-38, self.fRec1[0])) + 4.0, 0.0)) + self.fConst1 * self.fRec0[1];
 			self.fRec427[0] = fSlow119 + self.fConst3 * self.fRec427[1];
-			let mut fTemp644: F32 = self.fRec427[0] * fTemp641 * F32::powf(1e+01, 0.05 * self.fRec0[0]);
-			*output0 = fTemp644;
-			*output1 = fTemp644;
+			*output0 = self.fRec427[0] * fTemp641 * F32::powf(1e+01, 0.05 * self.fRec0[0]);
+			let mut fTemp644: F32 = fSlow51 * self.fRec309[0] + fSlow52 * fTemp409;
+			let mut fTemp648: F32 = F32::abs(fTemp644);
+			let mut fTemp649: F32 = if (fTemp648 > self.fRec433[1]) as i32 != 0 {self.fConst53} else {self.fConst52};
+			self.fRec433[0] = fTemp648 * (1.0 - fTemp649) + self.fRec433[1] * fTemp649;
+			self.fRec432[0] = self.fConst54 * (0.0 - 0.9166667 * F32::max(2e+01 * F32::log10(F32::max(1.1754944e-38, self.fRec433[0])) + 4.0, 0.0)) + self.fConst1 * self.fRec432[1];
+			*output1 = self.fRec427[0] * fTemp644 * F32::powf(1e+01, 0.05 * self.fRec432[0]);
+			let mut fTemp652: F32 = self.fRec427[0] * fTemp306 * self.fRec435[0];
+			*output2 = fTemp652 * F32::cos(0.7853982 * (self.fRec429[0] + 1.0));
+			*output3 = fTemp652 * F32::sin(0.7853982 * (self.fRec429[0] + 1.0));
 			self.iVec0[1] = self.iVec0[0];
 			self.fRec2[1] = self.fRec2[0];
 			self.fRec5[1] = self.fRec5[0];
@@ -4581,6 +4647,9 @@ impl FaustDsp for mydsp {
 			self.fRec303[1] = self.fRec303[0];
 			self.fRec304[1] = self.fRec304[0];
 			self.fRec305[1] = self.fRec305[0];
+			self.fRec428[1] = self.fRec428[0];
+			self.fRec429[1] = self.fRec429[0];
+			self.fRec430[1] = self.fRec430[0];
 			self.fRec307[1] = self.fRec307[0];
 			self.fRec318[1] = self.fRec318[0];
 			self.fRec320[1] = self.fRec320[0];
@@ -4756,6 +4825,10 @@ impl FaustDsp for mydsp {
 			self.fRec1[1] = self.fRec1[0];
 			self.fRec0[1] = self.fRec0[0];
 			self.fRec427[1] = self.fRec427[0];
+			self.fRec433[1] = self.fRec433[0];
+			self.fRec432[1] = self.fRec432[0];
+			self.fRec434[1] = self.fRec434[0];
+			self.fRec435[1] = self.fRec435[0];
 		}
 	}
 
@@ -0,0 +1,301 @@
+use std::ops::RangeInclusive;
+
+use crate::{
+    settings::{
+        Arpeggiator, ArpeggioPattern, ArpeggioRate, DivisionFeel, Geometry, ModulationHand,
+        NoteDivision, Tempo,
+    },
+    tempo::Clock,
+    HandMessage, MidiNoteF,
+};
+
+/// Default note value of the arpeggiator steps, eighth notes
+pub const DEFAULT_DIVISION: NoteDivision = NoteDivision::new(8, DivisionFeel::Straight);
+
+/// Default part of each step the voice sounds
+pub const DEFAULT_GATE: f32 = 0.8;
+
+/// Default steps per second of the hand driven rate
+pub const DEFAULT_HAND_RATE: RangeInclusive<f32> = 1.0..=12.0;
+
+impl ArpeggioRate {
+    /// Steps per second
+    ///
+    /// None if the rate follows the tempo, is driven by the other hand or is
+    /// not measured.
+    pub fn steps_per_second(
+        &self,
+        h: &HandMessage,
+        hand: ModulationHand,
+        geometry: &Geometry,
+    ) -> Option<f32> {
+        match self {
+            ArpeggioRate::Tempo { .. } => None,
+            ArpeggioRate::Hand {
+                hand: rate_hand,
+                source,
+                input,
+                steps_per_second,
+            } => {
+                if *rate_hand != hand {
+                    return None;
+                }
+                let x = source.normalized(h, hand, input.as_ref(), geometry)?;
+                Some(
+                    steps_per_second.start()
+                        + x * (steps_per_second.end() - steps_per_second.start()),
+                )
+            }
+        }
+    }
+}
+
+/// Running arpeggio of the lead voices
+#[derive(Debug, Default)]
+pub struct Arpeggio {
+    /// Steps per second of the hand driven rate
+    rate: f32,
+    /// Index of the current step
+    step: usize,
+    /// Progress in the current step (0-1)
+    phase: f32,
+    /// Time of the last update (s)
+    last_update: Option<f64>,
+    /// Position in the pattern of the random voice
+    random_position: usize,
+    /// State of the random generator
+    seed: u32,
+}
+
+impl Arpeggio {
+    /// Follow the rate measured on a hand
+    pub fn measure_rate(
+        &mut self,
+        arpeggiator: &Arpeggiator,
+        h: &HandMessage,
+        hand: ModulationHand,
+        geometry: &Geometry,
+    ) {
+        if let Some(rate) = arpeggiator.rate.steps_per_second(h, hand, geometry) {
+            self.rate = rate.max(0.0);
+        }
+    }
+
+    /// Volumes of the lead voices at a time (s)
+    ///
+    /// `volumes` are the volumes of the voices played together, the sounding
+    /// ones are played one at a time. The steps of a tempo rate follow the
+    /// beats of the clock.
+    pub fn volumes(
+        &mut self,
+        arpeggiator: &Arpeggiator,
+        tempo: &Tempo,
+        clock: &Clock,
+        time: f64,
+        chord: &[Option<MidiNoteF>; 4],
+        volumes: [f32; 4],
+    ) -> [f32; 4] {
+        let elapsed = self
+            .last_update
+            .map_or(0.0, |last_update| (time - last_update).max(0.0)) as f32;
+        self.last_update = Some(time);
+
+        let order = voice_order(arpeggiator.pattern, chord, &volumes);
+        if order.is_empty() {
+            return volumes;
+        }
+
+        let (step, phase) = match &arpeggiator.rate {
+            ArpeggioRate::Tempo { division } => {
+                let steps = (clock.beats(time) / tempo.beats(division) as f64).max(0.0);
+                (steps.floor() as usize, steps.fract() as f32)
+            }
+            ArpeggioRate::Hand { .. } => {
+                let phase = self.phase + elapsed * self.rate;
+                (
+                    self.step.wrapping_add(phase.floor() as usize),
+                    phase.fract(),
+                )
+            }
+        };
+        if step != self.step {
+            self.random_position = self.next_random_position(order.len());
+        }
+        self.step = step;
+        self.phase = phase;
+
+        let position = match arpeggiator.pattern {
+            ArpeggioPattern::Random => self.random_position % order.len(),
+            ArpeggioPattern::UpDown => {
+                let period = (2 * order.len()).saturating_sub(2).max(1);
+                let position = self.step % period;
+                if position < order.len() {
+                    position
+                } else {
+                    period - position
+                }
+            }
+            ArpeggioPattern::Up | ArpeggioPattern::Down | ArpeggioPattern::AsPlayed => {
+                self.step % order.len()
+            }
+        };
+        let voice = order[position];
+        let mut result = [0.0; 4];
+        if self.phase < arpeggiator.gate {
+            result[voice] = volumes[voice];
+        }
+        result
+    }
+
+    /// Random position in a pattern, other than the current one
+    fn next_random_position(&mut self, len: usize) -> usize {
+        if len < 2 {
+            return 0;
+        }
+        // Xorshift
+        if self.seed == 0 {
+            self.seed = 0x9E37_79B9;
+        }
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        let position = self.seed as usize % (len - 1);
+        if position >= self.random_position % len {
+            position + 1
+        } else {
+            position
+        }
+    }
+}
+
+/// Sounding voices in the order of the pattern
+fn voice_order(
+    pattern: ArpeggioPattern,
+    chord: &[Option<MidiNoteF>; 4],
+    volumes: &[f32; 4],
+) -> Vec<usize> {
+    let mut voices: Vec<(usize, MidiNoteF)> = chord
+        .iter()
+        .zip(volumes)
+        .enumerate()
+        .filter_map(|(voice, (note, volume))| Some((voice, (*note)?)).filter(|_| *volume > 0.0))
+        .collect();
+    match pattern {
+        ArpeggioPattern::AsPlayed => {}
+        ArpeggioPattern::Up | ArpeggioPattern::UpDown | ArpeggioPattern::Random => {
+            voices.sort_by(|(_, a), (_, b)| a.note().total_cmp(&b.note()))
+        }
+        ArpeggioPattern::Down => voices.sort_by(|(_, a), (_, b)| b.note().total_cmp(&a.note())),
+    }
+    voices.into_iter().map(|(voice, _)| voice).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::settings::HandFeature;
+
+    use super::*;
+
+    /// Second inversion of a C major triad, with a high C
+    fn chord() -> [Option<MidiNoteF>; 4] {
+        [67.0, 72.0, 64.0, 76.0].map(|note| Some(MidiNoteF(note)))
+    }
+
+    /// Sounding voice of each step, one step per second
+    fn steps(pattern: ArpeggioPattern, volumes: [f32; 4], count: usize) -> Vec<usize> {
+        let arpeggiator = Arpeggiator {
+            pattern,
+            rate: ArpeggioRate::Tempo {
+                division: NoteDivision::new(4, DivisionFeel::Straight),
+            },
+            gate: 0.5,
+        };
+        let tempo = Tempo {
+            bpm: 60.0,
+            ..Default::default()
+        };
+        let clock = Clock::new(tempo.bpm);
+        let mut arpeggio = Arpeggio::default();
+        (0..count)
+            .map(|step| {
+                let volumes =
+                    arpeggio.volumes(&arpeggiator, &tempo, &clock, step as f64, &chord(), volumes);
+                volumes.iter().position(|v| *v > 0.0).unwrap()
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case(ArpeggioPattern::Up, vec![2, 0, 1, 3, 2, 0])]
+    #[case(ArpeggioPattern::Down, vec![3, 1, 0, 2, 3, 1])]
+    #[case(ArpeggioPattern::UpDown, vec![2, 0, 1, 3, 1, 0, 2, 0])]
+    #[case(ArpeggioPattern::AsPlayed, vec![0, 1, 2, 3, 0, 1])]
+    fn patterns(#[case] pattern: ArpeggioPattern, #[case] expected: Vec<usize>) {
+        assert_eq!(expected, steps(pattern, [1.0; 4], expected.len()));
+    }
+
+    #[rstest]
+    fn length_from_chord_notes() {
+        let volumes = [1.0, 1.0, 0.5, 0.0];
+        assert_eq!(vec![2, 0, 1, 2, 0], steps(ArpeggioPattern::Up, volumes, 5));
+    }
+
+    #[rstest]
+    fn random_changes_voice() {
+        let steps = steps(ArpeggioPattern::Random, [1.0; 4], 20);
+        assert!(steps.windows(2).all(|w| w[0] != w[1]), "{steps:?}");
+    }
+
+    #[rstest]
+    fn gate() {
+        // Eighth notes at 60 bpm, two steps per second
+        let arpeggiator = Arpeggiator {
+            gate: 0.5,
+            ..Default::default()
+        };
+        let tempo = Tempo {
+            bpm: 60.0,
+            ..Default::default()
+        };
+        let clock = Clock::new(tempo.bpm);
+        let mut arpeggio = Arpeggio::default();
+        let volumes = [1.0, 1.0, 0.0, 0.0];
+        let mut at = |time| arpeggio.volumes(&arpeggiator, &tempo, &clock, time, &chord(), volumes);
+        assert_eq!([1.0, 0.0, 0.0, 0.0], at(0.0));
+        assert_eq!([0.0; 4], at(0.3));
+        assert_eq!([0.0, 1.0, 0.0, 0.0], at(0.55));
+    }
+
+    #[rstest]
+    fn hand_rate() {
+        let h = HandMessage {
+            hand_type: crate::HandType::Right,
+            position: Default::default(),
+            velocity: Default::default(),
+            rotation: nalgebra::UnitQuaternion::identity(),
+            pinch: 0.0,
+            grab: 0.5,
+        };
+        let geometry = Geometry::default();
+        let rate = ArpeggioRate::Hand {
+            hand: ModulationHand::Volume,
+            source: HandFeature::Grab,
+            input: None,
+            steps_per_second: DEFAULT_HAND_RATE,
+        };
+        assert_eq!(
+            Some(6.5),
+            rate.steps_per_second(&h, ModulationHand::Volume, &geometry)
+        );
+        assert_eq!(
+            None,
+            rate.steps_per_second(&h, ModulationHand::Pitch, &geometry)
+        );
+        assert_eq!(
+            None,
+            ArpeggioRate::default().steps_per_second(&h, ModulationHand::Volume, &geometry)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt::Display;
+
+use anyhow::Context;
+use cpal::traits::{DeviceTrait, HostTrait};
+use itertools::Itertools;
+
+use crate::settings::{AudioSettings, InputSettings, SampleFormat};
+
+/// Sample formats offered in the UI
+pub const SAMPLE_FORMATS: [SampleFormat; 4] = [
+    SampleFormat::F32,
+    SampleFormat::I16,
+    SampleFormat::U16,
+    SampleFormat::I32,
+];
+
+/// Input or output device of an audio host
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioDevice {
+    pub host: String,
+    pub name: String,
+}
+
+impl Display for AudioDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.host)
+    }
+}
+
+impl AudioSettings {
+    /// Selected output device, the default one when absent
+    pub fn output_device(&self) -> Option<AudioDevice> {
+        Some(AudioDevice {
+            host: self.host.clone()?,
+            name: self.device.clone()?,
+        })
+    }
+}
+
+impl InputSettings {
+    /// Selected input device, the default one when absent
+    pub fn input_device(&self) -> Option<AudioDevice> {
+        Some(AudioDevice {
+            host: self.host.clone()?,
+            name: self.device.clone()?,
+        })
+    }
+}
+
+impl From<SampleFormat> for cpal::SampleFormat {
+    fn from(value: SampleFormat) -> Self {
+        match value {
+            SampleFormat::F32 => cpal::SampleFormat::F32,
+            SampleFormat::I16 => cpal::SampleFormat::I16,
+            SampleFormat::U16 => cpal::SampleFormat::U16,
+            SampleFormat::I32 => cpal::SampleFormat::I32,
+        }
+    }
+}
+
+/// Output devices of all the available hosts
+pub fn output_devices() -> Vec<AudioDevice> {
+    devices(|host| Ok(host.output_devices()?.collect()))
+}
+
+/// Input devices of all the available hosts
+pub fn input_devices() -> Vec<AudioDevice> {
+    devices(|host| Ok(host.input_devices()?.collect()))
+}
+
+/// Devices of all the available hosts listed by `list`
+fn devices(list: impl Fn(&cpal::Host) -> anyhow::Result<Vec<cpal::Device>>) -> Vec<AudioDevice> {
+    cpal::available_hosts()
+        .into_iter()
+        .filter_map(|id| cpal::host_from_id(id).ok())
+        .flat_map(|host| {
+            let devices = match list(&host) {
+                Ok(devices) => devices
+                    .iter()
+                    .filter_map(|device| device.name().ok())
+                    .collect_vec(),
+                Err(err) => {
+                    log::warn!("Failed to list the {} devices: {err}", host.id().name());
+                    vec![]
+                }
+            };
+            devices.into_iter().map(move |name| AudioDevice {
+                host: host.id().name().to_string(),
+                name,
+            })
+        })
+        .collect()
+}
+
+/// Audio host of the given name, the default one when absent
+fn host(name: Option<&String>) -> anyhow::Result<cpal::Host> {
+    match name {
+        Some(name) => cpal::available_hosts()
+            .into_iter()
+            .find(|id| id.name() == name)
+            .with_context(|| format!("Unknown audio host {name}"))
+            .and_then(|id| Ok(cpal::host_from_id(id)?)),
+        None => Ok(cpal::default_host()),
+    }
+}
+
+/// Output device of the settings
+pub fn output_device(settings: &AudioSettings) -> anyhow::Result<cpal::Device> {
+    let host = host(settings.host.as_ref())?;
+    match &settings.device {
+        Some(name) => host
+            .output_devices()?
+            .find(|device| device.name().ok().as_ref() == Some(name))
+            .with_context(|| format!("Unknown audio device {name}")),
+        None => host
+            .default_output_device()
+            .context("No default audio device"),
+    }
+}
+
+/// Input device of the settings
+pub fn input_device(settings: &InputSettings) -> anyhow::Result<cpal::Device> {
+    let host = host(settings.host.as_ref())?;
+    match &settings.device {
+        Some(name) => host
+            .input_devices()?
+            .find(|device| device.name().ok().as_ref() == Some(name))
+            .with_context(|| format!("Unknown audio input {name}")),
+        None => host
+            .default_input_device()
+            .context("No default audio input"),
+    }
+}
+
+/// Stream configuration of the settings supported by the device
+pub fn stream_config(
+    device: &cpal::Device,
+    settings: &AudioSettings,
+) -> anyhow::Result<(cpal::StreamConfig, cpal::SampleFormat)> {
+    let default = device.default_output_config()?;
+    let sample_rate = settings
+        .sample_rate
+        .map_or(default.sample_rate(), cpal::SampleRate);
+    let sample_format = settings
+        .sample_format
+        .map_or(default.sample_format(), Into::into);
+    // The channels of the default configuration are preferred
+    let supported = device
+        .supported_output_configs()?
+        .filter(|config| {
+            config.sample_format() == sample_format
+                && config.min_sample_rate() <= sample_rate
+                && sample_rate <= config.max_sample_rate()
+        })
+        .min_by_key(|config| config.channels().abs_diff(default.channels()))
+        .with_context(|| {
+            format!(
+                "The device does not support {sample_format} samples at {} Hz",
+                sample_rate.0
+            )
+        })?;
+    let mut config = supported.with_sample_rate(sample_rate).config();
+    if let Some(frames) = settings.buffer_size {
+        config.buffer_size = cpal::BufferSize::Fixed(frames);
+    }
+    Ok((config, sample_format))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    fn from_yaml() {
+        let yaml = "{host: ALSA, device: hw:CARD=Scarlett, sample_rate: 48000, buffer_size: 128, sample_format: I16}";
+        let settings: AudioSettings = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(Some(48000), settings.sample_rate);
+        assert_eq!(Some(SampleFormat::I16), settings.sample_format);
+        assert_eq!(
+            Some(AudioDevice {
+                host: "ALSA".to_string(),
+                name: "hw:CARD=Scarlett".to_string()
+            }),
+            settings.output_device()
+        );
+        assert_eq!(None, AudioSettings::default().output_device());
+    }
+
+    #[rstest]
+    fn device_name() {
+        let device = AudioDevice {
+            host: "JACK".to_string(),
+            name: "system".to_string(),
+        };
+        assert_eq!("system (JACK)", device.to_string());
+    }
+}
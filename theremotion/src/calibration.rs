@@ -0,0 +1,254 @@
+use anyhow::{bail, Context, Result};
+use nalgebra::{Vector2, Vector3};
+
+use crate::settings::{Geometry, PitchLayout};
+
+/// Minimal distance between the two positions of a range (mm)
+const MIN_DISTANCE: f32 = 30.0;
+
+/// Reference position asked to the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalibrationStep {
+    Antenna,
+    LowestNote,
+    OneNote,
+    FourNotes,
+    Silent,
+    FullVolume,
+    LowCutoff,
+    HighCutoff,
+    LowResonance,
+    HighResonance,
+}
+
+/// Calibration steps, in order. Each pair of steps defines a range.
+pub const STEPS: [CalibrationStep; 10] = [
+    CalibrationStep::Antenna,
+    CalibrationStep::LowestNote,
+    CalibrationStep::OneNote,
+    CalibrationStep::FourNotes,
+    CalibrationStep::Silent,
+    CalibrationStep::FullVolume,
+    CalibrationStep::LowCutoff,
+    CalibrationStep::HighCutoff,
+    CalibrationStep::LowResonance,
+    CalibrationStep::HighResonance,
+];
+
+impl CalibrationStep {
+    /// Instruction displayed to the player
+    pub fn instruction(&self) -> &'static str {
+        match self {
+            CalibrationStep::Antenna => "Place the pitch hand where the highest note should be",
+            CalibrationStep::LowestNote => "Place the pitch hand where the lowest note should be",
+            CalibrationStep::OneNote => "Lower the pitch hand to where a single note should play",
+            CalibrationStep::FourNotes => "Raise the pitch hand to where four notes should play",
+            CalibrationStep::Silent => "Lower the volume hand to where the sound should be silent",
+            CalibrationStep::FullVolume => {
+                "Raise the volume hand to where the sound should be loudest"
+            }
+            CalibrationStep::LowCutoff => "Bring the volume hand inwards, for the darkest sound",
+            CalibrationStep::HighCutoff => "Move the volume hand outwards, for the brightest sound",
+            CalibrationStep::LowResonance => "Bring the volume hand back, for the lowest resonance",
+            CalibrationStep::HighResonance => {
+                "Move the volume hand forward, for the highest resonance"
+            }
+        }
+    }
+
+    /// The step is captured on the pitch hand, otherwise on the volume hand
+    pub fn is_pitch_hand(&self) -> bool {
+        matches!(
+            self,
+            CalibrationStep::Antenna
+                | CalibrationStep::LowestNote
+                | CalibrationStep::OneNote
+                | CalibrationStep::FourNotes
+        )
+    }
+
+    /// Distance between two positions along the axis measured by this step
+    fn distance(&self, from: &Vector3<f32>, to: &Vector3<f32>) -> f32 {
+        match self {
+            CalibrationStep::Antenna | CalibrationStep::LowestNote => {
+                (Vector2::new(to.x, to.z) - Vector2::new(from.x, from.z)).norm()
+            }
+            CalibrationStep::OneNote
+            | CalibrationStep::FourNotes
+            | CalibrationStep::Silent
+            | CalibrationStep::FullVolume => (to.y - from.y).abs(),
+            CalibrationStep::LowCutoff | CalibrationStep::HighCutoff => (to.x - from.x).abs(),
+            CalibrationStep::LowResonance | CalibrationStep::HighResonance => (to.z - from.z).abs(),
+        }
+    }
+}
+
+/// Guided measurement of the playing positions
+#[derive(Debug, Clone, Default)]
+pub struct Calibration {
+    /// Positions captured so far, from the body (mm)
+    captured: Vec<Vector3<f32>>,
+    /// Last position of the pitch hand, from the body (mm)
+    pitch_hand: Option<Vector3<f32>>,
+    /// Last position of the volume hand, from the body (mm)
+    volume_hand: Option<Vector3<f32>>,
+}
+
+impl Calibration {
+    /// Current step, if the calibration is not over
+    pub fn step(&self) -> Option<CalibrationStep> {
+        STEPS.get(self.captured.len()).copied()
+    }
+
+    /// Index of the current step
+    pub fn step_index(&self) -> usize {
+        self.captured.len()
+    }
+
+    /// Track the position of a hand, from the body
+    pub fn on_hand(&mut self, position_from_body: Vector3<f32>, is_pitch_hand: bool) {
+        if is_pitch_hand {
+            self.pitch_hand = Some(position_from_body);
+        } else {
+            self.volume_hand = Some(position_from_body);
+        }
+    }
+
+    /// Forget the positions of the hands that left the tracking, so that
+    /// they are not captured
+    pub fn on_visible_hands(&mut self, pitch_hand: bool, volume_hand: bool) {
+        if !pitch_hand {
+            self.pitch_hand = None;
+        }
+        if !volume_hand {
+            self.volume_hand = None;
+        }
+    }
+
+    /// Store the current hand position for the current step.
+    ///
+    /// Returns the computed geometry after the last step, for the pitch
+    /// layout.
+    pub fn capture(
+        &mut self,
+        semitones: f32,
+        pitch_layout: &PitchLayout,
+    ) -> Result<Option<Geometry>> {
+        let step = self.step().context("The calibration is over")?;
+        let position = if step.is_pitch_hand() {
+            self.pitch_hand.context("The pitch hand is not visible")?
+        } else {
+            self.volume_hand.context("The volume hand is not visible")?
+        };
+
+        // Second position of a range, check that it is not empty
+        if self.captured.len() % 2 == 1 {
+            let start = self.captured[self.captured.len() - 1];
+            if step.distance(&start, &position) < MIN_DISTANCE {
+                bail!("Too close to the previous position, move further");
+            }
+        }
+
+        self.captured.push(position);
+        if self.step().is_some() {
+            Ok(None)
+        } else {
+            Ok(Some(self.geometry(semitones, pitch_layout)))
+        }
+    }
+
+    /// Geometry from the captured positions
+    ///
+    /// `semitones` is the span of the notes played between the highest and
+    /// lowest note positions. Their distance is measured the way the pitch
+    /// layout does, which puts the lowest note at `semitones *
+    /// mm_per_semitone` from the antenna, except for the octaves layout whose
+    /// widths are kept: only the scale of the pitch plot follows then.
+    fn geometry(&self, semitones: f32, pitch_layout: &PitchLayout) -> Geometry {
+        // The steps are declared in the order of the captures
+        let captured = |step: CalibrationStep| self.captured[step as usize];
+        let antenna = captured(CalibrationStep::Antenna);
+        let lowest = captured(CalibrationStep::LowestNote);
+        let lowest_distance =
+            pitch_layout.distance(Vector2::new(lowest.x - antenna.x, antenna.z - lowest.z));
+        Geometry {
+            antenna_x: antenna.x,
+            antenna_z: antenna.z,
+            mm_per_semitone: lowest_distance.max(1.0) / semitones.max(1.0),
+            pitch_layout: pitch_layout.clone(),
+            chords_height: captured(CalibrationStep::OneNote).y
+                ..=captured(CalibrationStep::FourNotes).y,
+            volume_height: captured(CalibrationStep::Silent).y
+                ..=captured(CalibrationStep::FullVolume).y,
+            cutoff_distance: captured(CalibrationStep::LowCutoff).x
+                ..=captured(CalibrationStep::HighCutoff).x,
+            resonance_depth: captured(CalibrationStep::LowResonance).z
+                ..=captured(CalibrationStep::HighResonance).z,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(PitchLayout::Circular, 20.0)]
+    #[case(PitchLayout::Linear, 12.0)]
+    fn full_calibration(#[case] layout: PitchLayout, #[case] mm_per_semitone: f32) {
+        let mut calibration = Calibration::default();
+        let positions = [
+            Vector3::new(400.0, 300.0, -200.0),
+            Vector3::new(100.0, 300.0, 200.0),
+            Vector3::new(0.0, 350.0, 0.0),
+            Vector3::new(0.0, 500.0, 0.0),
+            Vector3::new(0.0, 300.0, 0.0),
+            Vector3::new(0.0, 400.0, 0.0),
+            Vector3::new(50.0, 0.0, 0.0),
+            Vector3::new(200.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 100.0),
+            Vector3::new(0.0, 0.0, -100.0),
+        ];
+        let mut geometry = None;
+        for (step, position) in STEPS.iter().zip(positions) {
+            assert_eq!(Some(*step), calibration.step());
+            calibration.on_hand(position, step.is_pitch_hand());
+            geometry = calibration.capture(25.0, &layout).unwrap();
+        }
+        assert_eq!(None, calibration.step());
+        assert_eq!(
+            Some(Geometry {
+                antenna_x: 400.0,
+                antenna_z: -200.0,
+                mm_per_semitone,
+                pitch_layout: layout,
+                ..Default::default()
+            }),
+            geometry
+        );
+    }
+
+    #[rstest]
+    fn rejects_empty_range() {
+        let mut calibration = Calibration::default();
+        let layout = PitchLayout::default();
+        assert!(calibration.capture(25.0, &layout).is_err());
+        calibration.on_hand(Vector3::new(400.0, 300.0, -200.0), true);
+        calibration.capture(25.0, &layout).unwrap();
+        calibration.on_hand(Vector3::new(410.0, 300.0, -200.0), true);
+        assert!(calibration.capture(25.0, &layout).is_err());
+        assert_eq!(Some(CalibrationStep::LowestNote), calibration.step());
+    }
+
+    #[rstest]
+    fn hidden_hand_not_captured() {
+        let mut calibration = Calibration::default();
+        calibration.on_hand(Vector3::new(400.0, 300.0, -200.0), true);
+        calibration.on_hand(Vector3::new(0.0, 300.0, 0.0), false);
+        calibration.on_visible_hands(false, true);
+        assert!(calibration.capture(25.0, &PitchLayout::default()).is_err());
+        assert!(calibration.volume_hand.is_some());
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use anyhow::Context;
+
+use crate::{ring::Ring, wav::WavWriter};
+
+/// Seconds of audio buffered for the writer thread
+const BUFFER_SECONDS: u32 = 4;
+
+/// Wait of the writer thread when the buffer is empty
+const WRITE_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Audio output sent to a file being recorded
+///
+/// Dropping it ends the recording once the buffered samples are written.
+pub struct Capture {
+    ring: Arc<Ring>,
+}
+
+impl Capture {
+    /// Record interleaved samples, without blocking
+    ///
+    /// The samples that do not fit in the buffer are lost.
+    pub fn push(&self, samples: &[f32]) {
+        self.ring.push(samples);
+    }
+}
+
+/// Start recording the output to a WAV file
+///
+/// The file is written by its own thread, finished when the [`Capture`] is
+/// dropped.
+pub fn start(
+    path: &Path,
+    channels: u16,
+    sample_rate: u32,
+) -> anyhow::Result<(Capture, thread::JoinHandle<()>)> {
+    let mut wav = WavWriter::create(path, channels, sample_rate)?;
+    let ring = Arc::new(Ring::new(
+        (BUFFER_SECONDS * sample_rate * channels as u32) as usize,
+    ));
+    let capture = Capture { ring: ring.clone() };
+    let path = path.to_path_buf();
+    let writer = thread::Builder::new()
+        .name("audio_recorder".to_string())
+        .spawn(move || {
+            let mut samples = vec![0.0; sample_rate as usize * channels as usize / 10];
+            let written: anyhow::Result<()> = loop {
+                // Alone with the buffer when the capture is dropped
+                let finished = Arc::strong_count(&ring) == 1;
+                let count = ring.pop(&mut samples);
+                if let Err(err) = wav.write(&samples[..count]) {
+                    break Err(err);
+                }
+                if count == 0 {
+                    if finished {
+                        break Ok(());
+                    }
+                    thread::sleep(WRITE_INTERVAL);
+                }
+            };
+            let dropped = ring.dropped();
+            if dropped > 0 {
+                log::warn!("{dropped} samples lost while recording {}", path.display());
+            }
+            match written.and_then(|_| wav.finish()) {
+                Ok(_) => log::info!("Recorded the output to {}", path.display()),
+                Err(err) => log::error!("Failed to record {}: {err:#}", path.display()),
+            }
+        })?;
+    Ok((capture, writer))
+}
+
+/// Name of a recording of the preset started at the date
+fn file_name(date: &str, preset: &str) -> String {
+    let preset: String = preset
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || " -_".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match preset.trim() {
+        "" => format!("{date}.wav"),
+        preset => format!("{date} {preset}.wav"),
+    }
+}
+
+/// File of a new recording of the preset, in the data directory
+pub fn recording_path(preset: &str) -> anyhow::Result<PathBuf> {
+    let directories =
+        directories::ProjectDirs::from("", "", "Theremotion").context("No data directory")?;
+    let date = chrono::Local::now().format("%Y-%m-%d %H-%M-%S").to_string();
+    Ok(directories
+        .data_dir()
+        .join("recordings")
+        .join(file_name(&date, preset)))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("Shiny Diamond", "2024-05-01 20-15-00 Shiny Diamond.wav")]
+    #[case("Lead/Pad: 2", "2024-05-01 20-15-00 Lead_Pad_ 2.wav")]
+    #[case(" ", "2024-05-01 20-15-00.wav")]
+    fn file_names(#[case] preset: &str, #[case] expected: &str) {
+        assert_eq!(expected, file_name("2024-05-01 20-15-00", preset));
+    }
+
+    #[rstest]
+    fn records_until_dropped() {
+        let path = std::env::temp_dir().join("theremotion-capture-test.wav");
+        let (capture, writer) = start(&path, 2, 100).unwrap();
+        capture.push(&[0.5; 300]);
+        drop(capture);
+        writer.join().unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        // Header and 4 bytes samples
+        assert_eq!(58 + 300 * 4, bytes.len());
+    }
+}
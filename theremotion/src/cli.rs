@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::settings::{TrackingSettings, TrackingSourceKind};
+
+/// Command line arguments
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Args {
+    /// Record the hand tracking session to this file
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Replay a recorded hand tracking session instead of reading the Leap Motion
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Restart the replay from the beginning when the session is over
+    #[arg(long = "loop", requires = "replay")]
+    pub replay_loop: bool,
+
+    /// Replay speed factor (2.0 is twice as fast)
+    #[arg(long, value_parser = parse_speed)]
+    pub speed: Option<f64>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Tasks run instead of the instrument
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Render a recorded session to a WAV file, without audio device
+    Render {
+        /// Recorded hand tracking session
+        #[arg(value_name = "SESSION")]
+        session: PathBuf,
+
+        /// Destination WAV file
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+
+        /// Name of the preset played, the current one by default
+        #[arg(long)]
+        preset: Option<String>,
+
+        /// Sample rate of the file (Hz)
+        #[arg(long, default_value_t = 48000)]
+        sample_rate: u32,
+
+        /// Seconds rendered after the end of the session
+        #[arg(long, default_value_t = 2.0)]
+        tail: f64,
+    },
+    /// Import a Scala scale as the tuning of the current preset, and save it
+    ImportScala {
+        /// Scala scale (.scl)
+        #[arg(value_name = "SCALE")]
+        scale: PathBuf,
+
+        /// Keyboard mapping (.kbm), setting the reference frequency and the
+        /// played degrees
+        #[arg(long, value_name = "FILE")]
+        kbm: Option<PathBuf>,
+    },
+}
+
+impl Args {
+    /// Tracking settings with the command line overrides applied
+    pub fn tracking(&self, settings: &TrackingSettings) -> TrackingSettings {
+        let mut settings = settings.clone();
+        if let Some(path) = &self.replay {
+            settings.source = TrackingSourceKind::Replay;
+            settings.replay.path = Some(path.clone());
+            settings.replay.looping = self.replay_loop;
+        }
+        if let Some(speed) = self.speed {
+            settings.replay.speed = speed;
+        }
+        settings
+    }
+}
+
+/// Parse a strictly positive speed factor
+fn parse_speed(s: &str) -> Result<f64, String> {
+    let speed: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if speed > 0.0 {
+        Ok(speed)
+    } else {
+        Err("the speed must be strictly positive".to_string())
+    }
+}
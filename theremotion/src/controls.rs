@@ -76,6 +76,21 @@ pub struct Controls {
 
     /// Guitar volume
     pub mix_pluck_volume: Control,
+
+    /// Drone position from the left (-1) to the right (1)
+    pub mix_drone_pan: Control,
+
+    /// Lead synth position from the left (-1) to the right (1)
+    pub mix_lead_pan: Control,
+
+    /// Guitar position from the left (-1) to the right (1)
+    pub mix_pluck_pan: Control,
+
+    /// Audio input volume
+    pub mix_input_volume: Control,
+
+    /// Lead synth carrying the vocoder instead of playing in the mix
+    pub mix_vocoder: BoolControl,
 }
 
 impl From<&StateHandle> for Controls {
@@ -124,6 +139,11 @@ impl From<&StateHandle> for Controls {
             mix_drone_volume: state.by_path("mix/drone").into(),
             mix_lead_volume: state.by_path("mix/lead").into(),
             mix_pluck_volume: state.by_path("mix/pluck").into(),
+            mix_drone_pan: state.by_path("mix/drone_pan").into(),
+            mix_lead_pan: state.by_path("mix/lead_pan").into(),
+            mix_pluck_pan: state.by_path("mix/pluck_pan").into(),
+            mix_input_volume: state.by_path("mix/input").into(),
+            mix_vocoder: state.by_path("mix/vocoder").into(),
         }
     }
 }
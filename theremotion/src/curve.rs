@@ -0,0 +1,170 @@
+use std::ops::RangeInclusive;
+
+use crate::{controls::convert_range, settings::Curve};
+
+/// Default steepness of the exponential and logarithmic curves
+pub const DEFAULT_STEEPNESS: f32 = 4.0;
+
+/// Default steepness of the S-curve
+pub const DEFAULT_SIGMOID_STEEPNESS: f32 = 8.0;
+
+/// Default number of plateaus of the stepped curve
+pub const DEFAULT_STEPS: u32 = 4;
+
+/// Default breakpoints, a gentle start
+pub const DEFAULT_BREAKPOINTS: [(f32, f32); 3] = [(0.0, 0.0), (0.5, 0.25), (1.0, 1.0)];
+
+impl Curve {
+    /// Shape a normalized value (0-1)
+    pub fn apply(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            Curve::Linear => x,
+            Curve::Exponential { steepness } if steepness.abs() > f32::EPSILON => {
+                (steepness * x).exp_m1() / steepness.exp_m1()
+            }
+            Curve::Logarithmic { steepness } if steepness.abs() > f32::EPSILON => {
+                (x * steepness.exp_m1()).ln_1p() / steepness
+            }
+            Curve::Sigmoid { steepness } if steepness.abs() > f32::EPSILON => {
+                let sigmoid = |x: f32| 1.0 / (1.0 + (-steepness * (x - 0.5)).exp());
+                (sigmoid(x) - sigmoid(0.0)) / (sigmoid(1.0) - sigmoid(0.0))
+            }
+            Curve::Exponential { .. } | Curve::Logarithmic { .. } | Curve::Sigmoid { .. } => x,
+            Curve::Stepped { steps } => {
+                let steps = (*steps).max(2) as f32;
+                (x * steps).floor().min(steps - 1.0) / (steps - 1.0)
+            }
+            Curve::Breakpoints { points } => breakpoints(points, x),
+        }
+    }
+
+    /// Remap a value from a range to another through the curve
+    ///
+    /// The value is clamped to the input range.
+    pub fn convert(
+        &self,
+        value: f32,
+        input_range: &RangeInclusive<f32>,
+        output_range: &RangeInclusive<f32>,
+    ) -> f32 {
+        let x = convert_range(value, input_range, &(0.0..=1.0)).clamp(0.0, 1.0);
+        convert_range(self.apply(x), &(0.0..=1.0), output_range)
+    }
+
+    /// Evenly spaced (input, output) samples of the curve, for display
+    pub fn preview(&self, samples: usize) -> Vec<(f32, f32)> {
+        let samples = samples.max(2);
+        (0..samples)
+            .map(|i| {
+                let x = i as f32 / (samples - 1) as f32;
+                (x, self.apply(x))
+            })
+            .collect()
+    }
+}
+
+/// Linear interpolation in a table of (input, output) points sorted by input
+///
+/// Points sharing an input make a jump, the output being the left one's at
+/// that input.
+fn breakpoints(points: &[(f32, f32)], x: f32) -> f32 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return x;
+    };
+    match points.iter().position(|(px, _)| *px >= x) {
+        None => last.1,
+        Some(0) => first.1,
+        Some(i) => {
+            let (x0, y0) = points[i - 1];
+            let (x1, y1) = points[i];
+            // Never divided by zero, whatever the points
+            if x1 <= x0 {
+                return y0;
+            }
+            convert_range(x, &(x0..=x1), &(y0..=y1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Curve::Linear)]
+    #[case(Curve::Exponential { steepness: DEFAULT_STEEPNESS })]
+    #[case(Curve::Logarithmic { steepness: DEFAULT_STEEPNESS })]
+    #[case(Curve::Sigmoid { steepness: DEFAULT_SIGMOID_STEEPNESS })]
+    #[case(Curve::Stepped { steps: DEFAULT_STEPS })]
+    #[case(Curve::Breakpoints { points: DEFAULT_BREAKPOINTS.to_vec() })]
+    fn bounds(#[case] curve: Curve) {
+        assert!(curve.apply(0.0).abs() < 1e-6);
+        assert!((curve.apply(1.0) - 1.0).abs() < 1e-6);
+        assert_eq!(curve.apply(0.0), curve.apply(-1.0));
+        assert_eq!(curve.apply(1.0), curve.apply(2.0));
+        let preview = curve.preview(50);
+        assert_eq!(50, preview.len());
+        for w in preview.windows(2) {
+            assert!(w[1].1 >= w[0].1);
+        }
+    }
+
+    #[rstest]
+    fn shapes() {
+        assert!(Curve::Exponential { steepness: 4.0 }.apply(0.5) < 0.5);
+        assert!(Curve::Logarithmic { steepness: 4.0 }.apply(0.5) > 0.5);
+        let s_curve = Curve::Sigmoid { steepness: 8.0 };
+        assert!(s_curve.apply(0.25) < 0.25);
+        assert!(s_curve.apply(0.75) > 0.75);
+        assert!((s_curve.apply(0.5) - 0.5).abs() < 1e-6);
+    }
+
+    #[rstest]
+    #[case(0.1, 0.0)]
+    #[case(0.3, 1.0 / 3.0)]
+    #[case(0.6, 2.0 / 3.0)]
+    #[case(0.99, 1.0)]
+    fn stepped(#[case] x: f32, #[case] expected: f32) {
+        assert_eq!(expected, Curve::Stepped { steps: 4 }.apply(x));
+    }
+
+    #[rstest]
+    #[case(vec![], 0.3, 0.3)]
+    #[case(vec![(0.2, 0.5)], 0.9, 0.5)]
+    #[case(vec![(0.2, 0.1), (0.6, 0.9)], 0.0, 0.1)]
+    #[case(vec![(0.2, 0.1), (0.6, 0.9)], 0.4, 0.5)]
+    #[case(vec![(0.2, 0.1), (0.6, 0.9)], 1.0, 0.9)]
+    #[case(vec![(0.0, 0.0), (0.5, 0.2), (0.5, 0.8), (1.0, 1.0)], 0.5, 0.2)]
+    #[case(vec![(0.0, 0.0), (0.5, 0.2), (0.5, 0.8), (1.0, 1.0)], 0.75, 0.9)]
+    fn breakpoints_table(#[case] points: Vec<(f32, f32)>, #[case] x: f32, #[case] expected: f32) {
+        let value = Curve::Breakpoints { points }.apply(x);
+        assert!((expected - value).abs() < 1e-6, "{value}");
+    }
+
+    #[rstest]
+    fn convert_ranges() {
+        let curve = Curve::Stepped { steps: 4 };
+        assert_eq!(2.0, curve.convert(420.0, &(350.0..=500.0), &(1.0..=4.0)));
+        assert_eq!(4.0, curve.convert(600.0, &(350.0..=500.0), &(1.0..=4.0)));
+        assert_eq!(
+            1.0,
+            Curve::Linear.convert(0.0, &(350.0..=500.0), &(1.0..=4.0))
+        );
+    }
+
+    #[rstest]
+    #[case("type: Linear", Curve::Linear)]
+    #[case("type: Exponential", Curve::Exponential { steepness: DEFAULT_STEEPNESS })]
+    #[case("{type: Sigmoid, steepness: 2.0}", Curve::Sigmoid { steepness: 2.0 })]
+    #[case("{type: Stepped, steps: 3}", Curve::Stepped { steps: 3 })]
+    #[case(
+        "{type: Breakpoints, points: [[0.0, 0.0], [1.0, 0.5]]}",
+        Curve::Breakpoints { points: vec![(0.0, 0.0), (1.0, 0.5)] }
+    )]
+    fn from_yaml(#[case] yaml: &str, #[case] expected: Curve) {
+        assert_eq!(expected, serde_yaml::from_str::<Curve>(yaml).unwrap());
+    }
+}
@@ -1,15 +1,17 @@
 use std::f32::consts::PI;
 
 use nalgebra::{UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
 
 const HALF_PI: f32 = PI / 2.0;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum HandType {
     Left,
     Right,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct HandMessage {
     pub hand_type: HandType,
     pub position: Vector3<f32>,
@@ -19,15 +21,21 @@ pub struct HandMessage {
     pub grab: f32,
 }
 
-impl HandMessage {
+impl HandType {
     pub fn x_factor(&self) -> f32 {
-        match self.hand_type {
+        match self {
             // The left hand goes away from the body in the negative x
             HandType::Left => -1.0,
             // The right hand goes away from the body in the positive x
             HandType::Right => 1.0,
         }
     }
+}
+
+impl HandMessage {
+    pub fn x_factor(&self) -> f32 {
+        self.hand_type.x_factor()
+    }
 
     pub fn position_from_body(&self) -> Vector3<f32> {
         Vector3::new(
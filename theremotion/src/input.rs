@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use cpal::{
+    traits::{DeviceTrait, StreamTrait},
+    FromSample, SizedSample,
+};
+
+use crate::{
+    audio,
+    ring::Ring,
+    settings::{InputSettings, InputSource},
+    wav,
+};
+
+/// Seconds of input buffered between the input and output streams
+const BUFFER_SECONDS: f32 = 0.5;
+
+/// Longest delay of the input before catching up with it (s)
+const MAX_LATENCY: f32 = 0.05;
+
+/// Samples converted at once in the input callback
+const CHUNK: usize = 256;
+
+/// Mono samples of the input, read in the audio callback
+pub enum InputReader {
+    /// Live input filled by its stream
+    Stream {
+        ring: Arc<Ring>,
+        /// Samples waiting over which the oldest are skipped
+        max_latency: usize,
+    },
+    /// File played in a loop
+    File { samples: Vec<f32>, position: usize },
+}
+
+impl InputReader {
+    /// Fill the buffer with the next samples, silence when missing
+    pub fn read(&mut self, buffer: &mut [f32]) {
+        match self {
+            InputReader::Stream { ring, max_latency } => {
+                let late = ring.available().saturating_sub(*max_latency + buffer.len());
+                ring.skip(late);
+                let count = ring.pop(buffer);
+                buffer[count..].fill(0.0);
+            }
+            InputReader::File { samples, position } => {
+                if samples.is_empty() {
+                    buffer.fill(0.0);
+                    return;
+                }
+                for sample in buffer {
+                    *sample = samples[*position];
+                    *position = (*position + 1) % samples.len();
+                }
+            }
+        }
+    }
+}
+
+/// Opened audio input
+pub struct Input {
+    /// Goes to the audio callback
+    pub reader: InputReader,
+    /// Stream of the input device, capturing until dropped
+    pub stream: Option<cpal::Stream>,
+}
+
+/// Open the input of the settings, at the sample rate of the output
+pub fn open(settings: &InputSettings, sample_rate: u32) -> anyhow::Result<Option<Input>> {
+    match settings.source {
+        InputSource::None => Ok(None),
+        InputSource::Device => open_device(settings, sample_rate).map(Some),
+        InputSource::File => {
+            let path = settings.file.as_ref().context("No input file")?;
+            let (samples, file_rate) = wav::read_mono(path)?;
+            log::info!("Audio input: {}", path.display());
+            Ok(Some(Input {
+                reader: InputReader::File {
+                    samples: resample(&samples, file_rate, sample_rate),
+                    position: 0,
+                },
+                stream: None,
+            }))
+        }
+    }
+}
+
+fn open_device(settings: &InputSettings, sample_rate: u32) -> anyhow::Result<Input> {
+    let device = audio::input_device(settings)?;
+    let default = device.default_input_config()?;
+    let rate = cpal::SampleRate(sample_rate);
+    // The sample format of the default configuration is preferred
+    let supported = device
+        .supported_input_configs()?
+        .filter(|config| config.min_sample_rate() <= rate && rate <= config.max_sample_rate())
+        .min_by_key(|config| {
+            (
+                config.sample_format() != default.sample_format(),
+                config.channels(),
+            )
+        })
+        .with_context(|| format!("The audio input does not support {sample_rate} Hz"))?
+        .with_sample_rate(rate);
+    let sample_format = supported.sample_format();
+    let config = supported.config();
+    log::info!(
+        "Audio input: {}, {} channels, {sample_format}",
+        device.name().unwrap_or_default(),
+        config.channels
+    );
+    let ring = Arc::new(Ring::new((BUFFER_SECONDS * sample_rate as f32) as usize));
+    let stream = match sample_format {
+        cpal::SampleFormat::F32 => build_input_stream::<f32>(&device, &config, &ring),
+        cpal::SampleFormat::I16 => build_input_stream::<i16>(&device, &config, &ring),
+        cpal::SampleFormat::U16 => build_input_stream::<u16>(&device, &config, &ring),
+        cpal::SampleFormat::I32 => build_input_stream::<i32>(&device, &config, &ring),
+        format => anyhow::bail!("Unsupported input sample format {format}"),
+    }?;
+    stream.play()?;
+    Ok(Input {
+        reader: InputReader::Stream {
+            ring,
+            max_latency: (MAX_LATENCY * sample_rate as f32) as usize,
+        },
+        stream: Some(stream),
+    })
+}
+
+/// Start a stream mixing the input frames down to the ring
+fn build_input_stream<S>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    ring: &Arc<Ring>,
+) -> anyhow::Result<cpal::Stream>
+where
+    S: SizedSample,
+    f32: FromSample<S>,
+{
+    let ring = ring.clone();
+    let channels = config.channels.max(1) as usize;
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[S], _: &cpal::InputCallbackInfo| {
+            let mut mono = [0.0; CHUNK];
+            for frames in data.chunks(CHUNK * channels) {
+                let mono = &mut mono[..frames.len() / channels];
+                for (sample, frame) in mono.iter_mut().zip(frames.chunks_exact(channels)) {
+                    *sample =
+                        frame.iter().map(|s| s.to_sample::<f32>()).sum::<f32>() / channels as f32;
+                }
+                ring.push(mono);
+            }
+        },
+        |err| log::error!("an error occurred on the input audio stream: {err}"),
+        None,
+    )?;
+    Ok(stream)
+}
+
+/// Change the sample rate of the samples, by linear interpolation
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() || from == 0 {
+        return samples.to_vec();
+    }
+    let step = from as f64 / to as f64;
+    let length = (samples.len() as f64 / step).floor() as usize;
+    (0..length)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let next = samples[(index + 1).min(samples.len() - 1)];
+            let fraction = (position - index as f64) as f32;
+            samples[index] + (next - samples[index]) * fraction
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(2, 2, vec![0.0, 1.0, 0.0, 1.0])]
+    #[case(2, 4, vec![0.0, 0.5, 1.0, 0.5, 0.0, 0.5, 1.0, 1.0])]
+    #[case(2, 1, vec![0.0, 0.0])]
+    fn resampling(#[case] from: u32, #[case] to: u32, #[case] expected: Vec<f32>) {
+        assert_eq!(expected, resample(&[0.0, 1.0, 0.0, 1.0], from, to));
+    }
+
+    #[rstest]
+    fn file_loops() {
+        let mut reader = InputReader::File {
+            samples: vec![1.0, 2.0, 3.0],
+            position: 0,
+        };
+        let mut buffer = [0.0; 5];
+        reader.read(&mut buffer);
+        assert_eq!([1.0, 2.0, 3.0, 1.0, 2.0], buffer);
+    }
+
+    #[rstest]
+    fn stream_catches_up() {
+        let ring = Arc::new(Ring::new(16));
+        let mut reader = InputReader::Stream {
+            ring: ring.clone(),
+            max_latency: 2,
+        };
+        ring.push(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let mut buffer = [0.0; 2];
+        reader.read(&mut buffer);
+        // Two samples late at most
+        assert_eq!([3.0, 4.0], buffer);
+        reader.read(&mut buffer);
+        assert_eq!([5.0, 6.0], buffer);
+        // Silence when the input is missing
+        reader.read(&mut buffer);
+        assert_eq!([0.0, 0.0], buffer);
+    }
+}
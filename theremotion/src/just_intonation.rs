@@ -0,0 +1,146 @@
+use crate::{
+    settings::{JustIntonation, JustLimit, JustReference},
+    IntervalF, MidiNoteF,
+};
+
+/// Ratios of the twelve semitones above the reference, 5-limit
+const FIVE_LIMIT: [(u32, u32); 12] = [
+    (1, 1),
+    (16, 15),
+    (9, 8),
+    (6, 5),
+    (5, 4),
+    (4, 3),
+    (45, 32),
+    (3, 2),
+    (8, 5),
+    (5, 3),
+    (9, 5),
+    (15, 8),
+];
+
+/// Ratios of the twelve semitones above the reference, 7-limit
+const SEVEN_LIMIT: [(u32, u32); 12] = [
+    (1, 1),
+    (16, 15),
+    (9, 8),
+    (6, 5),
+    (5, 4),
+    (4, 3),
+    (7, 5),
+    (3, 2),
+    (8, 5),
+    (5, 3),
+    (7, 4),
+    (15, 8),
+];
+
+impl JustLimit {
+    fn ratios(&self) -> &'static [(u32, u32); 12] {
+        match self {
+            JustLimit::FiveLimit => &FIVE_LIMIT,
+            JustLimit::SevenLimit => &SEVEN_LIMIT,
+        }
+    }
+
+    /// Difference between the pure and the equal-tempered semitone (in
+    /// semitones)
+    fn semitone_deviation(&self, semitone: i32) -> f32 {
+        let (numerator, denominator) = self.ratios()[semitone.rem_euclid(12) as usize];
+        12.0 * (numerator as f32 / denominator as f32).log2() - semitone.rem_euclid(12) as f32
+    }
+
+    /// Difference between the pure and the equal-tempered interval
+    ///
+    /// The deviation is interpolated between two semitones, so that sliding
+    /// notes stay continuous.
+    pub fn deviation(&self, interval: IntervalF) -> IntervalF {
+        let lower = interval.semitones().floor();
+        let factor = interval.semitones() - lower;
+        let lower = lower as i32;
+        IntervalF(
+            self.semitone_deviation(lower) * (1.0 - factor)
+                + self.semitone_deviation(lower + 1) * factor,
+        )
+    }
+}
+
+impl JustIntonation {
+    /// Pure note of an equal-tempered note, relative to a reference
+    pub fn retune(&self, reference: MidiNoteF, note: MidiNoteF) -> MidiNoteF {
+        note + self.limit.deviation(note - reference)
+    }
+
+    /// Pure notes of a chord, the first note being the played one
+    pub fn retune_chord(
+        &self,
+        root: MidiNoteF,
+        chord: [Option<MidiNoteF>; 4],
+    ) -> [Option<MidiNoteF>; 4] {
+        let reference = match (self.reference, chord[0]) {
+            (JustReference::Root, _) => root,
+            (JustReference::PlayedNote, Some(played)) => played,
+            (JustReference::PlayedNote, None) => return chord,
+        };
+        chord.map(|note| note.map(|note| self.retune(reference, note)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    /// Cents above the reference
+    fn cents(just_intonation: &JustIntonation, reference: f32, note: f32) -> f32 {
+        let retuned = just_intonation.retune(MidiNoteF(reference), MidiNoteF(note));
+        (retuned.note() - reference) * 100.0
+    }
+
+    #[rstest]
+    #[case(JustLimit::FiveLimit, 0.0, 0.0)]
+    #[case(JustLimit::FiveLimit, 4.0, 386.31)]
+    #[case(JustLimit::FiveLimit, 7.0, 701.96)]
+    #[case(JustLimit::FiveLimit, 10.0, 1017.6)]
+    #[case(JustLimit::FiveLimit, 16.0, 1586.31)]
+    #[case(JustLimit::SevenLimit, 10.0, 968.83)]
+    #[case(JustLimit::SevenLimit, 6.0, 582.51)]
+    #[case(JustLimit::SevenLimit, -5.0, -498.04)]
+    fn pure_intervals(#[case] limit: JustLimit, #[case] semitones: f32, #[case] expected: f32) {
+        let just_intonation = JustIntonation {
+            limit,
+            ..Default::default()
+        };
+        let cents = cents(&just_intonation, 60.0, 60.0 + semitones);
+        assert!((expected - cents).abs() < 0.1, "{cents}");
+    }
+
+    #[rstest]
+    fn sliding_is_continuous() {
+        let limit = JustLimit::FiveLimit;
+        let mut previous = limit.deviation(IntervalF(0.0)).semitones();
+        for step in 1..=1200 {
+            let deviation = limit.deviation(IntervalF(step as f32 / 100.0)).semitones();
+            assert!((deviation - previous).abs() < 0.01, "{step}");
+            previous = deviation;
+        }
+    }
+
+    #[rstest]
+    #[case(JustReference::Root, [63.86, 67.02, 70.88, 75.86])]
+    #[case(JustReference::PlayedNote, [64.0, 67.16, 71.02, 76.0])]
+    fn chord(#[case] reference: JustReference, #[case] expected: [f32; 4]) {
+        let just_intonation = JustIntonation {
+            reference,
+            ..Default::default()
+        };
+        // E minor triad over a C root
+        let chord = [64.0, 67.0, 71.0, 76.0].map(|note| Some(MidiNoteF(note)));
+        let retuned = just_intonation.retune_chord(MidiNoteF(60.0), chord);
+        for (expected, note) in expected.into_iter().zip(retuned) {
+            let note = note.unwrap().note();
+            assert!((expected - note).abs() < 0.01, "{retuned:?}");
+        }
+    }
+}
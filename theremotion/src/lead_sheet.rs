@@ -0,0 +1,239 @@
+use anyhow::{bail, Context};
+use staff::{scale::ScaleIntervals, Interval, Pitch};
+
+use crate::settings::{ChordTarget, LeadSheet, Preset};
+
+/// Semitones of the scales played over the chords
+const IONIAN: &[u8] = &[0, 2, 4, 5, 7, 9, 11];
+const DORIAN: &[u8] = &[0, 2, 3, 5, 7, 9, 10];
+const MIXOLYDIAN: &[u8] = &[0, 2, 4, 5, 7, 9, 10];
+const MELODIC_MINOR: &[u8] = &[0, 2, 3, 5, 7, 9, 11];
+const LOCRIAN: &[u8] = &[0, 1, 3, 5, 6, 8, 10];
+const DIMINISHED: &[u8] = &[0, 2, 3, 5, 6, 8, 9, 11];
+const WHOLE_TONE: &[u8] = &[0, 2, 4, 6, 8, 10];
+
+/// Chord qualities: suffixes of the symbol, semitones of the chord tones and
+/// of the chord scale from the root
+const QUALITIES: &[(&[&str], &[u8], &[u8])] = &[
+    (&["", "M", "maj"], &[0, 4, 7], IONIAN),
+    (&["6", "M6", "maj6"], &[0, 4, 7, 9], IONIAN),
+    (
+        &["maj7", "M7", "Δ", "Δ7", "maj9", "M9"],
+        &[0, 4, 7, 11],
+        IONIAN,
+    ),
+    (&["7", "9", "13"], &[0, 4, 7, 10], MIXOLYDIAN),
+    (&["7sus4", "7sus", "9sus4"], &[0, 5, 7, 10], MIXOLYDIAN),
+    (&["sus4", "sus"], &[0, 5, 7], MIXOLYDIAN),
+    (&["sus2"], &[0, 2, 7], IONIAN),
+    (&["m", "-", "min"], &[0, 3, 7], DORIAN),
+    (&["m6", "-6", "min6"], &[0, 3, 7, 9], DORIAN),
+    (
+        &["m7", "-7", "min7", "m9", "-9", "m11"],
+        &[0, 3, 7, 10],
+        DORIAN,
+    ),
+    (
+        &["mM7", "mmaj7", "-maj7", "m(maj7)"],
+        &[0, 3, 7, 11],
+        MELODIC_MINOR,
+    ),
+    (&["m7b5", "-7b5", "ø", "ø7"], &[0, 3, 6, 10], LOCRIAN),
+    (&["dim", "°", "o"], &[0, 3, 6], DIMINISHED),
+    (&["dim7", "°7", "o7"], &[0, 3, 6, 9], DIMINISHED),
+    (&["aug", "+"], &[0, 4, 8], WHOLE_TONE),
+];
+
+/// Chord of a lead sheet, like `Dm7`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChordSymbol {
+    /// Symbol as written in the chart
+    pub name: String,
+    pub root: Pitch,
+    /// Semitones of the chord tones from the root
+    pub tones: &'static [u8],
+    /// Semitones of the scale played over the chord from the root
+    pub scale: &'static [u8],
+}
+
+/// Chord of a chart with its length
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartChord {
+    pub symbol: ChordSymbol,
+    /// Length in bars of the tempo
+    pub bars: f32,
+}
+
+/// Read a chord symbol, the bass note of slash chords is ignored
+pub fn parse_chord(symbol: &str) -> anyhow::Result<ChordSymbol> {
+    let chord = symbol.split('/').next().unwrap_or_default();
+    let mut chars = chord.chars();
+    let natural: u8 = match chars.next() {
+        Some('C') => 0,
+        Some('D') => 2,
+        Some('E') => 4,
+        Some('F') => 5,
+        Some('G') => 7,
+        Some('A') => 9,
+        Some('B') => 11,
+        _ => bail!("Unknown root note in chord {symbol}"),
+    };
+    let rest = chars.as_str();
+    let (root, suffix) = if let Some(suffix) = rest.strip_prefix(['#', '♯']) {
+        (natural + 1, suffix)
+    } else if let Some(suffix) = rest.strip_prefix(['b', '♭']) {
+        (natural + 11, suffix)
+    } else {
+        (natural, rest)
+    };
+    let (_, tones, scale) = QUALITIES
+        .iter()
+        .find(|(suffixes, _, _)| suffixes.contains(&suffix))
+        .with_context(|| format!("Unknown chord quality in chord {symbol}"))?;
+    Ok(ChordSymbol {
+        name: symbol.to_string(),
+        root: Pitch::from_byte(root % 12),
+        tones,
+        scale,
+    })
+}
+
+/// Read a chord chart like `| Dm7 G7 | Cmaj7 | % |`
+///
+/// The chords of a bar share it evenly, `%` repeats the previous bar.
+pub fn parse_chart(chart: &str) -> anyhow::Result<Vec<ChartChord>> {
+    let mut chords = vec![];
+    let mut previous: Vec<ChordSymbol> = vec![];
+    for bar in chart
+        .split('|')
+        .map(str::trim)
+        .filter(|bar| !bar.is_empty())
+    {
+        let symbols = if bar == "%" {
+            if previous.is_empty() {
+                bail!("No bar to repeat at the start of the chart");
+            }
+            previous
+        } else {
+            bar.split_whitespace()
+                .map(parse_chord)
+                .collect::<anyhow::Result<Vec<_>>>()?
+        };
+        let bars = 1.0 / symbols.len() as f32;
+        chords.extend(symbols.iter().map(|symbol| ChartChord {
+            symbol: symbol.clone(),
+            bars,
+        }));
+        previous = symbols;
+    }
+    if chords.is_empty() {
+        bail!("The chart has no chord");
+    }
+    Ok(chords)
+}
+
+impl Preset {
+    /// Preset playing over a chord of the lead sheet
+    ///
+    /// The root pitch stays the bottom of the playing range, the scale and the
+    /// drone are the notes of the chord from it.
+    pub fn for_chord(&self, lead_sheet: &LeadSheet, chord: &ChordSymbol) -> Preset {
+        let offset = (chord.root.into_byte() + 12 - self.pitch.into_byte()) % 12;
+        let semitones = match lead_sheet.target {
+            ChordTarget::ChordTones => chord.tones,
+            ChordTarget::ChordScale => chord.scale,
+        };
+        let mut preset = self.clone();
+        preset.scale = ScaleIntervals::from_iter(
+            semitones
+                .iter()
+                .map(|semitones| Interval::new((offset + semitones) % 12)),
+        );
+        preset.descending_scale = None;
+        if lead_sheet.drone {
+            let mut intervals = [None; 4];
+            for (interval, semitones) in intervals.iter_mut().zip(chord.tones) {
+                *interval = Some(Interval::new(offset + semitones));
+            }
+            preset.drone.intervals = intervals;
+            preset.drone.progression = None;
+        }
+        preset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("C", Pitch::C, &[0, 4, 7])]
+    #[case("Dm7", Pitch::D, &[0, 3, 7, 10])]
+    #[case("G7", Pitch::G, &[0, 4, 7, 10])]
+    #[case("Bbmaj7", Pitch::ASharp, &[0, 4, 7, 11])]
+    #[case("F#ø", Pitch::FSharp, &[0, 3, 6, 10])]
+    #[case("Ebdim7", Pitch::DSharp, &[0, 3, 6, 9])]
+    #[case("C/E", Pitch::C, &[0, 4, 7])]
+    #[case("Cb", Pitch::B, &[0, 4, 7])]
+    fn chords(#[case] symbol: &str, #[case] root: Pitch, #[case] tones: &[u8]) {
+        let chord = parse_chord(symbol).unwrap();
+        assert_eq!(root, chord.root);
+        assert_eq!(tones, chord.tones);
+        assert_eq!(symbol, chord.name);
+    }
+
+    #[rstest]
+    #[case("H7")]
+    #[case("Cxyz")]
+    #[case("")]
+    fn unknown_chords(#[case] symbol: &str) {
+        assert!(parse_chord(symbol).is_err());
+    }
+
+    #[rstest]
+    fn chart() {
+        let chords = parse_chart("| Dm7 | G7 |\n| Cmaj7 A7 | % |").unwrap();
+        let names = chords
+            .iter()
+            .map(|c| c.symbol.name.as_str())
+            .collect::<Vec<_>>();
+        let bars = chords.iter().map(|c| c.bars).collect::<Vec<_>>();
+        assert_eq!(["Dm7", "G7", "Cmaj7", "A7", "Cmaj7", "A7"], names[..]);
+        assert_eq!([1.0, 1.0, 0.5, 0.5, 0.5, 0.5], bars[..]);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("| |")]
+    #[case("| % | C |")]
+    #[case("| Dm7 | Q |")]
+    fn invalid_charts(#[case] chart: &str) {
+        assert!(parse_chart(chart).is_err());
+    }
+
+    #[rstest]
+    fn preset_for_chord() {
+        let preset = Preset::default();
+        let lead_sheet = LeadSheet::default();
+        let chord = parse_chord("G7").unwrap();
+        let playing = preset.for_chord(&lead_sheet, &chord);
+        assert_eq!(Pitch::C, playing.pitch);
+        let scale = playing.scale.map(|i| i.semitones()).collect::<Vec<_>>();
+        assert_eq!([2, 5, 7, 11], scale[..]);
+        assert_eq!(
+            [Some(7), Some(11), Some(14), Some(17)],
+            playing.drone.intervals.map(|i| i.map(|i| i.semitones()))
+        );
+
+        let scale_target = LeadSheet {
+            target: ChordTarget::ChordScale,
+            drone: false,
+            ..Default::default()
+        };
+        let playing = preset.for_chord(&scale_target, &chord);
+        assert_eq!(7, playing.scale.count());
+        assert_eq!(preset.drone, playing.drone);
+    }
+}
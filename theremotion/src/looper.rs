@@ -0,0 +1,319 @@
+/// Longest loop (s)
+const MAX_LOOP_DURATION: u32 = 120;
+/// Most layers played together
+const MAX_LAYERS: usize = 16;
+/// Interleaved sides of the looped sound
+const CHANNELS: u32 = 2;
+
+/// Buffer of the layer a record command starts in the given status, if any
+///
+/// It is allocated out of the audio callback, before the layer starts. The
+/// first layer holds the longest loop and the overdubs the loop length, the
+/// command only ends the recording in the other phases.
+pub fn new_layer(status: &LooperStatus, sample_rate: u32) -> Option<Vec<f32>> {
+    match status.phase {
+        LooperPhase::Empty => Some(vec![
+            0.0;
+            (sample_rate * MAX_LOOP_DURATION * CHANNELS).max(CHANNELS)
+                as usize
+        ]),
+        LooperPhase::Playing if status.layers < MAX_LAYERS => Some(vec![0.0; status.len]),
+        LooperPhase::Playing | LooperPhase::Recording | LooperPhase::Overdubbing => None,
+    }
+}
+
+/// Action on the looper
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LooperCommand {
+    /// Record the first layer and close the loop, then start and stop the
+    /// overdubs
+    Record,
+    /// Remove the last layer, or the one being recorded
+    Undo,
+    /// Remove all the layers
+    Clear,
+}
+
+/// What the looper is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LooperPhase {
+    /// Nothing recorded
+    #[default]
+    Empty,
+    /// Recording the first layer, setting the loop length
+    Recording,
+    /// Playing the layers
+    Playing,
+    /// Playing the layers and recording a new one over them
+    Overdubbing,
+}
+
+/// State of the looper shown to the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LooperStatus {
+    pub phase: LooperPhase,
+    /// Number of recorded layers, including the one being recorded
+    pub layers: usize,
+    /// Length of the loop once the first layer is recorded (samples)
+    pub len: usize,
+}
+
+/// Audio loop played under the live sound, built up with overdub layers
+///
+/// Each layer only holds the live sound of its recording, so undoing it leaves
+/// the other layers untouched. It runs in the audio callback, so it never
+/// allocates nor frees: the buffers of the layers are given with the commands,
+/// and handed back when they are removed.
+pub struct Looper {
+    /// Recorded layers, the first one sets the loop length
+    layers: Vec<Vec<f32>>,
+    /// Length of the loop, or of the first layer while recording (samples)
+    len: usize,
+    /// What the looper is doing
+    phase: LooperPhase,
+    /// Position in the loop (samples)
+    position: usize,
+}
+
+impl Default for Looper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Looper {
+    pub fn new() -> Self {
+        Self {
+            layers: Vec::with_capacity(MAX_LAYERS),
+            len: 0,
+            phase: LooperPhase::Empty,
+            position: 0,
+        }
+    }
+
+    pub fn status(&self) -> LooperStatus {
+        LooperStatus {
+            phase: self.phase,
+            layers: self.layers.len(),
+            // Growing while the first layer is recorded
+            len: match self.phase {
+                LooperPhase::Empty | LooperPhase::Recording => 0,
+                LooperPhase::Playing | LooperPhase::Overdubbing => self.len,
+            },
+        }
+    }
+
+    /// Apply a command, with the zeroed buffer of the layer it may start
+    ///
+    /// The buffers no longer used, including the given one, are handed to
+    /// `release`.
+    pub fn command(
+        &mut self,
+        command: LooperCommand,
+        layer: Option<Vec<f32>>,
+        mut release: impl FnMut(Vec<f32>),
+    ) {
+        let layer = match (command, self.phase, layer) {
+            (LooperCommand::Record, LooperPhase::Empty, Some(layer)) if !layer.is_empty() => {
+                self.layers.push(layer);
+                self.len = 0;
+                self.phase = LooperPhase::Recording;
+                None
+            }
+            (LooperCommand::Record, LooperPhase::Recording, layer) => {
+                self.close_loop(&mut release);
+                layer
+            }
+            (LooperCommand::Record, LooperPhase::Playing, Some(layer))
+                if layer.len() >= self.len && self.layers.len() < MAX_LAYERS =>
+            {
+                self.layers.push(layer);
+                self.phase = LooperPhase::Overdubbing;
+                None
+            }
+            (LooperCommand::Record, LooperPhase::Overdubbing, layer) => {
+                self.phase = LooperPhase::Playing;
+                layer
+            }
+            (LooperCommand::Undo, LooperPhase::Empty, layer) => layer,
+            (LooperCommand::Undo, _, layer) => {
+                if let Some(undone) = self.layers.pop() {
+                    release(undone);
+                }
+                self.phase = match self.layers.is_empty() {
+                    true => LooperPhase::Empty,
+                    false => LooperPhase::Playing,
+                };
+                layer
+            }
+            (LooperCommand::Clear, _, layer) => {
+                self.layers.drain(..).for_each(&mut release);
+                self.phase = LooperPhase::Empty;
+                layer
+            }
+            // Without a buffer, or too many layers
+            (LooperCommand::Record, _, layer) => layer,
+        };
+        if let Some(layer) = layer {
+            release(layer);
+        }
+    }
+
+    /// End the recording of the first layer and play it
+    fn close_loop(&mut self, release: impl FnMut(Vec<f32>)) {
+        self.position = 0;
+        self.phase = match self.len {
+            0 => {
+                self.layers.drain(..).for_each(release);
+                LooperPhase::Empty
+            }
+            _ => LooperPhase::Playing,
+        };
+    }
+
+    /// Record the live samples and add the loop to them
+    ///
+    /// The samples are the interleaved sides of the output, whole frames are
+    /// given so that the loop keeps them in place.
+    pub fn process(&mut self, samples: &mut [f32], mut release: impl FnMut(Vec<f32>)) {
+        for sample in samples {
+            match self.phase {
+                LooperPhase::Empty => return,
+                LooperPhase::Recording => {
+                    let Some(layer) = self.layers.last_mut() else {
+                        return;
+                    };
+                    layer[self.len] = *sample;
+                    self.len += 1;
+                    if self.len >= layer.len() {
+                        self.close_loop(&mut release);
+                    }
+                }
+                LooperPhase::Playing | LooperPhase::Overdubbing => {
+                    let live = *sample;
+                    *sample += self
+                        .layers
+                        .iter()
+                        .map(|layer| layer[self.position])
+                        .sum::<f32>();
+                    if self.phase == LooperPhase::Overdubbing {
+                        if let Some(layer) = self.layers.last_mut() {
+                            layer[self.position] += live;
+                        }
+                    }
+                    self.position = (self.position + 1) % self.len;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn play(looper: &mut Looper, live: &[f32]) -> Vec<f32> {
+        let mut samples = live.to_vec();
+        looper.process(&mut samples, |_| panic!("Layer released while playing"));
+        samples
+    }
+
+    /// Apply the command with a new layer, returning the lengths of the
+    /// released buffers
+    fn command(looper: &mut Looper, command: LooperCommand, max_len: usize) -> Vec<usize> {
+        let mut released = vec![];
+        looper.command(command, Some(vec![0.0; max_len]), |layer| {
+            released.push(layer.len())
+        });
+        released
+    }
+
+    #[rstest]
+    fn record_and_play() {
+        let mut looper = Looper::new();
+        assert!(command(&mut looper, LooperCommand::Record, 100).is_empty());
+        assert_eq!(vec![1.0, 2.0, 3.0], play(&mut looper, &[1.0, 2.0, 3.0]));
+        assert_eq!(vec![100], command(&mut looper, LooperCommand::Record, 100));
+        assert_eq!(
+            vec![1.0, 2.0, 3.0, 1.5],
+            play(&mut looper, &[0.0, 0.0, 0.0, 0.5])
+        );
+        assert_eq!(
+            LooperStatus {
+                phase: LooperPhase::Playing,
+                layers: 1,
+                len: 3,
+            },
+            looper.status()
+        );
+    }
+
+    #[rstest]
+    fn overdub_and_undo() {
+        let mut looper = Looper::new();
+        command(&mut looper, LooperCommand::Record, 100);
+        play(&mut looper, &[1.0, 2.0]);
+        command(&mut looper, LooperCommand::Record, 100);
+        assert!(command(&mut looper, LooperCommand::Record, 100).is_empty());
+        assert_eq!(LooperPhase::Overdubbing, looper.status().phase);
+        // The overdub does not hear itself before the next cycle
+        assert_eq!(vec![11.0, 12.0], play(&mut looper, &[10.0, 10.0]));
+        command(&mut looper, LooperCommand::Record, 100);
+        assert_eq!(vec![11.0, 12.0], play(&mut looper, &[0.0, 0.0]));
+        assert_eq!(2, looper.status().layers);
+        // The undone layer is handed back with the unused buffer
+        assert_eq!(
+            vec![100, 100],
+            command(&mut looper, LooperCommand::Undo, 100)
+        );
+        assert_eq!(vec![1.0, 2.0], play(&mut looper, &[0.0, 0.0]));
+        command(&mut looper, LooperCommand::Undo, 100);
+        assert_eq!(LooperPhase::Empty, looper.status().phase);
+        assert_eq!(vec![0.0, 0.0], play(&mut looper, &[0.0, 0.0]));
+    }
+
+    #[rstest]
+    fn longest_loop() {
+        let mut looper = Looper::new();
+        command(&mut looper, LooperCommand::Record, 3);
+        assert_eq!(
+            vec![1.0, 1.0, 1.0, 1.0, 0.0],
+            play(&mut looper, &[1.0, 1.0, 1.0, 0.0, -1.0])
+        );
+        assert_eq!(LooperPhase::Playing, looper.status().phase);
+        assert_eq!(vec![3, 3], command(&mut looper, LooperCommand::Clear, 3));
+        assert_eq!(LooperStatus::default(), looper.status());
+    }
+
+    #[rstest]
+    fn overdub_needs_the_loop_length() {
+        let mut looper = Looper::new();
+        command(&mut looper, LooperCommand::Record, 100);
+        play(&mut looper, &[1.0, 2.0, 3.0]);
+        command(&mut looper, LooperCommand::Record, 100);
+        // Buffer allocated for a lower sample rate
+        assert_eq!(vec![2], command(&mut looper, LooperCommand::Record, 2));
+        assert_eq!(LooperPhase::Playing, looper.status().phase);
+        looper.command(LooperCommand::Record, None, |_| {});
+        assert_eq!(1, looper.status().layers);
+    }
+
+    #[rstest]
+    fn layer_sizes() {
+        let mut looper = Looper::new();
+        let layer = new_layer(&looper.status(), 100);
+        assert_eq!(
+            Some(100 * MAX_LOOP_DURATION as usize * 2),
+            layer.as_ref().map(Vec::len)
+        );
+        looper.command(LooperCommand::Record, layer, |_| panic!("Layer released"));
+        play(&mut looper, &[1.0, 2.0, 3.0, 4.0]);
+        // Closing the loop needs no buffer
+        assert_eq!(None, new_layer(&looper.status(), 100));
+        looper.command(LooperCommand::Record, None, |_| {});
+        // The overdubs hold the loop
+        assert_eq!(Some(vec![0.0; 4]), new_layer(&looper.status(), 100));
+    }
+}
@@ -5,9 +5,48 @@
 #![cfg_attr(not(feature = "leap"), allow(dead_code))] // When building without leap support for tests, allow dead code
 #![cfg_attr(not(feature = "leap"), allow(unused_variables))] // When building without leap support for tests, allow dead code
 
+/// Chord voices played one after the other
+mod arpeggiator;
+
+/// Audio output devices and stream configuration
+mod audio;
+
+/// Guided measurement of the playing positions
+mod calibration;
+
+/// Recording of the audio output to WAV files
+mod capture;
+
+/// Command line arguments
+mod cli;
+
+/// Response curves of the hand mappings
+mod curve;
+
 /// DSP controllable parameters
 mod controls;
 
+/// Pure ratios of the chord and drone voices
+mod just_intonation;
+
+/// Chord charts switching the scale and the drone
+mod lead_sheet;
+
+/// Loop of the output played under the live sound
+mod looper;
+
+/// Data-driven mapping of the hands to the DSP controls
+mod modulation;
+
+/// Placement of the notes around the pitch antenna
+mod pitch_layout;
+
+/// Drone chords following one another
+mod progression;
+
+/// Concert pitch and fine transposition of the notes
+mod reference_pitch;
+
 /// Thread transforming and dispatching the messages from the others
 mod thread_conductor;
 
@@ -20,23 +59,64 @@ mod thread_leap;
 /// Mod creating the main window and event loop
 mod thread_ui;
 
+/// User scripts driving custom play logic
+mod script;
+
+/// Offline rendering of the recorded sessions
+mod render;
+
+/// Lock-free exchange of samples with the audio callbacks
+mod ring;
+
+/// Recording and replay of hand tracking sessions
+mod session;
+
 /// Application settings
 mod settings;
 
+/// Stereo image of the output
+mod stereo;
+
+/// Hand tracking sources
+mod tracking;
+
+/// Filtering of the hand tracking data
+mod smoothing;
+
 /// Music related types and algorithms
 mod solfege;
 
 /// Newtypes for strongly typed exchanges
 mod types;
 
+/// Tempo clock and tap tempo
+mod tempo;
+
+/// Microtonal tunings and Scala files
+mod tuning;
+
+/// Audio input processed by the instrument
+mod input;
+
+/// Chords played by the pitch hand
+mod voicing;
+
+/// Channel vocoder of the audio input
+mod vocoder;
+
+/// WAV files of the rendered, recorded and input audio
+mod wav;
+
 mod hand;
 
 pub use hand::*;
 
 pub use types::*;
 
+use clap::Parser;
 use default_boxed::DefaultBoxed;
 use faust_state::DspHandle;
+use session::Recorder;
 use settings::Settings;
 use theremotion_ui::*;
 
@@ -47,8 +127,51 @@ fn main() {
     // Log to stdout (if you run with `RUST_LOG=debug`).
     tracing_subscriber::fmt::init();
 
+    let args = cli::Args::parse();
+
     // Read application settings
-    let settings = Settings::read();
+    let mut settings = Settings::read();
+
+    if let Some(cli::Command::Render {
+        session,
+        output,
+        preset,
+        sample_rate,
+        tail,
+    }) = &args.command
+    {
+        let rendered = session::Session::read(session).and_then(|session| {
+            let settings = render::settings_with_preset(settings, preset.as_deref())?;
+            render::render(settings, &session, output, *sample_rate, *tail)
+        });
+        if let Err(err) = rendered {
+            log::error!("{err:#}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(cli::Command::ImportScala { scale, kbm }) = &args.command {
+        let imported = tuning::import_scala(scale, kbm.as_deref()).and_then(|tuning| {
+            settings.current_preset.tuning = tuning;
+            settings.save()
+        });
+        match imported {
+            Ok(()) => log::info!(
+                "Imported {} in the preset {}",
+                scale.display(),
+                settings.current_preset.name
+            ),
+            Err(err) => {
+                log::error!("{err:#}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // The command line can override the tracking source, without saving it
+    let tracking = args.tracking(&settings.system.tracking);
 
     if settings.system.high_priority_process {
         set_high_priority();
@@ -68,7 +191,7 @@ fn main() {
     // Set the DSP in its initial state
     settings
         .current_preset
-        .send_to_dsp(&controls, &dsp_tx)
+        .send_to_dsp(&settings.played_reference_pitch(), 0, &controls, &dsp_tx)
         .unwrap();
 
     // Start the conductor thread
@@ -79,13 +202,25 @@ fn main() {
         dsp_tx.clone(),
         ui_tx.clone(),
         leap_tx.clone(),
+        args.record.and_then(|path| {
+            Recorder::new(path)
+                .map_err(|err| log::error!("Not recording the session: {err:#}"))
+                .ok()
+        }),
     );
 
     // Init sound output
-    let dsp = thread_dsp::run(dsp, state, dsp_rx);
+    let dsp = thread_dsp::run(
+        dsp,
+        state,
+        settings.system.audio.clone(),
+        settings.system.input.clone(),
+        dsp_rx,
+        co_tx.clone(),
+    );
 
     // Init leap thread
-    let leap = thread_leap::run(co_tx.clone(), leap_rx);
+    let leap = thread_leap::run(co_tx.clone(), leap_rx, tracking);
 
     // Start UI
     let (window, _window_timer) = thread_ui::run(co_tx.clone(), ui_rx, controls.clone(), settings);
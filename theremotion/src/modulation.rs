@@ -0,0 +1,265 @@
+use std::{f32::consts::PI, ops::RangeInclusive};
+
+use crate::{
+    controls::{convert_range, Control, Controls},
+    settings::{
+        Geometry, HandFeature, Modulation, ModulationDestination, ModulationHand, TriggerGesture,
+    },
+    HandMessage,
+};
+
+/// Default part of the measurement range to go over to trigger a gesture
+pub const DEFAULT_TRIGGER_THRESHOLD: f32 = 0.8;
+
+impl HandFeature {
+    /// Current value of the feature, if it is defined
+    pub fn value(&self, h: &HandMessage) -> Option<f32> {
+        let position = h.position_from_body();
+        let velocity = h.velocity_from_body();
+        match self {
+            HandFeature::PositionX => Some(position.x),
+            HandFeature::PositionY => Some(position.y),
+            HandFeature::PositionZ => Some(position.z),
+            HandFeature::VelocityX => Some(velocity.x),
+            HandFeature::VelocityY => Some(velocity.y),
+            HandFeature::VelocityZ => Some(velocity.z),
+            HandFeature::VelocityXZ => Some(velocity.x + velocity.z),
+            HandFeature::VerticalSpeed => Some(velocity.y.abs()),
+            HandFeature::Pinch => Some(h.pinch),
+            HandFeature::Grab => Some(h.grab),
+            HandFeature::Rotation => h.rotation_from_body(),
+        }
+    }
+
+    /// Range used when the modulation does not set one
+    ///
+    /// The positions follow the calibrated playing geometry.
+    pub fn default_range(&self, hand: ModulationHand, geometry: &Geometry) -> RangeInclusive<f32> {
+        match (self, hand) {
+            (HandFeature::PositionX, ModulationHand::Pitch) => 0.0..=geometry.antenna_x,
+            (HandFeature::PositionX, ModulationHand::Volume) => geometry.cutoff_distance.clone(),
+            (HandFeature::PositionY, ModulationHand::Pitch) => geometry.chords_height.clone(),
+            (HandFeature::PositionY, ModulationHand::Volume) => geometry.volume_height.clone(),
+            (HandFeature::PositionZ, ModulationHand::Pitch) => {
+                geometry.antenna_z..=-geometry.antenna_z
+            }
+            (HandFeature::PositionZ, ModulationHand::Volume) => geometry.resonance_depth.clone(),
+            (
+                HandFeature::VelocityX
+                | HandFeature::VelocityY
+                | HandFeature::VelocityZ
+                | HandFeature::VelocityXZ,
+                _,
+            ) => -300.0..=300.0,
+            (HandFeature::VerticalSpeed, _) => 0.0..=250.0,
+            (HandFeature::Pinch | HandFeature::Grab, _) => 0.0..=1.0,
+            (HandFeature::Rotation, _) => 0.0..=PI,
+        }
+    }
+
+    /// Value of the feature in its range (0-1), the default range if none is given
+    ///
+    /// None if the feature is not defined for this hand position or the range
+    /// is empty.
+    pub fn normalized(
+        &self,
+        h: &HandMessage,
+        hand: ModulationHand,
+        input: Option<&RangeInclusive<f32>>,
+        geometry: &Geometry,
+    ) -> Option<f32> {
+        let value = self.value(h)?;
+        let input = input
+            .cloned()
+            .unwrap_or_else(|| self.default_range(hand, geometry));
+        if input.start() == input.end() {
+            return None;
+        }
+        Some(convert_range(value, &input, &(0.0..=1.0)).clamp(0.0, 1.0))
+    }
+}
+
+impl ModulationDestination {
+    /// DSP control driven by this destination
+    pub fn control<'a>(&self, controls: &'a Controls) -> &'a Control {
+        match self {
+            ModulationDestination::LeadVolume => &controls.lead_volume,
+            ModulationDestination::CutoffNote => &controls.cutoff_note,
+            ModulationDestination::Resonance => &controls.resonance,
+            ModulationDestination::PluckMute => &controls.pluck_mute,
+            ModulationDestination::DroneDetune => &controls.drone_detune,
+            ModulationDestination::DroneTrumpet => &controls.drone_trumpet,
+            ModulationDestination::PitchBend => &controls.pitch_bend,
+            ModulationDestination::EchoMix => &controls.echo_mix,
+            ModulationDestination::EchoDuration => &controls.echo_duration,
+            ModulationDestination::EchoFeedback => &controls.echo_feedback,
+            ModulationDestination::ReverbMix => &controls.reverb_mix,
+            ModulationDestination::ReverbTime => &controls.reverb_time,
+            ModulationDestination::ReverbDamp => &controls.reverb_damp,
+            ModulationDestination::ReverbSize => &controls.reverb_size,
+            ModulationDestination::ReverbEarlyDiff => &controls.reverb_early_diff,
+            ModulationDestination::ReverbModDepth => &controls.reverb_mod_depth,
+            ModulationDestination::ReverbModFreq => &controls.reverb_mod_freq,
+            ModulationDestination::MasterVolume => &controls.mix_master_volume,
+            ModulationDestination::DroneVolume => &controls.mix_drone_volume,
+            ModulationDestination::LeadMixVolume => &controls.mix_lead_volume,
+            ModulationDestination::GuitarVolume => &controls.mix_pluck_volume,
+        }
+    }
+}
+
+impl Modulation {
+    /// Value of the destination for a hand, normalized on the control range (0-1)
+    ///
+    /// None if the source is not defined for this hand position.
+    pub fn evaluate(&self, h: &HandMessage, geometry: &Geometry) -> Option<f32> {
+        let x = self
+            .source
+            .normalized(h, self.hand, self.input.as_ref(), geometry)?;
+        let x = self.curve.apply(x);
+        let x = if self.invert { 1.0 - x } else { x };
+        Some(convert_range(x, &(0.0..=1.0), &self.output))
+    }
+}
+
+impl TriggerGesture {
+    /// Whether the hand just went over the threshold
+    ///
+    /// `over` keeps whether the hand was over the threshold on the previous
+    /// update, it is left untouched when the gesture is not measured.
+    pub fn triggered(
+        &self,
+        h: &HandMessage,
+        hand: ModulationHand,
+        geometry: &Geometry,
+        over: &mut bool,
+    ) -> bool {
+        if hand != self.hand {
+            return false;
+        }
+        let Some(x) = self
+            .source
+            .normalized(h, self.hand, self.input.as_ref(), geometry)
+        else {
+            return false;
+        };
+        let was_over = std::mem::replace(over, x > self.threshold);
+        *over && !was_over
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{UnitQuaternion, Vector3};
+    use rstest::rstest;
+
+    use crate::{
+        settings::{Curve, HandFeature::*, ModulationDestination::*, Preset},
+        HandType,
+    };
+
+    use super::*;
+
+    fn hand(position: Vector3<f32>) -> HandMessage {
+        HandMessage {
+            hand_type: HandType::Left,
+            position,
+            velocity: Vector3::zeros(),
+            rotation: UnitQuaternion::identity(),
+            pinch: 0.0,
+            grab: 0.0,
+        }
+    }
+
+    /// Normalized value of a destination for the default modulations
+    fn default_value(h: &HandMessage, destination: ModulationDestination) -> Option<f32> {
+        Preset::default()
+            .modulations
+            .iter()
+            .find(|m| m.destination == destination)
+            .and_then(|m| m.evaluate(h, &Geometry::default()))
+    }
+
+    #[rstest]
+    #[case(Vector3::new(-50.0, 300.0, 100.0), CutoffNote, 0.0)]
+    #[case(Vector3::new(-200.0, 300.0, 100.0), CutoffNote, 1.0)]
+    #[case(Vector3::new(-500.0, 300.0, 100.0), CutoffNote, 1.0)]
+    #[case(Vector3::new(-50.0, 350.0, 100.0), LeadVolume, 0.5)]
+    #[case(Vector3::new(-50.0, 350.0, -100.0), Resonance, 1.0)]
+    fn default_matrix(
+        #[case] position: Vector3<f32>,
+        #[case] destination: ModulationDestination,
+        #[case] expected: f32,
+    ) {
+        assert_eq!(Some(expected), default_value(&hand(position), destination));
+    }
+
+    #[rstest]
+    fn undefined_rotation() {
+        let mut h = hand(Vector3::zeros());
+        h.rotation = UnitQuaternion::from_euler_angles(0.0, 0.0, PI);
+        assert_eq!(None, default_value(&h, PluckMute));
+    }
+
+    #[rstest]
+    #[case(false, 0.0..=1.0, 0.25)]
+    #[case(true, 0.0..=1.0, 0.75)]
+    #[case(false, 0.5..=1.0, 0.625)]
+    #[case(true, 1.0..=0.5, 0.625)]
+    fn invert_and_output(
+        #[case] invert: bool,
+        #[case] output: RangeInclusive<f32>,
+        #[case] expected: f32,
+    ) {
+        let mut h = hand(Vector3::zeros());
+        h.pinch = 0.25;
+        let modulation = Modulation {
+            invert,
+            output,
+            ..Modulation::new(ModulationHand::Pitch, Pinch, EchoMix)
+        };
+        assert_eq!(
+            Some(expected),
+            modulation.evaluate(&h, &Geometry::default())
+        );
+    }
+
+    #[rstest]
+    fn trigger_once_over_threshold() {
+        let gesture = TriggerGesture {
+            hand: ModulationHand::Volume,
+            source: Grab,
+            input: None,
+            threshold: DEFAULT_TRIGGER_THRESHOLD,
+        };
+        let geometry = Geometry::default();
+        let mut over = false;
+        let triggers = [0.2, 0.9, 1.0, 0.5, 0.85].map(|grab| {
+            let h = HandMessage {
+                grab,
+                ..hand(Vector3::zeros())
+            };
+            gesture.triggered(&h, ModulationHand::Volume, &geometry, &mut over)
+        });
+        assert_eq!([false, true, false, false, true], triggers);
+        assert!(!gesture.triggered(
+            &hand(Vector3::zeros()),
+            ModulationHand::Pitch,
+            &geometry,
+            &mut over
+        ));
+    }
+
+    #[rstest]
+    fn from_yaml() {
+        let yaml = "hand: Volume\nsource: Grab\ndestination: ReverbMix\ncurve: {type: Exponential}";
+        let modulation: Modulation = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            Modulation {
+                curve: Curve::Exponential { steepness: 4.0 },
+                ..Modulation::new(ModulationHand::Volume, Grab, ReverbMix)
+            },
+            modulation
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use nalgebra::{Vector2, Vector3};
+
+use crate::settings::{Geometry, PitchLayout};
+
+/// Default distance added to the hand distance of the vertical rod (mm)
+pub const DEFAULT_ROD_OFFSET: f32 = 100.0;
+
+/// Default octave widths, each one half wider than the previous one (mm)
+pub const DEFAULT_OCTAVE_WIDTHS: [f32; 4] = [120.0, 180.0, 270.0, 405.0];
+
+/// Highest number of octaves walked through, the whole MIDI range
+const MAX_OCTAVES: usize = 11;
+
+/// Time step used to derive the pitch speed from the hand velocity (s)
+const SPEED_TIME_STEP: f32 = 0.01;
+
+impl PitchLayout {
+    /// Distance of a position to the antenna, from the antenna (mm)
+    pub fn distance(&self, from_antenna: Vector2<f32>) -> f32 {
+        match self {
+            PitchLayout::Linear => from_antenna.x.abs(),
+            PitchLayout::Circular
+            | PitchLayout::VerticalRod { .. }
+            | PitchLayout::Octaves { .. } => from_antenna.norm(),
+        }
+    }
+
+    /// Semitones below the highest note at a distance from the antenna (mm)
+    ///
+    /// `span` is the number of semitones played, the evenly spaced layouts
+    /// put the lowest note at `span * mm_per_semitone`.
+    pub fn semitones(&self, distance: f32, mm_per_semitone: f32, span: f32) -> f32 {
+        match self {
+            PitchLayout::Circular | PitchLayout::Linear => distance / mm_per_semitone,
+            PitchLayout::VerticalRod { offset } => {
+                // The notes are evenly spaced on 1 / distance, like the
+                // capacitance between the hand and the rod. The lowest note
+                // stays where the evenly spaced layouts put it.
+                let offset = offset.max(1.0);
+                let lowest = span * mm_per_semitone;
+                span * distance * (lowest + offset) / (lowest * (distance + offset))
+            }
+            PitchLayout::Octaves { widths } => {
+                let mut remaining = distance;
+                for octave in 0..MAX_OCTAVES {
+                    let width = octave_width(widths, octave, mm_per_semitone);
+                    if remaining < width {
+                        return 12.0 * (octave as f32 + remaining / width);
+                    }
+                    remaining -= width;
+                }
+                12.0 * MAX_OCTAVES as f32
+            }
+        }
+    }
+
+    /// Distance from the antenna of the note the given semitones below the
+    /// highest one (mm), reverse of [`PitchLayout::semitones`]
+    ///
+    /// Returns `None` for a note out of reach.
+    pub fn note_distance(&self, semitones: f32, mm_per_semitone: f32, span: f32) -> Option<f32> {
+        match self {
+            PitchLayout::Circular | PitchLayout::Linear => Some(semitones * mm_per_semitone),
+            PitchLayout::VerticalRod { offset } => {
+                let offset = offset.max(1.0);
+                let lowest = span * mm_per_semitone;
+                let denominator = span * (lowest + offset) - semitones * lowest;
+                (denominator > 0.0).then(|| semitones * lowest * offset / denominator)
+            }
+            PitchLayout::Octaves { widths } => {
+                let octaves = semitones / 12.0;
+                let full_octaves = octaves.floor() as usize;
+                if full_octaves >= MAX_OCTAVES {
+                    return None;
+                }
+                let full_width: f32 = (0..full_octaves)
+                    .map(|octave| octave_width(widths, octave, mm_per_semitone))
+                    .sum();
+                Some(
+                    full_width
+                        + octaves.fract() * octave_width(widths, full_octaves, mm_per_semitone),
+                )
+            }
+        }
+    }
+}
+
+/// Width of an octave (mm), the last width is repeated
+fn octave_width(widths: &[f32], octave: usize, mm_per_semitone: f32) -> f32 {
+    widths
+        .get(octave)
+        .or(widths.last())
+        .copied()
+        .unwrap_or(12.0 * mm_per_semitone)
+        .max(1.0)
+}
+
+impl Geometry {
+    /// Position of the pitch hand from the antenna, in semitones of the
+    /// evenly spaced layouts
+    ///
+    /// This is the position shown on the pitch plot.
+    pub fn pitch_coord(&self, position_from_body: &Vector3<f32>) -> Vector2<f32> {
+        Vector2::new(
+            position_from_body.x - self.antenna_x,
+            self.antenna_z - position_from_body.z,
+        ) / self.mm_per_semitone
+    }
+
+    /// Semitones below the highest note at a position of the pitch plot
+    pub fn pitch_semitones(&self, pitch_coord: Vector2<f32>, span: f32) -> f32 {
+        let distance = self
+            .pitch_layout
+            .distance(pitch_coord * self.mm_per_semitone);
+        self.pitch_layout
+            .semitones(distance, self.mm_per_semitone, span)
+    }
+
+    /// Speed of the pitch of the pitch hand, going up when positive
+    /// (semitones/s)
+    pub fn pitch_speed(
+        &self,
+        position_from_body: &Vector3<f32>,
+        velocity_from_body: &Vector3<f32>,
+        span: f32,
+    ) -> f32 {
+        let next_position = position_from_body + velocity_from_body * SPEED_TIME_STEP;
+        let semitones = self.pitch_semitones(self.pitch_coord(position_from_body), span);
+        let next_semitones = self.pitch_semitones(self.pitch_coord(&next_position), span);
+        (semitones - next_semitones) / SPEED_TIME_STEP
+    }
+
+    /// Distance from the antenna on the pitch plot of the note the given
+    /// semitones below the highest one
+    pub fn note_distance(&self, semitones: f32, span: f32) -> Option<f32> {
+        self.pitch_layout
+            .note_distance(semitones, self.mm_per_semitone, span)
+            .map(|distance| distance / self.mm_per_semitone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn layouts() -> Vec<PitchLayout> {
+        vec![
+            PitchLayout::Circular,
+            PitchLayout::Linear,
+            PitchLayout::VerticalRod {
+                offset: DEFAULT_ROD_OFFSET,
+            },
+            PitchLayout::Octaves {
+                widths: DEFAULT_OCTAVE_WIDTHS.to_vec(),
+            },
+            PitchLayout::Octaves { widths: vec![] },
+        ]
+    }
+
+    #[rstest]
+    fn reversible() {
+        for layout in layouts() {
+            for semitones in [0.0, 1.0, 5.5, 12.0, 30.0, 36.0] {
+                let distance = layout.note_distance(semitones, 15.0, 36.0).unwrap();
+                let back = layout.semitones(distance, 15.0, 36.0);
+                assert!(
+                    (semitones - back).abs() < 1e-3,
+                    "{layout:?} {semitones} {back}"
+                );
+            }
+        }
+    }
+
+    #[rstest]
+    fn lower_away_from_antenna() {
+        for layout in layouts() {
+            let mut previous = -1.0;
+            for distance in (0..60).map(|d| d as f32 * 10.0) {
+                let semitones = layout.semitones(distance, 15.0, 36.0);
+                assert!(semitones > previous, "{layout:?} {distance}");
+                previous = semitones;
+            }
+        }
+    }
+
+    #[rstest]
+    fn vertical_rod_tighter_near_antenna() {
+        let layout = PitchLayout::VerticalRod {
+            offset: DEFAULT_ROD_OFFSET,
+        };
+        let first = layout.note_distance(1.0, 15.0, 36.0).unwrap();
+        let last = layout.note_distance(36.0, 15.0, 36.0).unwrap()
+            - layout.note_distance(35.0, 15.0, 36.0).unwrap();
+        assert!(first < 15.0);
+        assert!(last > 15.0);
+        assert!((layout.note_distance(36.0, 15.0, 36.0).unwrap() - 540.0).abs() < 1e-2);
+    }
+
+    #[rstest]
+    #[case(0.0, 0.0)]
+    #[case(60.0, 6.0)]
+    #[case(120.0, 12.0)]
+    #[case(210.0, 18.0)]
+    #[case(705.0, 42.0)]
+    fn octave_widths(#[case] distance: f32, #[case] expected: f32) {
+        let layout = PitchLayout::Octaves {
+            widths: vec![120.0, 180.0, 270.0],
+        };
+        assert!((expected - layout.semitones(distance, 15.0, 36.0)).abs() < 1e-4);
+    }
+
+    #[rstest]
+    fn linear_ignores_depth() {
+        let geometry = Geometry {
+            pitch_layout: PitchLayout::Linear,
+            ..Default::default()
+        };
+        let near = geometry.pitch_coord(&Vector3::new(250.0, 0.0, -200.0));
+        let far = geometry.pitch_coord(&Vector3::new(250.0, 0.0, 100.0));
+        assert_eq!(10.0, geometry.pitch_semitones(near, 36.0));
+        assert_eq!(10.0, geometry.pitch_semitones(far, 36.0));
+        let circular = Geometry::default();
+        assert!(circular.pitch_semitones(far, 36.0) > 10.0);
+    }
+
+    #[rstest]
+    fn pitch_speed() {
+        let geometry = Geometry::default();
+        let position = Vector3::new(250.0, 0.0, -200.0);
+        // Towards the antenna, 10 cm/s
+        let towards = Vector3::new(100.0, 0.0, 0.0);
+        let speed = geometry.pitch_speed(&position, &towards, 36.0);
+        assert!((speed - 100.0 / 15.0).abs() < 1e-2, "{speed}");
+        assert!(geometry.pitch_speed(&position, &-towards, 36.0) < 0.0);
+        let still = geometry.pitch_speed(&position, &Vector3::new(0.0, 50.0, 0.0), 36.0);
+        assert!(still.abs() < 1e-3);
+    }
+
+    #[rstest]
+    #[case("type: Circular", PitchLayout::Circular)]
+    #[case("type: VerticalRod", PitchLayout::VerticalRod { offset: DEFAULT_ROD_OFFSET })]
+    #[case(
+        "{type: Octaves, widths: [100.0, 200.0]}",
+        PitchLayout::Octaves { widths: vec![100.0, 200.0] }
+    )]
+    fn from_yaml(#[case] yaml: &str, #[case] expected: PitchLayout) {
+        assert_eq!(expected, serde_yaml::from_str::<PitchLayout>(yaml).unwrap());
+    }
+}
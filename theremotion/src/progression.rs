@@ -0,0 +1,148 @@
+use crate::settings::{ChordDuration, DroneProgression, ProgressionAdvance, Tempo};
+
+/// Shortest drone chord (beats), keeping the progression from racing
+const MIN_CHORD_BEATS: f64 = 0.25;
+
+impl ChordDuration {
+    /// Length in beats of the tempo
+    pub fn beats(&self, tempo: &Tempo) -> f64 {
+        let beats = match self {
+            ChordDuration::Bars { bars } => *bars as f64 * tempo.beats_per_bar as f64,
+            ChordDuration::Seconds { seconds } => *seconds as f64 / tempo.beat_duration() as f64,
+        };
+        beats.max(MIN_CHORD_BEATS)
+    }
+}
+
+impl DroneProgression {
+    /// Lengths of the chords in beats when following the tempo, empty
+    /// otherwise
+    pub fn durations(&self, tempo: &Tempo) -> Vec<f64> {
+        if self.advance != ProgressionAdvance::Tempo {
+            return vec![];
+        }
+        self.chords
+            .iter()
+            .map(|chord| chord.duration.beats(tempo))
+            .collect()
+    }
+}
+
+/// Current chord of a progression, drone chords or lead sheet
+#[derive(Debug, Default)]
+pub struct ProgressionPosition {
+    /// Index of the chord
+    pub chord: usize,
+    /// Beat of the clock the chord started on
+    start: f64,
+}
+
+impl ProgressionPosition {
+    /// Move to the chord whose time has come, given the chord lengths in
+    /// beats, returns whether it changed
+    pub fn follow(&mut self, durations: &[f64], beats: f64) -> bool {
+        if durations.is_empty() {
+            return false;
+        }
+        let total: f64 = durations.iter().sum();
+        if beats < self.start || beats - self.start > total {
+            // The clock restarted or the progression was not followed
+            self.start = beats;
+            return false;
+        }
+        let previous = self.chord;
+        self.chord %= durations.len();
+        loop {
+            let duration = durations[self.chord].max(MIN_CHORD_BEATS);
+            if beats - self.start < duration {
+                break;
+            }
+            self.start += duration;
+            self.chord = (self.chord + 1) % durations.len();
+        }
+        self.chord != previous
+    }
+
+    /// Move to a chord of the `count` chords now
+    pub fn jump(&mut self, count: usize, chord: usize, beats: f64) {
+        self.chord = chord % count.max(1);
+        self.start = beats;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::settings::DroneChord;
+
+    use super::*;
+
+    fn progression(durations: &[ChordDuration]) -> DroneProgression {
+        DroneProgression {
+            chords: durations
+                .iter()
+                .map(|duration| DroneChord {
+                    duration: *duration,
+                    ..Default::default()
+                })
+                .collect(),
+            advance: ProgressionAdvance::Tempo,
+        }
+    }
+
+    #[rstest]
+    #[case(ChordDuration::Bars { bars: 2.0 }, 8.0)]
+    #[case(ChordDuration::Seconds { seconds: 1.5 }, 3.0)]
+    #[case(ChordDuration::Bars { bars: 0.0 }, MIN_CHORD_BEATS)]
+    fn beats(#[case] duration: ChordDuration, #[case] expected: f64) {
+        assert_eq!(expected, duration.beats(&Tempo::default()));
+    }
+
+    #[rstest]
+    fn follow_tempo() {
+        let progression = progression(&[
+            ChordDuration::Bars { bars: 1.0 },
+            ChordDuration::Bars { bars: 2.0 },
+            ChordDuration::Seconds { seconds: 0.5 },
+        ]);
+        let durations = progression.durations(&Tempo::default());
+        let mut position = ProgressionPosition::default();
+        let chords = [0.0, 3.9, 4.0, 11.0, 12.0, 12.5, 13.0, 17.0].map(|beats| {
+            position.follow(&durations, beats);
+            position.chord
+        });
+        assert_eq!([0, 0, 1, 1, 2, 2, 0, 1], chords);
+    }
+
+    #[rstest]
+    fn from_yaml() {
+        let yaml = "advance: {type: Manual, gesture: {hand: Volume, source: Pinch}}
+chords:
+- {intervals: [0, 7, 12, null], duration: {type: Bars, bars: 2.0}}
+- {intervals: [5, 9, 12, null]}";
+        let progression: DroneProgression = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(2, progression.chords.len());
+        assert_eq!(
+            ChordDuration::Bars { bars: 1.0 },
+            progression.chords[1].duration
+        );
+        assert!(matches!(
+            progression.advance,
+            ProgressionAdvance::Manual { gesture: Some(_) }
+        ));
+    }
+
+    #[rstest]
+    fn manual() {
+        let progression = DroneProgression {
+            advance: ProgressionAdvance::Manual { gesture: None },
+            ..progression(&[Default::default(), Default::default()])
+        };
+        let durations = progression.durations(&Tempo::default());
+        let mut position = ProgressionPosition::default();
+        assert!(!position.follow(&durations, 10.0));
+        position.jump(progression.chords.len(), 3, 10.0);
+        assert_eq!(1, position.chord);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{
+    settings::{ReferencePitch, Settings},
+    IntervalF, MidiNoteF,
+};
+
+/// Frequency of the A4 assumed by the DSP (Hz)
+pub const STANDARD_A4: f32 = 440.0;
+
+/// Concert pitches offered in the UI (Hz)
+pub const CONCERT_PITCHES: [f32; 4] = [415.0, 432.0, 440.0, 442.0];
+
+/// MIDI note number of the A4
+const A4_NOTE: f32 = 69.0;
+
+impl ReferencePitch {
+    /// Interval added to the notes sent to the DSP
+    pub fn offset(&self) -> IntervalF {
+        IntervalF(12.0 * (self.a4 / STANDARD_A4).log2() + self.cents / 100.0)
+    }
+
+    /// Frequency of a note (Hz)
+    pub fn frequency(&self, note: MidiNoteF) -> f32 {
+        STANDARD_A4 * 2f32.powf(((note + self.offset()).note() - A4_NOTE) / 12.0)
+    }
+}
+
+impl Settings {
+    /// Concert pitch of the current preset
+    pub fn reference_pitch(&self) -> ReferencePitch {
+        self.current_preset
+            .reference_pitch
+            .unwrap_or(self.system.reference_pitch)
+    }
+
+    /// Concert pitch applied to the played notes
+    ///
+    /// A tuning with its own reference frequency, from a Scala keyboard
+    /// mapping, is not transposed again.
+    pub fn played_reference_pitch(&self) -> ReferencePitch {
+        if self.current_preset.tuning.has_reference_frequency() {
+            ReferencePitch::default()
+        } else {
+            self.reference_pitch()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::settings::Tuning;
+
+    use super::*;
+
+    #[rstest]
+    #[case(440.0, 0.0, 0.0)]
+    #[case(880.0, 0.0, 12.0)]
+    #[case(415.0, 0.0, -1.0)]
+    #[case(440.0, -50.0, -0.5)]
+    #[case(415.0, 100.0, 0.0)]
+    fn offset(#[case] a4: f32, #[case] cents: f32, #[case] expected: f32) {
+        let offset = ReferencePitch { a4, cents }.offset();
+        assert!((expected - offset.semitones()).abs() < 0.02, "{offset:?}");
+    }
+
+    #[rstest]
+    #[case(440.0, 69.0, 440.0)]
+    #[case(440.0, 60.0, 261.63)]
+    #[case(432.0, 69.0, 432.0)]
+    #[case(442.0, 81.0, 884.0)]
+    fn frequency(#[case] a4: f32, #[case] note: f32, #[case] expected: f32) {
+        let reference = ReferencePitch { a4, cents: 0.0 };
+        let frequency = reference.frequency(MidiNoteF(note));
+        assert!((expected - frequency).abs() < 0.01, "{frequency}");
+    }
+
+    #[rstest]
+    fn preset_overrides_system() {
+        let mut settings = Settings::default();
+        settings.system.reference_pitch.a4 = 432.0;
+        assert_eq!(432.0, settings.reference_pitch().a4);
+        settings.current_preset.reference_pitch = Some(ReferencePitch {
+            a4: 415.0,
+            cents: 0.0,
+        });
+        assert_eq!(415.0, settings.reference_pitch().a4);
+    }
+
+    #[rstest]
+    fn tuning_frequency_not_transposed() {
+        let mut settings = Settings::default();
+        settings.system.reference_pitch.a4 = 432.0;
+        assert_eq!(432.0, settings.played_reference_pitch().a4);
+        settings.current_preset.tuning = Tuning::Cents {
+            name: "Rast".to_string(),
+            cents: vec![200.0, 1200.0],
+            degrees: None,
+            root: Some(60.0),
+        };
+        assert_eq!(0.0, settings.played_reference_pitch().offset().semitones());
+        assert_eq!(432.0, settings.reference_pitch().a4);
+    }
+}
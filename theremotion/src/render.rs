@@ -0,0 +1,118 @@
+use std::{path::Path, sync::mpsc::Receiver};
+
+use anyhow::Context;
+use default_boxed::DefaultBoxed;
+use faust_state::DspHandle;
+
+use crate::{
+    controls::Controls, session::Session, settings::Settings, thread_conductor::OfflineConductor,
+    thread_dsp::OfflineEngine, wav::WavWriter,
+};
+
+/// Output channels of the rendered file
+const CHANNELS: u16 = 2;
+
+/// Frames computed between the updates of the parameters, about as often as
+/// the tracking
+const BLOCK_FRAMES: usize = 256;
+
+/// Render a recorded session played with the settings to a WAV file
+///
+/// The session is followed by `tail` seconds for the release of the notes
+/// and the effects.
+pub fn render(
+    settings: Settings,
+    session: &Session,
+    path: &Path,
+    sample_rate: u32,
+    tail: f64,
+) -> anyhow::Result<()> {
+    let (dsp_tx, dsp_rx) = std::sync::mpsc::channel();
+    let (ui_tx, ui_rx) = std::sync::mpsc::channel();
+    let (leap_tx, leap_rx) = std::sync::mpsc::channel();
+
+    let dsp = theremotion_dsp::Instrument::default_boxed();
+    let (dsp, state) = DspHandle::<theremotion_dsp::Instrument>::from_dsp(dsp);
+    let controls = Controls::from(&state);
+    let mut engine = OfflineEngine::new(dsp, state, sample_rate, CHANNELS);
+
+    settings.current_preset.send_to_dsp(
+        &settings.played_reference_pitch(),
+        0,
+        &controls,
+        &dsp_tx,
+    )?;
+    let mut conductor = OfflineConductor::new(settings, controls, dsp_tx, ui_tx, leap_tx)?;
+    let mut wav = WavWriter::create(path, CHANNELS, sample_rate)?;
+
+    let mut frames = session.frames.iter().peekable();
+    let mut block = vec![0.0; BLOCK_FRAMES * CHANNELS as usize];
+    let length = ((session.duration() + tail) * sample_rate as f64).ceil() as usize;
+    let mut rendered = 0;
+    while rendered < length {
+        let time = rendered as f64 / sample_rate as f64;
+        while let Some(frame) = frames.next_if(|frame| frame.time <= time) {
+            conductor.update(time, Some(frame.event.clone().into()))?;
+        }
+        conductor.update(time, None)?;
+        for msg in dsp_rx.try_iter() {
+            engine.update(msg);
+        }
+        discard(&ui_rx);
+        discard(&leap_rx);
+
+        let count = BLOCK_FRAMES.min(length - rendered);
+        let block = &mut block[..count * CHANNELS as usize];
+        engine.process(block);
+        wav.write(block)?;
+        rendered += count;
+    }
+    wav.finish()
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    log::info!(
+        "Rendered {:.1} s of the session to {}",
+        length as f64 / sample_rate as f64,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Drop the messages to the threads not running while rendering
+fn discard<T>(rx: &Receiver<T>) {
+    rx.try_iter().for_each(drop);
+}
+
+/// Settings playing the named preset, the current one when absent
+pub fn settings_with_preset(
+    mut settings: Settings,
+    name: Option<&str>,
+) -> anyhow::Result<Settings> {
+    if let Some(name) = name {
+        let preset = settings
+            .system_and_user_presets()
+            .find(|(preset, _)| preset.name == name)
+            .map(|(preset, _)| preset.clone())
+            .with_context(|| format!("Unknown preset {name}"))?;
+        settings.current_preset = preset;
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::settings::Preset;
+
+    use super::*;
+
+    #[rstest]
+    fn preset_by_name() {
+        let name = &Preset::system_presets()[0].name;
+        let settings = settings_with_preset(Settings::default(), Some(name)).unwrap();
+        assert_eq!(*name, settings.current_preset.name);
+        assert!(settings_with_preset(Settings::default(), Some("Unknown preset")).is_err());
+        let settings = settings_with_preset(Settings::default(), None).unwrap();
+        assert_eq!(Settings::default().current_preset, settings.current_preset);
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+/// Samples exchanged with the audio callback
+///
+/// Single producer single consumer queue, neither side ever waits for the
+/// other. The positions only grow, the samples are stored at their
+/// position modulo the capacity.
+pub struct Ring {
+    /// Bits of the `f32` samples
+    samples: Box<[AtomicU32]>,
+    /// Position of the next sample read
+    read: AtomicUsize,
+    /// Position of the next sample written
+    write: AtomicUsize,
+    /// Samples lost because the buffer was full
+    dropped: AtomicUsize,
+}
+
+impl Ring {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: (0..capacity.max(1)).map(|_| AtomicU32::new(0)).collect(),
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    /// Append the samples that fit, only called by the producer
+    pub fn push(&self, samples: &[f32]) {
+        let write = self.write.load(Ordering::Relaxed);
+        let read = self.read.load(Ordering::Acquire);
+        let free = self.samples.len() - write.wrapping_sub(read);
+        let count = samples.len().min(free);
+        for (i, sample) in samples[..count].iter().enumerate() {
+            self.samples[write.wrapping_add(i) % self.samples.len()]
+                .store(sample.to_bits(), Ordering::Relaxed);
+        }
+        self.write
+            .store(write.wrapping_add(count), Ordering::Release);
+        if count < samples.len() {
+            self.dropped
+                .fetch_add(samples.len() - count, Ordering::Relaxed);
+        }
+    }
+
+    /// Take the oldest samples, only called by the consumer
+    pub fn pop(&self, samples: &mut [f32]) -> usize {
+        let read = self.read.load(Ordering::Relaxed);
+        let write = self.write.load(Ordering::Acquire);
+        let count = samples.len().min(write.wrapping_sub(read));
+        for (i, sample) in samples[..count].iter_mut().enumerate() {
+            *sample = f32::from_bits(
+                self.samples[read.wrapping_add(i) % self.samples.len()].load(Ordering::Relaxed),
+            );
+        }
+        self.read.store(read.wrapping_add(count), Ordering::Release);
+        count
+    }
+
+    /// Samples waiting to be read
+    pub fn available(&self) -> usize {
+        let read = self.read.load(Ordering::Relaxed);
+        self.write.load(Ordering::Acquire).wrapping_sub(read)
+    }
+
+    /// Forget the oldest samples, only called by the consumer
+    pub fn skip(&self, count: usize) {
+        let count = count.min(self.available());
+        self.read.fetch_add(count, Ordering::Release);
+    }
+
+    /// Samples lost because the buffer was full
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    fn ring() {
+        let ring = Ring::new(4);
+        let mut samples = [0.0; 4];
+        ring.push(&[1.0, 2.0, 3.0]);
+        assert_eq!(2, ring.pop(&mut samples[..2]));
+        assert_eq!([1.0, 2.0], samples[..2]);
+        // Wraps around the end of the buffer, the last sample does not fit
+        ring.push(&[4.0, 5.0, 6.0, 7.0]);
+        assert_eq!(4, ring.pop(&mut samples));
+        assert_eq!([3.0, 4.0, 5.0, 6.0], samples);
+        assert_eq!(1, ring.dropped());
+        assert_eq!(0, ring.pop(&mut samples));
+    }
+
+    #[rstest]
+    fn skip() {
+        let ring = Ring::new(8);
+        ring.push(&[1.0, 2.0, 3.0, 4.0]);
+        ring.skip(3);
+        assert_eq!(1, ring.available());
+        let mut samples = [0.0; 2];
+        assert_eq!(1, ring.pop(&mut samples));
+        assert_eq!(4.0, samples[0]);
+        ring.skip(3);
+        assert_eq!(0, ring.available());
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use rhai::{
+    module_resolvers::DummyModuleResolver, CallFnOptions, Dynamic, Engine, Map, Scope, AST,
+};
+
+use crate::{
+    settings::{ModulationDestination, ModulationHand, Preset},
+    HandMessage, HandType,
+};
+
+/// Interval between two checks of the script file
+const RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Maximal number of operations of a single script call, to stop endless loops
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// Function of the script called on each hand update
+const ENTRY_POINT: &str = "on_hand";
+
+/// Updates requested by the script for a hand update
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ScriptOutput {
+    /// Values of the controls, normalized on their range (0-1)
+    pub controls: Vec<(ModulationDestination, f32)>,
+    /// Message displayed in the status bar
+    pub message: Option<String>,
+    /// Show the guitar indicator
+    pub strum_ready: Option<bool>,
+    /// Autotune indicator (0-5)
+    pub autotune: Option<usize>,
+}
+
+/// User script driving custom play logic
+///
+/// The script is reloaded when its file changes. It has no access to the
+/// file system or the network, and its calls are limited in time.
+pub struct Script {
+    path: PathBuf,
+    engine: Engine,
+    /// Modification time and size of the loaded file
+    version: Option<(SystemTime, u64)>,
+    /// Compiled script, or the compilation error
+    ast: Result<AST, String>,
+    /// Value of `this` in the script, kept between calls
+    state: Dynamic,
+    /// Current preset, as a read-only script value shared by the calls
+    preset: Dynamic,
+    /// Last check of the file
+    last_check: Option<Instant>,
+}
+
+impl Script {
+    pub fn new(path: &Path) -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_module_resolver(DummyModuleResolver::new())
+            .disable_symbol("eval")
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(32)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(10_000)
+            .set_max_array_size(10_000)
+            .set_max_map_size(10_000)
+            .on_print(|text| log::info!("Script: {text}"))
+            .on_debug(|text, _, position| log::debug!("Script {position}: {text}"));
+        Self {
+            path: path.to_path_buf(),
+            engine,
+            version: None,
+            ast: Err("Not loaded".to_string()),
+            state: Map::new().into(),
+            preset: Dynamic::UNIT,
+            last_check: None,
+        }
+    }
+
+    /// Path of the script file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Update the preset given to the script
+    pub fn set_preset(&mut self, preset: &Preset) -> Result<()> {
+        self.preset = rhai::serde::to_dynamic(preset)
+            .map_err(|err| anyhow!("{err}"))?
+            .into_read_only()
+            .into_shared();
+        Ok(())
+    }
+
+    /// Reload the script if its file changed
+    ///
+    /// Only the metadata of the file is read when it did not change.
+    /// Returns true if the script was reloaded.
+    pub fn reload(&mut self) -> Result<bool> {
+        self.last_check = Some(Instant::now());
+        let read_error = || format!("Failed to read the script {}", self.path.display());
+        let metadata = std::fs::metadata(&self.path).with_context(read_error)?;
+        let version = Some((metadata.modified()?, metadata.len()));
+        if self.version == version {
+            return Ok(false);
+        }
+        let source = std::fs::read_to_string(&self.path).with_context(read_error)?;
+        log::info!("Loading the script {}", self.path.display());
+        self.ast = self
+            .engine
+            .compile(&source)
+            .map_err(|err| format!("Script error: {err}"));
+        self.version = version;
+        self.state = Map::new().into();
+        Ok(true)
+    }
+
+    /// Run the script on a hand update
+    pub fn on_hand(&mut self, h: &HandMessage, hand: ModulationHand) -> Result<ScriptOutput> {
+        let reload_due = match self.last_check {
+            Some(last) => last.elapsed() > RELOAD_INTERVAL,
+            None => true,
+        };
+        if reload_due {
+            self.reload()?;
+        }
+        let ast = self.ast.as_ref().map_err(|err| anyhow!("{err}"))?;
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        let result: Dynamic = self
+            .engine
+            .call_fn_with_options(
+                options,
+                &mut Scope::new(),
+                ast,
+                ENTRY_POINT,
+                (hand_value(h, hand), self.preset.clone()),
+            )
+            .map_err(|err| anyhow!("Script error: {err}"))?;
+        parse_output(result)
+    }
+}
+
+/// Hand seen by the script, positions and velocities are from the body
+fn hand_value(h: &HandMessage, hand: ModulationHand) -> Dynamic {
+    let position = h.position_from_body();
+    let velocity = h.velocity_from_body();
+    let mut map = Map::new();
+    let side = match h.hand_type {
+        HandType::Left => "left",
+        HandType::Right => "right",
+    };
+    let role = match hand {
+        ModulationHand::Pitch => "pitch",
+        ModulationHand::Volume => "volume",
+    };
+    map.insert("side".into(), side.into());
+    map.insert("role".into(), role.into());
+    for (name, value) in [
+        ("x", position.x),
+        ("y", position.y),
+        ("z", position.z),
+        ("vx", velocity.x),
+        ("vy", velocity.y),
+        ("vz", velocity.z),
+        ("pinch", h.pinch),
+        ("grab", h.grab),
+    ] {
+        map.insert(name.into(), (value as rhai::FLOAT).into());
+    }
+    map.insert(
+        "rotation".into(),
+        h.rotation_from_body()
+            .map_or(Dynamic::UNIT, |r| (r as rhai::FLOAT).into()),
+    );
+    map.into()
+}
+
+fn as_number(value: &Dynamic) -> Option<f32> {
+    value
+        .as_float()
+        .ok()
+        .or_else(|| value.as_int().ok().map(|v| v as rhai::FLOAT))
+        .map(|v| v as f32)
+}
+
+/// Read the map returned by the script
+fn parse_output(result: Dynamic) -> Result<ScriptOutput> {
+    let mut output = ScriptOutput::default();
+    if result.is_unit() {
+        return Ok(output);
+    }
+    let Some(map) = result.try_cast::<Map>() else {
+        bail!("Script error: {ENTRY_POINT} must return a map");
+    };
+    for (key, value) in map {
+        match key.as_str() {
+            "controls" => {
+                let Some(controls) = value.try_cast::<Map>() else {
+                    bail!("Script error: controls must be a map");
+                };
+                for (name, value) in controls {
+                    let destination: ModulationDestination =
+                        rhai::serde::from_dynamic(&name.to_string().into())
+                            .map_err(|_| anyhow!("Script error: unknown control {name}"))?;
+                    let value = as_number(&value)
+                        .with_context(|| format!("Script error: {name} must be a number"))?;
+                    output.controls.push((destination, value));
+                }
+            }
+            "message" => output.message = Some(value.to_string()),
+            "strum_ready" => {
+                output.strum_ready = Some(
+                    value
+                        .as_bool()
+                        .map_err(|_| anyhow!("Script error: strum_ready must be a boolean"))?,
+                )
+            }
+            "autotune" => {
+                let autotune = as_number(&value)
+                    .context("Script error: autotune must be a number")?
+                    .clamp(0.0, 5.0);
+                output.autotune = Some(autotune as usize);
+            }
+            _ => bail!("Script error: unknown output {key}"),
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{UnitQuaternion, Vector3};
+    use rstest::rstest;
+
+    use super::*;
+
+    fn hand() -> HandMessage {
+        HandMessage {
+            hand_type: HandType::Left,
+            position: Vector3::new(-100.0, 300.0, 50.0),
+            velocity: Vector3::zeros(),
+            rotation: UnitQuaternion::identity(),
+            pinch: 0.5,
+            grab: 0.0,
+        }
+    }
+
+    /// Script in a file of its own temporary directory, removed with it
+    struct TempScript {
+        script: Script,
+        directory: PathBuf,
+    }
+
+    impl Drop for TempScript {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.directory).ok();
+        }
+    }
+
+    fn script(name: &str, source: &str) -> TempScript {
+        let directory =
+            std::env::temp_dir().join(format!("theremotion-script-{}-{name}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("script.rhai");
+        std::fs::write(&path, source).unwrap();
+        let mut script = Script::new(&path);
+        script.set_preset(&Preset::default()).unwrap();
+        TempScript { script, directory }
+    }
+
+    #[rstest]
+    fn controls_and_hints() {
+        let mut script = script(
+            "controls",
+            r#"
+            fn on_hand(hand, preset) {
+                #{
+                    controls: #{ Resonance: hand.pinch, CutoffNote: hand.x / 100 },
+                    message: `${preset.name} ${hand.role}`,
+                    autotune: 2,
+                }
+            }"#,
+        );
+        let output = script
+            .script
+            .on_hand(&hand(), ModulationHand::Volume)
+            .unwrap();
+        assert_eq!(Some("Default volume".to_string()), output.message);
+        assert_eq!(Some(2), output.autotune);
+        assert!(output
+            .controls
+            .contains(&(ModulationDestination::Resonance, 0.5)));
+        assert!(output
+            .controls
+            .contains(&(ModulationDestination::CutoffNote, 1.0)));
+    }
+
+    #[rstest]
+    fn state_between_calls() {
+        let mut script = script(
+            "state",
+            r#"
+            fn on_hand(hand, preset) {
+                this.count = (this.count ?? 0) + 1;
+                #{ message: `${this.count}` }
+            }"#,
+        );
+        let script = &mut script.script;
+        script.on_hand(&hand(), ModulationHand::Pitch).unwrap();
+        let output = script.on_hand(&hand(), ModulationHand::Pitch).unwrap();
+        assert_eq!(Some("2".to_string()), output.message);
+    }
+
+    #[rstest]
+    fn preset_is_read_only() {
+        let mut script = script(
+            "preset",
+            r#"
+            fn on_hand(hand, preset) {
+                let name = preset.name;
+                try { preset.name = "Changed"; } catch {}
+                #{ message: name }
+            }"#,
+        );
+        let script = &mut script.script;
+        script.on_hand(&hand(), ModulationHand::Pitch).unwrap();
+        let output = script.on_hand(&hand(), ModulationHand::Pitch).unwrap();
+        assert_eq!(Some("Default".to_string()), output.message);
+    }
+
+    #[rstest]
+    #[case("syntax", "fn on_hand(hand, preset) {")]
+    #[case("missing", "fn other() {}")]
+    #[case("endless", "fn on_hand(hand, preset) { loop {} }")]
+    #[case("import", "fn on_hand(hand, preset) { import \"other\" as other; }")]
+    #[case("eval", "fn on_hand(hand, preset) { eval(\"1\") }")]
+    #[case(
+        "control",
+        "fn on_hand(hand, preset) { #{ controls: #{ Unknown: 1.0 } } }"
+    )]
+    fn errors(#[case] name: &str, #[case] source: &str) {
+        let mut script = script(name, source);
+        assert!(script
+            .script
+            .on_hand(&hand(), ModulationHand::Pitch)
+            .is_err());
+    }
+
+    #[rstest]
+    fn hot_reload() {
+        let mut script = script("reload", "fn on_hand(hand, preset) { #{ message: \"a\" } }");
+        let script = &mut script.script;
+        let output = script.on_hand(&hand(), ModulationHand::Pitch).unwrap();
+        assert_eq!(Some("a".to_string()), output.message);
+        std::fs::write(
+            script.path(),
+            "fn on_hand(hand, preset) { #{ message: \"b\" } }",
+        )
+        .unwrap();
+        // The same size, written within the resolution of the file times
+        let file = std::fs::File::options()
+            .write(true)
+            .open(script.path())
+            .unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(1))
+            .unwrap();
+        assert!(script.reload().unwrap());
+        assert!(!script.reload().unwrap());
+        let output = script.on_hand(&hand(), ModulationHand::Pitch).unwrap();
+        assert_eq!(Some("b".to_string()), output.message);
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::tracking::TrackingEvent;
+
+/// Interval between two writes of the recorded frames to the file, so that
+/// little is lost if the application stops abruptly
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Recorded hand tracking session
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Session {
+    /// Timestamped tracking events, in chronological order
+    pub frames: Vec<SessionFrame>,
+}
+
+/// Tracking event with its timestamp
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SessionFrame {
+    /// Time since the start of the recording (seconds)
+    pub time: f64,
+    /// Recorded event
+    pub event: TrackingEvent,
+}
+
+impl Session {
+    /// Initialize from a stream
+    pub fn from_reader<R>(f: R) -> Result<Self>
+    where
+        R: std::io::Read,
+    {
+        Ok(serde_yaml::from_reader(f)?)
+    }
+
+    /// Read a session file
+    pub fn read(path: &Path) -> Result<Self> {
+        log::debug!("Loading session from {}", path.display());
+        let f = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open the session {}", path.display()))?;
+        Self::from_reader(f)
+    }
+
+    /// Duration of the session (seconds)
+    pub fn duration(&self) -> f64 {
+        self.frames.last().map(|f| f.time).unwrap_or_default()
+    }
+}
+
+/// Records the tracking events received by the conductor
+///
+/// The frames are appended to the session file as they arrive, the file
+/// being readable as a [`Session`] once anything is recorded.
+pub struct Recorder {
+    /// Destination file
+    path: PathBuf,
+    writer: BufWriter<File>,
+    /// Start of the recording
+    start: Instant,
+    /// Number of frames recorded so far
+    frames: usize,
+    /// Last write of the buffered frames to the file
+    last_flush: Instant,
+}
+
+impl Recorder {
+    /// Creates a new [`Recorder`] saving to the given path.
+    pub fn new(path: PathBuf) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        log::debug!("Recording session to {}", path.display());
+        let f = File::create(&path)
+            .with_context(|| format!("Failed to create the session {}", path.display()))?;
+        let mut writer = BufWriter::new(f);
+        // The frames follow as the items of a block sequence
+        write!(writer, "frames:")?;
+        Ok(Self {
+            path,
+            writer,
+            start: Instant::now(),
+            frames: 0,
+            last_flush: Instant::now(),
+        })
+    }
+
+    /// Timestamp and write a tracking event
+    pub fn record(&mut self, event: TrackingEvent) -> Result<()> {
+        let frame = SessionFrame {
+            time: self.start.elapsed().as_secs_f64(),
+            event,
+        };
+        let item = serde_yaml::to_string(std::slice::from_ref(&frame))?;
+        write!(self.writer, "\n{}", item.trim_end())?;
+        self.frames += 1;
+        if self.last_flush.elapsed() > FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write the buffered frames to the file
+    pub fn flush(&mut self) -> Result<()> {
+        self.last_flush = Instant::now();
+        self.writer
+            .flush()
+            .with_context(|| format!("Failed to write the session {}", self.path.display()))
+    }
+
+    /// End the session file
+    pub fn finish(&mut self) -> Result<()> {
+        if self.frames == 0 {
+            write!(self.writer, " []")?;
+        }
+        writeln!(self.writer)?;
+        self.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{UnitQuaternion, Vector3};
+    use rstest::rstest;
+
+    use crate::{thread_conductor::TrackingStatus, HandMessage, HandType};
+
+    use super::*;
+
+    #[rstest]
+    fn round_trip() {
+        let session = Session {
+            frames: vec![
+                SessionFrame {
+                    time: 0.0,
+                    event: TrackingEvent::Status(TrackingStatus::Ok),
+                },
+                SessionFrame {
+                    time: 0.01,
+                    event: TrackingEvent::Hand(HandMessage {
+                        hand_type: HandType::Right,
+                        position: Vector3::new(100.0, 250.0, -20.0),
+                        velocity: Vector3::new(1.0, 2.0, 3.0),
+                        rotation: UnitQuaternion::from_euler_angles(0.0, 0.0, 0.5),
+                        pinch: 0.2,
+                        grab: 1.0,
+                    }),
+                },
+                SessionFrame {
+                    time: 0.02,
+                    event: TrackingEvent::VisibleHands {
+                        left: false,
+                        right: true,
+                    },
+                },
+            ],
+        };
+        let yaml = serde_yaml::to_string(&session).unwrap();
+        let read = Session::from_reader(yaml.as_bytes()).unwrap();
+        assert_eq!(session, read);
+        assert_eq!(0.02, read.duration());
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(3)]
+    fn streamed_recording(#[case] frames: usize) {
+        let path = std::env::temp_dir().join(format!(
+            "theremotion-session-{}-{frames}.yaml",
+            std::process::id()
+        ));
+        let mut recorder = Recorder::new(path.clone()).unwrap();
+        for _ in 0..frames {
+            recorder
+                .record(TrackingEvent::VisibleHands {
+                    left: true,
+                    right: false,
+                })
+                .unwrap();
+        }
+        if frames > 0 {
+            // Readable before the end of the recording
+            recorder.flush().unwrap();
+            assert_eq!(frames, Session::read(&path).unwrap().frames.len());
+        }
+        recorder.finish().unwrap();
+        let session = Session::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(frames, session.frames.len());
+    }
+}
@@ -1,3 +1,4 @@
+mod saver;
 mod v1;
 mod v2;
 use std::{
@@ -18,13 +19,25 @@ use std::sync::mpsc::Sender;
 
 use crate::{
     controls::Controls,
-    solfege::{MoreScales, ScaleWindows},
+    solfege::{MelodicDirection, MoreScales, ScaleWindows},
     thread_dsp, HandType, IntervalF, MidiNoteF, OctaveInterval,
 };
 
-pub use self::v1::{EchoSettings, FxSettings, Handedness, MixSettings, NamedScale, ReverbSettings};
+pub use self::saver::SettingsSaver;
 
-pub use self::v2::{Preset, Settings};
+pub use self::v1::{
+    AudioSettings, DivisionFeel, EchoSettings, FxSettings, Geometry, Handedness, InputSettings,
+    InputSource, MixSettings, NamedScale, NoteDivision, OscSettings, PitchLayout, ReferencePitch,
+    ReplaySettings, ReverbSettings, SampleFormat, SmoothingFilter, SmoothingSettings,
+    TrackingSettings, TrackingSourceKind,
+};
+
+pub use self::v2::{
+    Arpeggiator, ArpeggioPattern, ArpeggioRate, ChordDuration, ChordTarget, Curve, DroneChord,
+    DroneProgression, HandFeature, JustIntonation, JustLimit, JustReference, LeadSheet, Modulation,
+    ModulationDestination, ModulationHand, Preset, ProgressionAdvance, Settings, Tempo,
+    TriggerGesture, Tuning, Voicing, VoicingSelector,
+};
 
 /// Default presets
 const PRESETS_BYTES: &[u8] = include_bytes!("settings/presets.yaml");
@@ -40,13 +53,13 @@ lazy_static::lazy_static! {
 #[derive(Clone, Serialize, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
 enum Version {
-    V1(v1::Settings),
-    V2(v2::Settings),
+    V1(Box<v1::Settings>),
+    V2(Box<v2::Settings>),
 }
 
 impl Default for Version {
     fn default() -> Self {
-        Version::V2(v2::Settings::default())
+        Version::V2(Default::default())
     }
 }
 
@@ -58,8 +71,8 @@ impl Settings {
     {
         let settings: Version = serde_yaml::from_reader(f)?;
         match settings {
-            Version::V1(settings) => Ok(settings.into()),
-            Version::V2(settings) => Ok(settings),
+            Version::V1(settings) => Ok((*settings).into()),
+            Version::V2(settings) => Ok(*settings),
         }
     }
 
@@ -100,7 +113,7 @@ impl Settings {
             .truncate(true)
             .create(true)
             .open(path)?;
-        let settings = Version::V2(self.clone());
+        let settings = Version::V2(Box::new(self.clone()));
         serde_yaml::to_writer(f, &settings)?;
         Ok(())
     }
@@ -108,17 +121,29 @@ impl Settings {
     pub fn system_and_user_scales(&self) -> impl Iterator<Item = (NamedScale, bool)> {
         let user_scales = self.scales.clone().into_iter().map(|s| (s, true));
         let system_scales = [
-            ("Chromatic", ScaleIntervals::all()),
-            ("Major", ScaleIntervals::major()),
-            ("Melodic Minor", ScaleIntervals::melodic_minor()),
-            ("Harmonic Minor", ScaleIntervals::harmonic_minor()),
-            ("Natural Minor", ScaleIntervals::natural_minor()),
-            ("Dorian", ScaleIntervals::dorian()),
-            ("Blues", ScaleIntervals::blues()),
-            ("Freygish", ScaleIntervals::freygish()),
-            ("Altered Dorian", ScaleIntervals::altered_dorian()),
+            ("Chromatic", ScaleIntervals::all(), None),
+            ("Major", ScaleIntervals::major(), None),
+            ("Melodic Minor", ScaleIntervals::melodic_minor(), None),
+            (
+                "Classical Melodic Minor",
+                ScaleIntervals::melodic_minor(),
+                Some(ScaleIntervals::natural_minor()),
+            ),
+            ("Harmonic Minor", ScaleIntervals::harmonic_minor(), None),
+            ("Natural Minor", ScaleIntervals::natural_minor(), None),
+            ("Dorian", ScaleIntervals::dorian(), None),
+            ("Blues", ScaleIntervals::blues(), None),
+            ("Freygish", ScaleIntervals::freygish(), None),
+            ("Altered Dorian", ScaleIntervals::altered_dorian(), None),
+            (
+                "Khamaj",
+                ScaleIntervals::khamaj_ascending(),
+                Some(ScaleIntervals::khamaj_descending()),
+            ),
         ]
-        .map(|(name, scale)| (NamedScale::new(name.to_string(), scale), false));
+        .map(|(name, scale, descending)| {
+            (NamedScale::new(name.to_string(), scale, descending), false)
+        });
         user_scales.chain(system_scales)
     }
 
@@ -144,9 +169,6 @@ impl Settings {
 }
 
 impl Preset {
-    pub fn octave_range() -> OctaveInterval {
-        OctaveInterval(3)
-    }
     /// Root note from the first octave
     pub fn root_note(&self) -> MidiNote {
         MidiNote::new(self.pitch, Octave::NEGATIVE_ONE)
@@ -177,7 +199,7 @@ impl Preset {
     }
 
     pub fn note_range(&self) -> RangeInclusive<MidiNote> {
-        self.root_note()..=(self.root_note() + Self::octave_range().into())
+        self.root_note()..=(self.root_note() + self.octave_range.into())
     }
 
     pub fn note_range_f(&self) -> RangeInclusive<MidiNoteF> {
@@ -185,55 +207,203 @@ impl Preset {
         (MidiNoteF::from(*range.start()))..=(MidiNoteF::from(*range.end()))
     }
 
+    /// Scale of the autotune when the melody goes in a direction
+    pub fn directional_scale(&self, direction: MelodicDirection) -> ScaleIntervals {
+        match (direction, self.descending_scale) {
+            (MelodicDirection::Descending, Some(descending)) => descending,
+            _ => self.scale,
+        }
+    }
+
     /// List all the notes of the current scale for the selected number of octaves
-    pub fn restricted_scale(&self) -> Vec<MidiNote> {
-        self.scale_notes(self.note_range())
+    pub fn restricted_scale(&self, direction: MelodicDirection) -> Vec<MidiNote> {
+        self.scale_notes(direction, self.note_range())
     }
 
     /// List all the notes of the current scale for the whole keyboard
-    pub fn full_scale(&self) -> Vec<MidiNote> {
-        self.scale_notes(MidiNote::from_byte(0)..=MidiNote::from_byte(127))
+    pub fn full_scale(&self, direction: MelodicDirection) -> Vec<MidiNote> {
+        self.scale_notes(direction, MidiNote::from_byte(0)..=MidiNote::from_byte(127))
     }
 
     /// Build the scale two by two floating window for the full keyboard
-    pub fn full_scale_floating_window(&self) -> ScaleWindows {
-        ScaleWindows::from_notes(self.full_scale())
+    pub fn full_scale_floating_window(&self, direction: MelodicDirection) -> ScaleWindows {
+        let range = MidiNoteF(0.0)..=MidiNoteF(127.0);
+        match self.tuning.notes(self.tuning_root(), range) {
+            Some(notes) => ScaleWindows::from_notes_f(notes),
+            None => ScaleWindows::from_notes(self.full_scale(direction)),
+        }
     }
 
     /// Build the scale two by two floating window for the selected number of octaves
-    pub fn restricted_scale_floating_window(&self) -> ScaleWindows {
-        ScaleWindows::from_notes(self.restricted_scale())
+    pub fn restricted_scale_floating_window(&self, direction: MelodicDirection) -> ScaleWindows {
+        match self.tuning.notes(self.tuning_root(), self.note_range_f()) {
+            Some(notes) => ScaleWindows::from_notes_f(notes),
+            None => ScaleWindows::from_notes(self.restricted_scale(direction)),
+        }
+    }
+
+    /// Note of the first degree of the tuning
+    pub fn tuning_root(&self) -> MidiNoteF {
+        match &self.tuning {
+            Tuning::Cents {
+                root: Some(root), ..
+            } => MidiNoteF(*root),
+            _ => self.root_note_f(),
+        }
     }
 
     /// List all the notes in the current scale for the given range
-    fn scale_notes(&self, range: RangeInclusive<MidiNote>) -> Vec<MidiNote> {
-        crate::solfege::build_scale_notes(self.pitch, self.scale, range)
+    fn scale_notes(
+        &self,
+        direction: MelodicDirection,
+        range: RangeInclusive<MidiNote>,
+    ) -> Vec<MidiNote> {
+        crate::solfege::build_scale_notes(self.pitch, self.directional_scale(direction), range)
+    }
+
+    /// Just intonation of the chords, ignored by the microtonal tunings
+    pub fn active_just_intonation(&self) -> Option<&JustIntonation> {
+        match self.tuning {
+            Tuning::TwelveTone => self.just_intonation.as_ref(),
+            _ => None,
+        }
     }
 
-    /// Send the relevant preset data to the DSP
-    pub fn send_to_dsp(&self, controls: &Controls, tx: &Sender<thread_dsp::Msg>) -> Result<()> {
-        controls.drone_detune.send(tx, self.drone.detune)?;
-        let drone_interval = self.drone_interval();
-        for (control, drone) in controls.drone_notes.iter().zip(self.drone_notes()) {
+    /// Note of a drone voice sent to the DSP
+    pub fn drone_note_f(&self, drone: MidiNote, reference_pitch: &ReferencePitch) -> MidiNoteF {
+        let note = MidiNoteF::from(drone + self.drone_interval());
+        let note = match self.active_just_intonation() {
+            Some(just_intonation) => just_intonation.retune(self.root_note_f(), note),
+            None => note,
+        };
+        note + reference_pitch.offset()
+    }
+
+    /// Send the notes of a drone chord to the DSP
+    pub fn send_drone_notes(
+        &self,
+        drone_chord: usize,
+        reference_pitch: &ReferencePitch,
+        controls: &Controls,
+        tx: &Sender<thread_dsp::Msg>,
+    ) -> Result<()> {
+        for (control, drone) in controls
+            .drone_notes
+            .iter()
+            .zip(self.drone_notes(drone_chord))
+        {
             if let Some(drone) = drone {
-                control
-                    .note
-                    .send(tx, ((drone + drone_interval).into_byte()) as f32)?;
+                let note = self.drone_note_f(drone, reference_pitch);
+                control.note.send(tx, note.note())?;
             }
         }
+        Ok(())
+    }
+
+    /// Send the relevant preset data to the DSP
+    pub fn send_to_dsp(
+        &self,
+        reference_pitch: &ReferencePitch,
+        drone_chord: usize,
+        controls: &Controls,
+        tx: &Sender<thread_dsp::Msg>,
+    ) -> Result<()> {
+        controls.drone_detune.send(tx, self.drone.detune)?;
+        self.send_drone_notes(drone_chord, reference_pitch, controls, tx)?;
 
         self.mix.send_to_dsp(controls, tx)?;
-        self.fx.send_to_dsp(controls, tx)?;
+        self.fx.send_to_dsp(&self.tempo, controls, tx)?;
         Ok(())
     }
 
-    pub fn drone_notes(&self) -> [Option<MidiNote>; 4] {
+    /// Names and response curves of the hand mappings
+    pub fn mapping_curves(&self) -> Vec<(String, &Curve)> {
+        let modulations = self.modulations.iter().map(|m| {
+            (
+                format!("{:?} hand {:?} → {:?}", m.hand, m.source, m.destination),
+                &m.curve,
+            )
+        });
+        let others = [
+            ("Pitch hand height → Chord notes", &self.curves.chords),
+            ("Pitch hand pinch → Autotune", &self.curves.autotune),
+        ]
+        .map(|(name, curve)| (name.to_string(), curve));
+        modulations.chain(others).collect()
+    }
+
+    /// Response curve of a hand mapping, in the order of `mapping_curves`
+    pub fn mapping_curve_mut(&mut self, index: usize) -> Option<&mut Curve> {
+        match index.checked_sub(self.modulations.len()) {
+            None => Some(&mut self.modulations[index].curve),
+            Some(0) => Some(&mut self.curves.chords),
+            Some(1) => Some(&mut self.curves.autotune),
+            Some(_) => None,
+        }
+    }
+
+    /// Notes of a drone chord
+    pub fn drone_notes(&self, drone_chord: usize) -> [Option<MidiNote>; 4] {
         let root_note = self.root_note();
-        self.drone
-            .intervals
+        self.drone_intervals(drone_chord)
             .map(|drone| drone.map(|drone| root_note + drone))
     }
 
+    /// Intervals of a drone chord, the chord of the progression if any
+    pub fn drone_intervals(&self, drone_chord: usize) -> [Option<Interval>; 4] {
+        match &self.drone.progression {
+            Some(progression) if !progression.chords.is_empty() => {
+                progression.chords[drone_chord % progression.chords.len()].intervals
+            }
+            _ => self.drone.intervals,
+        }
+    }
+
+    pub fn drone_intervals_mut(&mut self, drone_chord: usize) -> &mut [Option<Interval>; 4] {
+        match &mut self.drone.progression {
+            Some(progression) if !progression.chords.is_empty() => {
+                let len = progression.chords.len();
+                &mut progression.chords[drone_chord % len].intervals
+            }
+            _ => &mut self.drone.intervals,
+        }
+    }
+
+    /// Insert a copy of a drone chord after it, returns the index of the copy
+    ///
+    /// The first copy starts the progression from the intervals.
+    pub fn add_drone_chord(&mut self, drone_chord: usize) -> usize {
+        let intervals = self.drone.intervals;
+        let progression = self.drone.progression.get_or_insert_with(Default::default);
+        if progression.chords.is_empty() {
+            progression.chords.push(DroneChord {
+                intervals,
+                duration: Default::default(),
+            });
+        }
+        let drone_chord = drone_chord % progression.chords.len();
+        let copy = progression.chords[drone_chord].clone();
+        progression.chords.insert(drone_chord + 1, copy);
+        drone_chord + 1
+    }
+
+    /// Remove a drone chord, the last one left becomes the intervals
+    pub fn remove_drone_chord(&mut self, drone_chord: usize) {
+        let Some(progression) = &mut self.drone.progression else {
+            return;
+        };
+        if !progression.chords.is_empty() {
+            let len = progression.chords.len();
+            progression.chords.remove(drone_chord % len);
+        }
+        if progression.chords.len() <= 1 {
+            if let Some(last) = progression.chords.pop() {
+                self.drone.intervals = last.intervals;
+            }
+            self.drone.progression = None;
+        }
+    }
+
     pub fn system_presets() -> &'static Vec<Self> {
         &PRESETS
     }
@@ -251,13 +421,35 @@ impl MixSettings {
         controls.mix_lead_volume.send(tx, self.lead)?;
         controls.mix_master_volume.send(tx, self.master)?;
         controls.mix_pluck_volume.send(tx, self.guitar)?;
+        controls.mix_drone_pan.send(tx, self.drone_pan)?;
+        controls.mix_lead_pan.send(tx, self.lead_pan)?;
+        controls.mix_pluck_pan.send(tx, self.guitar_pan)?;
+        tx.send(thread_dsp::Msg::Stereo {
+            balance: self.balance,
+            width: self.width,
+        })?;
+        controls.mix_input_volume.send(tx, self.input)?;
+        controls.mix_vocoder.send(tx, self.vocoder);
         Ok(())
     }
 }
 
 impl EchoSettings {
-    pub fn send_to_dsp(&self, controls: &Controls, tx: &Sender<thread_dsp::Msg>) -> Result<()> {
-        controls.echo_duration.send(tx, self.duration)?;
+    /// Echo duration (s), following the tempo when synced
+    pub fn effective_duration(&self, tempo: &Tempo) -> f32 {
+        self.sync
+            .map_or(self.duration, |division| tempo.duration(&division))
+    }
+
+    pub fn send_to_dsp(
+        &self,
+        tempo: &Tempo,
+        controls: &Controls,
+        tx: &Sender<thread_dsp::Msg>,
+    ) -> Result<()> {
+        controls
+            .echo_duration
+            .send(tx, self.effective_duration(tempo))?;
         controls.echo_feedback.send(tx, self.feedback)?;
         controls.echo_mix.send(tx, self.mix)?;
         Ok(())
@@ -275,8 +467,13 @@ impl ReverbSettings {
 }
 
 impl FxSettings {
-    pub fn send_to_dsp(&self, controls: &Controls, tx: &Sender<thread_dsp::Msg>) -> Result<()> {
-        self.echo.send_to_dsp(controls, tx)?;
+    pub fn send_to_dsp(
+        &self,
+        tempo: &Tempo,
+        controls: &Controls,
+        tx: &Sender<thread_dsp::Msg>,
+    ) -> Result<()> {
+        self.echo.send_to_dsp(tempo, controls, tx)?;
         self.reverb.send_to_dsp(controls, tx)?;
         Ok(())
     }
@@ -301,7 +498,7 @@ mod tests {
         assert_eq!(Octave::THREE, settings.current_preset.guitar_octave);
         assert_eq!(
             MidiNote::from_byte(50),
-            settings.current_preset.drone_notes()[0].unwrap()
+            settings.current_preset.drone_notes(0)[0].unwrap()
                 + settings.current_preset.drone_interval()
         );
         assert_eq!(None, settings.current_preset.drone.intervals[1]);
@@ -310,6 +507,40 @@ mod tests {
         assert_eq!(Volume(0.8), settings.current_preset.fx.reverb.mix);
     }
 
+    #[rstest]
+    fn descending_scale() {
+        let mut preset = Preset {
+            scale: ScaleIntervals::melodic_minor(),
+            descending_scale: Some(ScaleIntervals::natural_minor()),
+            ..Default::default()
+        };
+        let sixth = MidiNote::from_byte(9);
+        assert!(preset
+            .restricted_scale(MelodicDirection::Ascending)
+            .contains(&sixth));
+        assert!(!preset
+            .restricted_scale(MelodicDirection::Descending)
+            .contains(&sixth));
+        preset.descending_scale = None;
+        assert!(preset
+            .restricted_scale(MelodicDirection::Descending)
+            .contains(&sixth));
+    }
+
+    #[rstest]
+    fn drone_progression() {
+        let fifth = [Some(Interval::new(0)), Some(Interval::new(7)), None, None];
+        let mut preset = Preset::default();
+        preset.drone.intervals = fifth;
+        assert_eq!(1, preset.add_drone_chord(0));
+        preset.drone_intervals_mut(1)[1] = Some(Interval::new(9));
+        assert_eq!(fifth, preset.drone_intervals(0));
+        assert_eq!(Some(Interval::new(9)), preset.drone_intervals(3)[1]);
+        preset.remove_drone_chord(0);
+        assert_eq!(None, preset.drone.progression);
+        assert_eq!(Some(Interval::new(9)), preset.drone_intervals(0)[1]);
+    }
+
     #[rstest]
     fn default() {
         // Dynamically deserialized at runtime...
@@ -0,0 +1,102 @@
+use std::{
+    sync::mpsc::{Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+
+use super::Settings;
+
+/// Longest delay of a settings change before being written, the changes
+/// made meanwhile being written at once
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Writes the settings from its own thread, so that the changes made while
+/// playing neither wait for the file nor write it at every change
+///
+/// The pending settings are written when it is dropped.
+pub struct SettingsSaver {
+    tx: Option<Sender<Settings>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl SettingsSaver {
+    /// Start the thread, saving the settings with the function
+    pub fn new<F>(save: F) -> Self
+    where
+        F: Fn(&Settings) -> Result<()> + Send + 'static,
+    {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("settings_saver".to_string())
+            .spawn(move || save_settings(rx, save))
+            .expect("Failed to spawn the settings saver thread");
+        Self {
+            tx: Some(tx),
+            thread: Some(thread),
+        }
+    }
+
+    /// Write the settings soon, replacing the pending ones
+    pub fn save(&self, settings: Settings) {
+        if let Some(tx) = &self.tx {
+            tx.send(settings).ok();
+        }
+    }
+}
+
+impl Drop for SettingsSaver {
+    fn drop(&mut self) {
+        self.tx = None;
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+fn save_settings<F>(rx: Receiver<Settings>, save: F)
+where
+    F: Fn(&Settings) -> Result<()>,
+{
+    while let Ok(mut settings) = rx.recv() {
+        let deadline = Instant::now() + SAVE_DELAY;
+        while let Ok(newer) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            settings = newer;
+        }
+        if let Err(err) = save(&settings) {
+            log::error!("Failed to save the settings: {err:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    fn saver_gathers_changes() {
+        let saved = Arc::new(Mutex::new(vec![]));
+        let saver = {
+            let saved = saved.clone();
+            SettingsSaver::new(move |settings| {
+                saved
+                    .lock()
+                    .unwrap()
+                    .push(settings.current_preset.name.clone());
+                Ok(())
+            })
+        };
+        for name in ["a", "b", "c"] {
+            let mut settings = Settings::default();
+            settings.current_preset.name = name.to_string();
+            saver.save(settings);
+        }
+        drop(saver);
+        assert_eq!(vec!["c".to_string()], *saved.lock().unwrap());
+    }
+}
@@ -1,6 +1,8 @@
 use std::{
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher},
+    ops::RangeInclusive,
+    path::PathBuf,
 };
 
 use serde::{Deserialize, Serialize};
@@ -10,7 +12,7 @@ use staff::{
     Pitch,
 };
 
-use crate::{OctaveInterval, Volume};
+use crate::{pitch_layout, reference_pitch::STANDARD_A4, OctaveInterval, Volume};
 
 /// Application settings
 #[derive(Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -96,6 +98,21 @@ pub struct MixSettings {
     pub guitar: Volume,
     /// Drone volume
     pub drone: Volume,
+    /// Position of the lead synthesizer from the left (-1) to the right (1)
+    pub lead_pan: f32,
+    /// Position of the guitar from the left (-1) to the right (1)
+    pub guitar_pan: f32,
+    /// Position of the drone from the left (-1) to the right (1)
+    pub drone_pan: f32,
+    /// Balance of the output from the left (-1) to the right (1), attenuating
+    /// the other side
+    pub balance: f32,
+    /// Stereo width of the output from mono (0) to as played (1) and wider (2)
+    pub width: f32,
+    /// Volume of the audio input heard with the instrument
+    pub input: Volume,
+    /// Whether the lead takes the spectral envelope of the audio input
+    pub vocoder: bool,
 }
 
 impl Default for MixSettings {
@@ -105,6 +122,13 @@ impl Default for MixSettings {
             lead: Volume(1.0),
             guitar: Volume(1.0),
             drone: Volume(0.14),
+            lead_pan: 0.0,
+            guitar_pan: 0.0,
+            drone_pan: 0.0,
+            balance: 0.0,
+            width: 1.0,
+            input: Volume(0.0),
+            vocoder: false,
         }
     }
 }
@@ -129,6 +153,8 @@ pub struct EchoSettings {
     pub duration: f32,
     /// Echo feedback (0-1)
     pub feedback: f32,
+    /// Echo duration following the tempo, instead of the duration
+    pub sync: Option<NoteDivision>,
 }
 
 impl Default for EchoSettings {
@@ -137,10 +163,33 @@ impl Default for EchoSettings {
             mix: Volume(1.0),
             duration: 0.3,
             feedback: 0.3,
+            sync: None,
         }
     }
 }
 
+/// Note value, like a dotted eighth note
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct NoteDivision {
+    /// Fraction of a whole note, 4 for a quarter note
+    pub fraction: u32,
+    /// Dotted or triplet variation of the note
+    #[serde(default)]
+    pub feel: DivisionFeel,
+}
+
+/// Variation of a note value
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DivisionFeel {
+    #[default]
+    Straight,
+    /// One and a half times longer
+    Dotted,
+    /// Three in the time of two
+    Triplet,
+}
+
 /// Reverb settings
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields, default)]
@@ -185,7 +234,7 @@ impl Default for DroneSettings {
     }
 }
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct System {
     /// Start theremotion in full screen
@@ -202,6 +251,317 @@ pub struct System {
 
     #[serde(default)]
     pub handedness: Handedness,
+
+    /// Source of the hand tracking
+    #[serde(default)]
+    pub tracking: TrackingSettings,
+
+    /// Filtering of the hand tracking data
+    #[serde(default)]
+    pub smoothing: SmoothingSettings,
+
+    /// Playing positions, adjusted by the calibration
+    #[serde(default)]
+    pub geometry: Geometry,
+
+    /// Concert pitch of the presets without their own
+    #[serde(default)]
+    pub reference_pitch: ReferencePitch,
+
+    /// Audio output device and stream configuration
+    #[serde(default)]
+    pub audio: AudioSettings,
+
+    /// Sound processed by the instrument
+    #[serde(default)]
+    pub input: InputSettings,
+}
+
+/// Audio output, the defaults of the system for the missing values
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct AudioSettings {
+    /// Name of the audio host, like ALSA, JACK, WASAPI or ASIO
+    pub host: Option<String>,
+
+    /// Name of the output device
+    pub device: Option<String>,
+
+    /// Sample rate (Hz)
+    pub sample_rate: Option<u32>,
+
+    /// Frames computed per buffer
+    pub buffer_size: Option<u32>,
+
+    /// Format of the samples sent to the device
+    pub sample_format: Option<SampleFormat>,
+}
+
+/// Audio input, the default device of the system for the missing values
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct InputSettings {
+    pub source: InputSource,
+
+    /// Name of the audio host of the input device
+    pub host: Option<String>,
+
+    /// Name of the input device
+    pub device: Option<String>,
+
+    /// WAV file played in a loop as input
+    pub file: Option<PathBuf>,
+}
+
+/// Origin of the audio input
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum InputSource {
+    /// No input
+    #[default]
+    None,
+    /// Input device, like a microphone or a line input
+    Device,
+    /// WAV file of the settings
+    File,
+}
+
+/// Format of the audio samples, converted from the 32 bits float of the DSP
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SampleFormat {
+    F32,
+    I16,
+    U16,
+    I32,
+}
+
+/// Concert pitch of the instrument
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct ReferencePitch {
+    /// Frequency of the A4 (Hz)
+    pub a4: f32,
+
+    /// Fine transposition of all the notes (cents)
+    pub cents: f32,
+}
+
+impl Default for ReferencePitch {
+    fn default() -> Self {
+        Self {
+            a4: STANDARD_A4,
+            cents: 0.0,
+        }
+    }
+}
+
+/// Positions of the hands mapped to the instrument controls
+///
+/// The positions are in mm, from the body: the x axis goes away from the body
+/// for both hands.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct Geometry {
+    /// Position of the pitch antenna on the x axis
+    pub antenna_x: f32,
+
+    /// Position of the pitch antenna on the z axis
+    pub antenna_z: f32,
+
+    /// Distance from the antenna between two semitones
+    pub mm_per_semitone: f32,
+
+    /// Layout of the notes around the antenna
+    pub pitch_layout: PitchLayout,
+
+    /// Height of the pitch hand from one to four notes
+    pub chords_height: RangeInclusive<f32>,
+
+    /// Height of the volume hand from silent to full volume
+    pub volume_height: RangeInclusive<f32>,
+
+    /// Distance of the volume hand from the lowest to the highest cutoff
+    pub cutoff_distance: RangeInclusive<f32>,
+
+    /// Depth of the volume hand from the lowest to the highest resonance
+    pub resonance_depth: RangeInclusive<f32>,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self {
+            antenna_x: 400.0,
+            antenna_z: -200.0,
+            mm_per_semitone: 15.0,
+            pitch_layout: Default::default(),
+            chords_height: 350.0..=500.0,
+            volume_height: 300.0..=400.0,
+            cutoff_distance: 50.0..=200.0,
+            resonance_depth: 100.0..=-100.0,
+        }
+    }
+}
+
+/// Layout of the notes around the pitch antenna
+///
+/// The notes go down from the highest one, at the antenna, to the lowest one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum PitchLayout {
+    /// Horizontal distance to the antenna, evenly spaced notes
+    #[default]
+    Circular,
+    /// Distance to the antenna along the x axis only, evenly spaced notes
+    Linear,
+    /// Classic theremin rod: horizontal distance to the antenna, the notes
+    /// get closer to each other near the antenna
+    VerticalRod {
+        /// Distance added to the hand distance (mm), the lower the tighter
+        /// the notes near the antenna
+        #[serde(default = "default_rod_offset")]
+        offset: f32,
+    },
+    /// Horizontal distance to the antenna, with a width for each octave
+    Octaves {
+        /// Width of the octaves from the highest one (mm), the last one is
+        /// repeated. Growing widths give a logarithmic layout.
+        #[serde(default = "default_octave_widths")]
+        widths: Vec<f32>,
+    },
+}
+
+fn default_rod_offset() -> f32 {
+    pitch_layout::DEFAULT_ROD_OFFSET
+}
+
+fn default_octave_widths() -> Vec<f32> {
+    pitch_layout::DEFAULT_OCTAVE_WIDTHS.to_vec()
+}
+
+/// Hand tracking settings
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct TrackingSettings {
+    /// Selected tracking source
+    pub source: TrackingSourceKind,
+
+    /// Settings of the replay source
+    pub replay: ReplaySettings,
+
+    /// Settings of the OSC source
+    pub osc: OscSettings,
+}
+
+/// Kind of hand tracking source
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub enum TrackingSourceKind {
+    /// Leap Motion device
+    #[default]
+    Leap,
+    /// Replay of a recorded session
+    Replay,
+    /// Generated hand motions
+    Synthetic,
+    /// Virtual hands driven by the mouse or a touchscreen
+    Pointer,
+    /// External tracker sending Open Sound Control messages
+    Osc,
+}
+
+/// Open Sound Control input
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct OscSettings {
+    /// UDP address to listen on
+    pub address: String,
+}
+
+impl Default for OscSettings {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:9000".to_string(),
+        }
+    }
+}
+
+/// Replay of a recorded session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct ReplaySettings {
+    /// Session file to replay
+    pub path: Option<PathBuf>,
+
+    /// Playback speed factor (2.0 is twice as fast)
+    pub speed: f64,
+
+    /// Restart from the beginning at the end of the session
+    pub looping: bool,
+}
+
+impl Default for ReplaySettings {
+    fn default() -> Self {
+        Self {
+            path: None,
+            speed: 1.0,
+            looping: false,
+        }
+    }
+}
+
+/// Filters applied to each channel of the hand tracking data
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct SmoothingSettings {
+    /// Palm position
+    pub position: SmoothingFilter,
+
+    /// Palm velocity
+    pub velocity: SmoothingFilter,
+
+    /// Arm rotation
+    pub rotation: SmoothingFilter,
+
+    /// Pinch strength
+    pub pinch: SmoothingFilter,
+
+    /// Grab strength
+    pub grab: SmoothingFilter,
+}
+
+/// Filter reducing the jitter of a tracking channel
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum SmoothingFilter {
+    /// Raw tracking data
+    #[default]
+    None,
+
+    /// One Euro filter, adapting its cutoff to the speed of the motion
+    OneEuro {
+        /// Cutoff frequency when still (Hz)
+        min_cutoff: f32,
+        /// Cutoff increase with the speed
+        beta: f32,
+        /// Cutoff frequency of the speed estimation (Hz)
+        #[serde(default = "default_derivative_cutoff")]
+        derivative_cutoff: f32,
+    },
+
+    /// Exponential moving average
+    Exponential {
+        /// Weight of the new samples, from 0 (frozen) to 1 (raw)
+        alpha: f32,
+    },
+
+    /// Median of the last samples
+    Median {
+        /// Number of samples
+        window: usize,
+    },
+}
+
+fn default_derivative_cutoff() -> f32 {
+    1.0
 }
 
 /// Left or right handed mode
@@ -232,12 +592,18 @@ pub struct NamedScale {
     pub name: String,
     /// Corresponding scale
     pub scale: ScaleIntervals,
+    /// Notes played when the melody goes down, if they differ
+    pub descending: Option<ScaleIntervals>,
 }
 
 impl NamedScale {
     /// Creates a new [`NamedScale`].
-    pub fn new(name: String, scale: ScaleIntervals) -> Self {
-        Self { name, scale }
+    pub fn new(name: String, scale: ScaleIntervals, descending: Option<ScaleIntervals>) -> Self {
+        Self {
+            name,
+            scale,
+            descending,
+        }
     }
 
     /// Pseudo hash for identification in the ui
@@ -245,6 +611,7 @@ impl NamedScale {
         let mut hasher = DefaultHasher::default();
         self.name.hash(&mut hasher);
         self.scale.bits.hash(&mut hasher);
+        self.descending.map(|scale| scale.bits).hash(&mut hasher);
         hasher.finish() as i32
     }
 }
@@ -1,4 +1,7 @@
+use std::{ops::RangeInclusive, path::PathBuf};
+
 use super::v1;
+use crate::{arpeggiator, curve, modulation, tempo, OctaveInterval};
 use serde::{Deserialize, Serialize};
 use staff::{
     midi::{MidiNote, Octave},
@@ -38,6 +41,91 @@ pub struct DroneSettings {
     pub detune: f32,
     /// Enable the pluck drone
     pub pluck_drone: bool,
+    /// Chords of the drone following one another, replacing the intervals
+    pub progression: Option<DroneProgression>,
+}
+
+/// Sequence of drone chords, played in a loop
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct DroneProgression {
+    /// Chords of the sequence
+    pub chords: Vec<DroneChord>,
+    /// How the sequence moves to the next chord
+    pub advance: ProgressionAdvance,
+}
+
+/// Chord of a drone progression
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct DroneChord {
+    /// Intervals of the notes from the root
+    #[serde(with = "interval_list_serde")]
+    pub intervals: [Option<Interval>; 4],
+    /// Time the chord is played before the next one
+    pub duration: ChordDuration,
+}
+
+/// Time a drone chord is played
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum ChordDuration {
+    /// Bars of the tempo
+    Bars { bars: f32 },
+    /// Fixed time
+    Seconds { seconds: f32 },
+}
+
+impl Default for ChordDuration {
+    fn default() -> Self {
+        Self::Bars { bars: 1.0 }
+    }
+}
+
+/// How a drone progression moves to the next chord
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum ProgressionAdvance {
+    /// At the end of each chord, following the tempo
+    #[default]
+    Tempo,
+    /// On the next chord button or a gesture
+    Manual {
+        #[serde(default)]
+        gesture: Option<TriggerGesture>,
+    },
+}
+
+/// Chord chart followed by the scale and the drone
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct LeadSheet {
+    /// Chord symbols of the bars, like `| Dm7 | G7 | Cmaj7 |`
+    pub chart: String,
+    /// Notes of the chord the autotune and the chords snap to
+    pub target: ChordTarget,
+    /// Whether the drone plays the chord tones
+    pub drone: bool,
+}
+
+impl Default for LeadSheet {
+    fn default() -> Self {
+        Self {
+            chart: String::new(),
+            target: Default::default(),
+            drone: true,
+        }
+    }
+}
+
+/// Notes of a lead sheet chord used as the scale
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ChordTarget {
+    /// Only the notes of the chord
+    #[default]
+    ChordTones,
+    /// The scale played over the chord
+    ChordScale,
 }
 
 /// Proxy the interval (de)serialization to flatten it
@@ -75,6 +163,441 @@ mod interval_list_serde {
     }
 }
 
+/// Hand driving a modulation
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ModulationHand {
+    Pitch,
+    Volume,
+}
+
+/// Measurement of a hand used as a modulation source
+///
+/// Positions and velocities are from the body: the x axis goes away from the
+/// body for both hands.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HandFeature {
+    /// Distance from the body (mm)
+    PositionX,
+    /// Height (mm)
+    PositionY,
+    /// Depth, towards the player (mm)
+    PositionZ,
+    /// Speed away from the body (mm/s)
+    VelocityX,
+    /// Vertical speed (mm/s)
+    VelocityY,
+    /// Speed towards the player (mm/s)
+    VelocityZ,
+    /// Sum of the horizontal speeds (mm/s)
+    VelocityXZ,
+    /// Absolute vertical speed (mm/s)
+    VerticalSpeed,
+    /// Pinch strength (0-1)
+    Pinch,
+    /// Grab strength (0-1)
+    Grab,
+    /// Rotation of the palm from the body, only defined facing up or down (rad)
+    Rotation,
+}
+
+/// DSP control driven by a modulation
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ModulationDestination {
+    LeadVolume,
+    CutoffNote,
+    Resonance,
+    PluckMute,
+    DroneDetune,
+    DroneTrumpet,
+    PitchBend,
+    EchoMix,
+    EchoDuration,
+    EchoFeedback,
+    ReverbMix,
+    ReverbTime,
+    ReverbDamp,
+    ReverbSize,
+    ReverbEarlyDiff,
+    ReverbModDepth,
+    ReverbModFreq,
+    MasterVolume,
+    DroneVolume,
+    LeadMixVolume,
+    GuitarVolume,
+}
+
+/// Response curve of a hand mapping
+///
+/// Shapes the hand measurement normalized on its range (0-1) into the
+/// normalized control value (0-1).
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum Curve {
+    #[default]
+    Linear,
+    /// Slow start, fast end
+    Exponential {
+        #[serde(default = "default_steepness")]
+        steepness: f32,
+    },
+    /// Fast start, slow end
+    Logarithmic {
+        #[serde(default = "default_steepness")]
+        steepness: f32,
+    },
+    /// Slow at both ends, fast in the middle
+    Sigmoid {
+        #[serde(default = "default_sigmoid_steepness")]
+        steepness: f32,
+    },
+    /// Evenly spaced plateaus
+    Stepped {
+        #[serde(default = "default_steps")]
+        steps: u32,
+    },
+    /// Linear interpolation between (input, output) points
+    Breakpoints { points: Vec<(f32, f32)> },
+}
+
+fn default_steepness() -> f32 {
+    curve::DEFAULT_STEEPNESS
+}
+
+fn default_sigmoid_steepness() -> f32 {
+    curve::DEFAULT_SIGMOID_STEEPNESS
+}
+
+fn default_steps() -> u32 {
+    curve::DEFAULT_STEPS
+}
+
+/// Response curves of the mappings that are not in the modulation matrix
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct MappingCurves {
+    /// Height of the pitch hand to the number of notes of the chord
+    pub chords: Curve,
+
+    /// Pinch of the pitch hand to the autotune strength
+    pub autotune: Curve,
+}
+
+/// Mapping of a hand measurement to a DSP control
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Modulation {
+    /// Hand driving the control
+    pub hand: ModulationHand,
+
+    /// Measurement of the hand
+    pub source: HandFeature,
+
+    /// Driven control
+    pub destination: ModulationDestination,
+
+    /// Range of the measurement, by default the calibrated or usual range of
+    /// the source
+    #[serde(default)]
+    pub input: Option<RangeInclusive<f32>>,
+
+    /// Part of the control range that is covered (0-1)
+    #[serde(default = "full_range")]
+    pub output: RangeInclusive<f32>,
+
+    /// Response curve
+    #[serde(default)]
+    pub curve: Curve,
+
+    /// Decrease the control when the measurement increases
+    #[serde(default)]
+    pub invert: bool,
+}
+
+fn full_range() -> RangeInclusive<f32> {
+    0.0..=1.0
+}
+
+impl Modulation {
+    pub fn new(
+        hand: ModulationHand,
+        source: HandFeature,
+        destination: ModulationDestination,
+    ) -> Self {
+        Self {
+            hand,
+            source,
+            destination,
+            input: None,
+            output: full_range(),
+            curve: Curve::Linear,
+            invert: false,
+        }
+    }
+}
+
+/// Notes of the chord played by the four voices, from the played note
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum Voicing {
+    /// Degrees of the scale, 0 being the played note
+    Degrees { degrees: [isize; 4] },
+    /// Fixed chromatic intervals (semitones), like a parallel harmonizer
+    Intervals { semitones: [f32; 4] },
+}
+
+impl Default for Voicing {
+    fn default() -> Self {
+        Self::Degrees {
+            degrees: [0, 2, 4, 7],
+        }
+    }
+}
+
+/// Pitches of the notes played by the autotune and the chords
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum Tuning {
+    /// Twelve-tone equal temperament, with the scale of the preset
+    #[default]
+    TwelveTone,
+    /// Equal divisions of the octave
+    Edo {
+        /// Number of divisions of the octave
+        divisions: u32,
+        /// Degrees played (0 being the root note), all of them by default
+        #[serde(default)]
+        degrees: Option<Vec<usize>>,
+    },
+    /// Degrees in cents from the root note, usually imported from a Scala file
+    Cents {
+        /// Description of the tuning
+        name: String,
+        /// Cents of the degrees after the root note, the last one is the
+        /// period, usually the octave (1200)
+        cents: Vec<f32>,
+        /// Degrees played (0 being the root note), all of them by default
+        #[serde(default)]
+        degrees: Option<Vec<usize>>,
+        /// Note of the root (fractional MIDI note, A4 being 440 Hz), by
+        /// default the root note of the preset. When set, the concert pitch
+        /// does not apply.
+        #[serde(default)]
+        root: Option<f32>,
+    },
+}
+
+/// Gesture selecting the voicing among the voicings of the preset
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct VoicingSelector {
+    /// Hand selecting the voicing
+    pub hand: ModulationHand,
+
+    /// Measurement of the hand, its range is split evenly between the voicings
+    pub source: HandFeature,
+
+    /// Range of the measurement, by default the calibrated or usual range of
+    /// the source
+    #[serde(default)]
+    pub input: Option<RangeInclusive<f32>>,
+}
+
+impl Default for VoicingSelector {
+    fn default() -> Self {
+        Self {
+            hand: ModulationHand::Volume,
+            source: HandFeature::Grab,
+            input: None,
+        }
+    }
+}
+
+/// Retuning of the chord and drone voices to pure ratios
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct JustIntonation {
+    /// Note the ratios are relative to
+    pub reference: JustReference,
+
+    /// Highest prime factor of the ratios
+    pub limit: JustLimit,
+}
+
+/// Note the just intonation ratios are relative to
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum JustReference {
+    /// Root note of the preset, the played note is retuned too
+    #[default]
+    Root,
+    /// Played note, kept equal-tempered, only the other chord voices are
+    /// retuned
+    PlayedNote,
+}
+
+/// Highest prime factor of the just intonation ratios
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum JustLimit {
+    /// Ratios of 2, 3 and 5: pure fifths and thirds
+    #[default]
+    FiveLimit,
+    /// Ratios of 2, 3, 5 and 7: adds the harmonic seventh and tritone
+    SevenLimit,
+}
+
+/// Chord voices played one after the other instead of together
+///
+/// The number of chord notes selected by the height of the pitch hand is the
+/// length of the arpeggio.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct Arpeggiator {
+    /// Order of the chord voices
+    pub pattern: ArpeggioPattern,
+
+    /// Speed of the steps
+    pub rate: ArpeggioRate,
+
+    /// Part of each step the voice sounds (0-1)
+    pub gate: f32,
+}
+
+impl Default for Arpeggiator {
+    fn default() -> Self {
+        Self {
+            pattern: Default::default(),
+            rate: Default::default(),
+            gate: arpeggiator::DEFAULT_GATE,
+        }
+    }
+}
+
+/// Order of the chord voices of the arpeggiator
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ArpeggioPattern {
+    /// From the lowest note to the highest one
+    #[default]
+    Up,
+    /// From the highest note to the lowest one
+    Down,
+    /// Up then down, without repeating the ends
+    UpDown,
+    /// Random voice on each step
+    Random,
+    /// Order of the voicing degrees
+    AsPlayed,
+}
+
+/// Speed of the arpeggiator steps
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum ArpeggioRate {
+    /// Steps following the tempo of the preset
+    Tempo {
+        /// Note value of a step
+        #[serde(default = "default_arpeggio_division")]
+        division: v1::NoteDivision,
+    },
+    /// Steps following a measurement of a hand, like its speed
+    Hand {
+        /// Hand driving the rate
+        hand: ModulationHand,
+        /// Measurement of the hand
+        source: HandFeature,
+        /// Range of the measurement, by default the calibrated or usual range
+        /// of the source
+        #[serde(default)]
+        input: Option<RangeInclusive<f32>>,
+        /// Steps per second from the lowest to the highest measurement
+        #[serde(default = "default_arpeggio_hand_rate")]
+        steps_per_second: RangeInclusive<f32>,
+    },
+}
+
+impl Default for ArpeggioRate {
+    fn default() -> Self {
+        Self::Tempo {
+            division: default_arpeggio_division(),
+        }
+    }
+}
+
+fn default_arpeggio_division() -> v1::NoteDivision {
+    arpeggiator::DEFAULT_DIVISION
+}
+
+fn default_arpeggio_hand_rate() -> RangeInclusive<f32> {
+    arpeggiator::DEFAULT_HAND_RATE
+}
+
+/// Tempo of the rhythmic features
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct Tempo {
+    /// Beats per minute
+    pub bpm: f32,
+
+    /// Beats in a bar, the upper number of the time signature
+    pub beats_per_bar: u32,
+
+    /// Note value of a beat, the lower number of the time signature
+    pub beat_unit: u32,
+
+    /// Gesture tapping the tempo, along with the tap button
+    pub tap_gesture: Option<TriggerGesture>,
+}
+
+impl Default for Tempo {
+    fn default() -> Self {
+        Self {
+            bpm: tempo::DEFAULT_BPM,
+            beats_per_bar: 4,
+            beat_unit: 4,
+            tap_gesture: None,
+        }
+    }
+}
+
+/// Hand movement triggering an action when it goes over a threshold
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TriggerGesture {
+    /// Hand doing the gesture
+    pub hand: ModulationHand,
+
+    /// Measurement of the hand
+    pub source: HandFeature,
+
+    /// Range of the measurement, by default the calibrated or usual range of
+    /// the source
+    #[serde(default)]
+    pub input: Option<RangeInclusive<f32>>,
+
+    /// Part of the range to go over to trigger (0-1)
+    #[serde(default = "default_trigger_threshold")]
+    pub threshold: f32,
+}
+
+fn default_trigger_threshold() -> f32 {
+    modulation::DEFAULT_TRIGGER_THRESHOLD
+}
+
+/// Modulations of the original instrument
+fn default_modulations() -> Vec<Modulation> {
+    use HandFeature::*;
+    use ModulationDestination::*;
+    vec![
+        Modulation::new(ModulationHand::Pitch, VelocityXZ, PitchBend),
+        Modulation::new(ModulationHand::Pitch, VerticalSpeed, DroneTrumpet),
+        Modulation {
+            input: Some(0.0..=(std::f32::consts::FRAC_PI_2 - 0.2)),
+            ..Modulation::new(ModulationHand::Volume, Rotation, PluckMute)
+        },
+        Modulation::new(ModulationHand::Volume, PositionX, CutoffNote),
+        Modulation::new(ModulationHand::Volume, PositionZ, Resonance),
+        Modulation::new(ModulationHand::Volume, PositionY, LeadVolume),
+    ]
+}
+
 /// Sound preset
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields, default)]
@@ -94,9 +617,16 @@ pub struct Preset {
     /// Pitch of the root note
     pub pitch: Pitch,
 
+    /// Number of octaves played by the pitch hand
+    pub octave_range: OctaveInterval,
+
     /// Scale of the autotune
     pub scale: ScaleIntervals,
 
+    /// Scale of the autotune when the melody goes down, `scale` is used both
+    /// ways when absent
+    pub descending_scale: Option<ScaleIntervals>,
+
     /// Current drone
     pub drone: DroneSettings,
 
@@ -105,6 +635,41 @@ pub struct Preset {
 
     /// Effects settings
     pub fx: v1::FxSettings,
+
+    /// Mapping of the hand movements to the sound controls
+    pub modulations: Vec<Modulation>,
+
+    /// Response curves of the playing mappings
+    pub curves: MappingCurves,
+
+    /// Tuning of the notes, the scale is only used in twelve-tone equal
+    /// temperament
+    pub tuning: Tuning,
+
+    /// Concert pitch of this preset, overriding the system one
+    pub reference_pitch: Option<v1::ReferencePitch>,
+
+    /// Pure ratios of the chord and drone voices, only in twelve-tone equal
+    /// temperament
+    pub just_intonation: Option<JustIntonation>,
+
+    /// Chords played by the pitch hand
+    pub voicings: Vec<Voicing>,
+
+    /// Gesture switching between the voicings, when there are several
+    pub voicing_selector: VoicingSelector,
+
+    /// Chord voices played one after the other, together when absent
+    pub arpeggiator: Option<Arpeggiator>,
+
+    /// Tempo of the arpeggiator and the synced echo
+    pub tempo: Tempo,
+
+    /// Chord chart switching the scale and the drone along the tempo
+    pub lead_sheet: Option<LeadSheet>,
+
+    /// Rhai script run on each hand update, after the mappings
+    pub script: Option<PathBuf>,
 }
 
 impl Default for Preset {
@@ -115,10 +680,23 @@ impl Default for Preset {
             guitar_octave: Octave::THREE,
             drone_octave: Octave::THREE,
             pitch: Pitch::C,
+            octave_range: OctaveInterval(3),
             scale: ScaleIntervals::major(),
+            descending_scale: None,
             drone: Default::default(),
             mix: Default::default(),
             fx: Default::default(),
+            modulations: default_modulations(),
+            curves: Default::default(),
+            tuning: Default::default(),
+            reference_pitch: None,
+            just_intonation: None,
+            voicings: vec![Default::default()],
+            voicing_selector: Default::default(),
+            arpeggiator: None,
+            tempo: Default::default(),
+            lead_sheet: None,
+            script: None,
         }
     }
 }
@@ -136,14 +714,28 @@ impl From<v1::Preset> for Preset {
             guitar_octave: value.guitar_octave,
             drone_octave: value.octave,
             pitch: value.pitch,
+            octave_range: value.octave_range,
             scale: value.scale,
+            descending_scale: None,
             drone: DroneSettings {
                 intervals: drone_intervals,
                 pluck_drone: false,
                 detune: value.drone.detune,
+                progression: None,
             },
             mix: value.mix,
             fx: value.fx,
+            modulations: default_modulations(),
+            curves: Default::default(),
+            tuning: Default::default(),
+            reference_pitch: None,
+            just_intonation: None,
+            voicings: vec![Default::default()],
+            voicing_selector: Default::default(),
+            arpeggiator: None,
+            tempo: Default::default(),
+            lead_sheet: None,
+            script: None,
         }
     }
 }
@@ -0,0 +1,388 @@
+use std::{collections::VecDeque, f32::consts::TAU};
+
+use nalgebra::{Quaternion, UnitQuaternion, Vector3};
+
+use crate::{
+    settings::{SmoothingFilter, SmoothingSettings},
+    HandMessage, HandType,
+};
+
+/// Filters restart from scratch after a tracking gap this long (s)
+const RESET_GAP: f64 = 0.5;
+
+/// Stateful filter of a single value
+#[derive(Debug, Clone)]
+enum Filter {
+    None,
+    OneEuro(OneEuroFilter),
+    Exponential(ExponentialFilter),
+    Median(MedianFilter),
+}
+
+impl Filter {
+    fn new(settings: &SmoothingFilter) -> Self {
+        match *settings {
+            SmoothingFilter::None => Filter::None,
+            SmoothingFilter::OneEuro {
+                min_cutoff,
+                beta,
+                derivative_cutoff,
+            } => Filter::OneEuro(OneEuroFilter::new(min_cutoff, beta, derivative_cutoff)),
+            SmoothingFilter::Exponential { alpha } => {
+                Filter::Exponential(ExponentialFilter::new(alpha))
+            }
+            SmoothingFilter::Median { window } => Filter::Median(MedianFilter::new(window)),
+        }
+    }
+
+    /// Filter a new sample taken at the given time (s)
+    fn filter(&mut self, value: f32, time: f64) -> f32 {
+        match self {
+            Filter::None => value,
+            Filter::OneEuro(f) => f.filter(value, time),
+            Filter::Exponential(f) => f.filter(value),
+            Filter::Median(f) => f.filter(value),
+        }
+    }
+}
+
+/// One Euro filter
+///
+/// Low pass filter whose cutoff frequency increases with the speed of the
+/// signal: jitter is removed when still, lag is reduced when moving.
+/// See <https://gery.casiez.net/1euro/>.
+#[derive(Debug, Clone)]
+pub struct OneEuroFilter {
+    min_cutoff: f32,
+    beta: f32,
+    derivative_cutoff: f32,
+    /// Previous time, filtered value and filtered derivative
+    previous: Option<(f64, f32, f32)>,
+}
+
+impl OneEuroFilter {
+    pub fn new(min_cutoff: f32, beta: f32, derivative_cutoff: f32) -> Self {
+        Self {
+            min_cutoff,
+            beta,
+            derivative_cutoff,
+            previous: None,
+        }
+    }
+
+    /// Smoothing factor of a low pass filter at the given cutoff
+    fn alpha(cutoff: f32, dt: f32) -> f32 {
+        let tau = 1.0 / (TAU * cutoff);
+        1.0 / (1.0 + tau / dt)
+    }
+
+    pub fn filter(&mut self, value: f32, time: f64) -> f32 {
+        let Some((previous_time, previous_value, previous_derivative)) = self.previous else {
+            self.previous = Some((time, value, 0.0));
+            return value;
+        };
+        let dt = (time - previous_time) as f32;
+        if dt <= 0.0 {
+            return previous_value;
+        }
+
+        let derivative = (value - previous_value) / dt;
+        let derivative = previous_derivative
+            + Self::alpha(self.derivative_cutoff, dt) * (derivative - previous_derivative);
+        let cutoff = self.min_cutoff + self.beta * derivative.abs();
+        let value = previous_value + Self::alpha(cutoff, dt) * (value - previous_value);
+        self.previous = Some((time, value, derivative));
+        value
+    }
+}
+
+/// Exponential moving average
+#[derive(Debug, Clone)]
+pub struct ExponentialFilter {
+    alpha: f32,
+    previous: Option<f32>,
+}
+
+impl ExponentialFilter {
+    pub fn new(alpha: f32) -> Self {
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            previous: None,
+        }
+    }
+
+    pub fn filter(&mut self, value: f32) -> f32 {
+        let value = match self.previous {
+            Some(previous) => previous + self.alpha * (value - previous),
+            None => value,
+        };
+        self.previous = Some(value);
+        value
+    }
+}
+
+/// Median of the last samples, removing isolated spikes
+#[derive(Debug, Clone)]
+pub struct MedianFilter {
+    window: usize,
+    samples: VecDeque<f32>,
+}
+
+impl MedianFilter {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn filter(&mut self, value: f32) -> f32 {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(value);
+
+        let mut sorted: Vec<f32> = self.samples.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        // Average of the two middle samples when their number is even
+        let len = sorted.len();
+        (sorted[(len - 1) / 2] + sorted[len / 2]) / 2.0
+    }
+}
+
+/// Filters of all the channels of a hand
+#[derive(Debug, Clone)]
+struct HandFilter {
+    position: [Filter; 3],
+    velocity: [Filter; 3],
+    /// Quaternion components, if the rotation is filtered
+    rotation: Option<[Filter; 4]>,
+    pinch: Filter,
+    grab: Filter,
+    /// Time of the last sample and last filtered rotation
+    previous: Option<(f64, UnitQuaternion<f32>)>,
+}
+
+impl HandFilter {
+    fn new(settings: &SmoothingSettings) -> Self {
+        Self {
+            position: [(); 3].map(|_| Filter::new(&settings.position)),
+            velocity: [(); 3].map(|_| Filter::new(&settings.velocity)),
+            rotation: (settings.rotation != SmoothingFilter::None)
+                .then(|| [(); 4].map(|_| Filter::new(&settings.rotation))),
+            pinch: Filter::new(&settings.pinch),
+            grab: Filter::new(&settings.grab),
+            previous: None,
+        }
+    }
+
+    fn filter(&mut self, hand: HandMessage, time: f64) -> HandMessage {
+        let filter_vector = |filters: &mut [Filter; 3], v: Vector3<f32>| {
+            Vector3::new(
+                filters[0].filter(v.x, time),
+                filters[1].filter(v.y, time),
+                filters[2].filter(v.z, time),
+            )
+        };
+        let position = filter_vector(&mut self.position, hand.position);
+        let velocity = filter_vector(&mut self.velocity, hand.velocity);
+
+        let rotation = match &mut self.rotation {
+            Some(filters) => {
+                // q and -q are the same rotation, stay on the side of the previous one
+                let mut rotation = *hand.rotation.quaternion();
+                if let Some((_, previous)) = self.previous {
+                    if previous.quaternion().dot(&rotation) < 0.0 {
+                        rotation = -rotation;
+                    }
+                }
+                UnitQuaternion::from_quaternion(Quaternion::new(
+                    filters[0].filter(rotation.w, time),
+                    filters[1].filter(rotation.i, time),
+                    filters[2].filter(rotation.j, time),
+                    filters[3].filter(rotation.k, time),
+                ))
+            }
+            None => hand.rotation,
+        };
+        self.previous = Some((time, rotation));
+
+        HandMessage {
+            position,
+            velocity,
+            rotation,
+            pinch: self.pinch.filter(hand.pinch, time),
+            grab: self.grab.filter(hand.grab, time),
+            ..hand
+        }
+    }
+}
+
+/// Filtering stage between the hand tracking and the conductor mappings
+#[derive(Debug, Clone)]
+pub struct Smoothing {
+    settings: SmoothingSettings,
+    left: HandFilter,
+    right: HandFilter,
+}
+
+impl Smoothing {
+    pub fn new(settings: &SmoothingSettings) -> Self {
+        Self {
+            settings: settings.clone(),
+            left: HandFilter::new(settings),
+            right: HandFilter::new(settings),
+        }
+    }
+
+    /// Current filters settings
+    pub fn settings(&self) -> &SmoothingSettings {
+        &self.settings
+    }
+
+    /// Filter a hand update received at the given time (s)
+    ///
+    /// The result only depends on the successive updates and their time, so
+    /// recorded sessions always give the same output.
+    pub fn filter(&mut self, hand: HandMessage, time: f64) -> HandMessage {
+        let filter = match hand.hand_type {
+            HandType::Left => &mut self.left,
+            HandType::Right => &mut self.right,
+        };
+        if filter
+            .previous
+            .is_some_and(|(previous, _)| time - previous > RESET_GAP)
+        {
+            // The hand was lost, do not smooth from its old position
+            *filter = HandFilter::new(&self.settings);
+        }
+        filter.filter(hand, time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::{
+        session::{Session, SessionFrame},
+        tracking::{SyntheticSource, TrackingEvent},
+    };
+
+    use super::*;
+
+    /// Session of synthetic motions with a deterministic jitter
+    fn jittery_session() -> Session {
+        let mut frames = vec![];
+        for i in 0..500 {
+            let time = i as f64 * 0.01;
+            for event in SyntheticSource::frame(time) {
+                if let TrackingEvent::Hand(mut hand) = event {
+                    let jitter = if i % 2 == 0 { 3.0 } else { -3.0 };
+                    hand.position.x += jitter;
+                    hand.pinch = if i % 7 == 0 { 1.0 } else { 0.0 };
+                    frames.push(SessionFrame {
+                        time,
+                        event: TrackingEvent::Hand(hand),
+                    });
+                }
+            }
+        }
+        Session { frames }
+    }
+
+    /// Filter the right hand of a session
+    fn smooth(session: &Session, settings: &SmoothingSettings) -> Vec<HandMessage> {
+        let mut smoothing = Smoothing::new(settings);
+        session
+            .frames
+            .iter()
+            .filter_map(|frame| match &frame.event {
+                TrackingEvent::Hand(hand) if hand.hand_type == HandType::Right => {
+                    Some(smoothing.filter(hand.clone(), frame.time))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Sum of the absolute differences between successive x positions
+    fn x_variation(hands: &[HandMessage]) -> f32 {
+        hands
+            .windows(2)
+            .map(|w| (w[1].position.x - w[0].position.x).abs())
+            .sum()
+    }
+
+    #[rstest]
+    #[case(SmoothingFilter::OneEuro { min_cutoff: 1.0, beta: 0.001, derivative_cutoff: 1.0 })]
+    #[case(SmoothingFilter::Exponential { alpha: 0.2 })]
+    fn reduces_jitter(#[case] filter: SmoothingFilter) {
+        let session = jittery_session();
+        let settings = SmoothingSettings {
+            position: filter,
+            ..Default::default()
+        };
+        let raw = smooth(&session, &SmoothingSettings::default());
+        let smoothed = smooth(&session, &settings);
+        assert!(x_variation(&smoothed) < x_variation(&raw) / 2.0);
+        // Deterministic on the same data
+        assert_eq!(smoothed, smooth(&session, &settings));
+    }
+
+    #[rstest]
+    fn none_is_transparent() {
+        let session = jittery_session();
+        let raw = smooth(&session, &SmoothingSettings::default());
+        let hands: Vec<_> = session
+            .frames
+            .iter()
+            .filter_map(|frame| match &frame.event {
+                TrackingEvent::Hand(hand) if hand.hand_type == HandType::Right => {
+                    Some(hand.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(hands, raw);
+    }
+
+    #[rstest]
+    fn settings_from_yaml() {
+        let yaml = "position: {type: OneEuro, min_cutoff: 1.0, beta: 0.01}\npinch: {type: Median, window: 3}";
+        let settings: SmoothingSettings = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            SmoothingSettings {
+                position: SmoothingFilter::OneEuro {
+                    min_cutoff: 1.0,
+                    beta: 0.01,
+                    derivative_cutoff: 1.0
+                },
+                pinch: SmoothingFilter::Median { window: 3 },
+                ..Default::default()
+            },
+            settings
+        );
+    }
+
+    #[rstest]
+    #[case(vec![1.0, 1.0, 10.0, 1.0, 1.0], vec![1.0, 1.0, 1.0, 1.0, 1.0])]
+    #[case(vec![1.0, 2.0, 3.0, 4.0], vec![1.0, 1.5, 2.0, 3.0])]
+    fn median(#[case] input: Vec<f32>, #[case] expected: Vec<f32>) {
+        let mut filter = MedianFilter::new(3);
+        let output: Vec<f32> = input.into_iter().map(|v| filter.filter(v)).collect();
+        assert_eq!(expected, output);
+    }
+
+    #[rstest]
+    fn one_euro_converges() {
+        let mut filter = OneEuroFilter::new(1.0, 0.0, 1.0);
+        assert_eq!(0.0, filter.filter(0.0, 0.0));
+        let mut value = 0.0;
+        for i in 1..=200 {
+            value = filter.filter(1.0, i as f64 * 0.01);
+            assert!((0.0..=1.0).contains(&value));
+        }
+        assert!(value > 0.99);
+    }
+}
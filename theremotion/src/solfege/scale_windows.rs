@@ -5,6 +5,34 @@ use staff::{midi::MidiNote, scale::ScaleIntervals, Pitch};
 
 use crate::MidiNoteF;
 
+/// Speed of the melody changing its direction (semitones/s)
+pub const DIRECTION_HYSTERESIS: f32 = 4.0;
+
+/// Direction of the melody, selecting the ascending or descending notes of
+/// a scale
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MelodicDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl MelodicDirection {
+    /// Direction after a pitch change (semitones/s)
+    ///
+    /// The direction is kept while the speed stays within the hysteresis, so
+    /// that the scale does not flicker when the hand hesitates.
+    pub fn follow(self, speed: f32, hysteresis: f32) -> Self {
+        if speed > hysteresis {
+            MelodicDirection::Ascending
+        } else if speed < -hysteresis {
+            MelodicDirection::Descending
+        } else {
+            self
+        }
+    }
+}
+
 /// Floating 2 by 2 window in a scale.
 /// Useful for many blurry algorithms in theremotion
 pub struct ScaleWindows {
@@ -19,13 +47,12 @@ impl ScaleWindows {
 
     /// Initialize from a list of midi notes
     pub fn from_notes(notes: Vec<MidiNote>) -> Self {
-        Self::new(
-            notes
-                .into_iter()
-                .map(MidiNoteF::from)
-                .tuple_windows()
-                .collect(),
-        )
+        Self::from_notes_f(notes.into_iter().map(MidiNoteF::from).collect())
+    }
+
+    /// Initialize from an ordered list of fractional notes, for microtonal tunings
+    pub fn from_notes_f(notes: Vec<MidiNoteF>) -> Self {
+        Self::new(notes.into_iter().tuple_windows().collect())
     }
 
     /// Find the position of the two neighbours surrounding the given note.
@@ -191,4 +218,18 @@ mod tests {
         let scale = ScaleWindows::from_notes(notes);
         assert_eq!(Some(expected), scale.autodegree(note.into(), degree));
     }
+    #[rstest]
+    #[case(MelodicDirection::Ascending, 10.0, MelodicDirection::Ascending)]
+    #[case(MelodicDirection::Ascending, -2.0, MelodicDirection::Ascending)]
+    #[case(MelodicDirection::Ascending, -10.0, MelodicDirection::Descending)]
+    #[case(MelodicDirection::Descending, 2.0, MelodicDirection::Descending)]
+    #[case(MelodicDirection::Descending, 0.0, MelodicDirection::Descending)]
+    #[case(MelodicDirection::Descending, 10.0, MelodicDirection::Ascending)]
+    fn direction_hysteresis(
+        #[case] direction: MelodicDirection,
+        #[case] speed: f32,
+        #[case] expected: MelodicDirection,
+    ) {
+        assert_eq!(expected, direction.follow(speed, DIRECTION_HYSTERESIS));
+    }
 }
@@ -7,6 +7,14 @@ pub trait MoreScales {
 
     /// <https://en.wikipedia.org/wiki/Ukrainian_Dorian_scale>
     fn altered_dorian() -> Self;
+
+    /// Ascending notes of the raga, without the second
+    /// <https://en.wikipedia.org/wiki/Khamaj>
+    fn khamaj_ascending() -> Self;
+
+    /// Descending notes of the raga, with the minor seventh
+    /// <https://en.wikipedia.org/wiki/Khamaj>
+    fn khamaj_descending() -> Self;
 }
 
 impl MoreScales for ScaleIntervals {
@@ -33,4 +41,27 @@ impl MoreScales for ScaleIntervals {
             Interval::MINOR_SEVENTH,
         ])
     }
+
+    fn khamaj_ascending() -> Self {
+        Self::from_iter([
+            Interval::UNISON,
+            Interval::MAJOR_THIRD,
+            Interval::PERFECT_FOURTH,
+            Interval::PERFECT_FIFTH,
+            Interval::MAJOR_SIXTH,
+            Interval::MAJOR_SEVENTH,
+        ])
+    }
+
+    fn khamaj_descending() -> Self {
+        Self::from_iter([
+            Interval::UNISON,
+            Interval::MAJOR_SECOND,
+            Interval::MAJOR_THIRD,
+            Interval::PERFECT_FOURTH,
+            Interval::PERFECT_FIFTH,
+            Interval::MAJOR_SIXTH,
+            Interval::MINOR_SEVENTH,
+        ])
+    }
 }
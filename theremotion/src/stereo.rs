@@ -0,0 +1,105 @@
+/// Widest stereo image, doubling the difference between the sides
+pub const MAX_WIDTH: f32 = 2.0;
+
+/// Stereo image of the output of the DSP
+#[derive(Debug)]
+pub struct Stereo {
+    /// Balance from the left (-1) to the right (1), attenuating the other side
+    balance: f32,
+    /// Spread from mono (0) to as played (1) and wider
+    width: f32,
+}
+
+impl Default for Stereo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stereo {
+    pub fn new() -> Self {
+        Self {
+            balance: 0.0,
+            width: 1.0,
+        }
+    }
+
+    pub fn set(&mut self, balance: f32, width: f32) {
+        self.balance = balance.clamp(-1.0, 1.0);
+        self.width = width.clamp(0.0, MAX_WIDTH);
+    }
+
+    /// Place the left and right samples in the image
+    pub fn process(&self, [left, right]: [f32; 2]) -> [f32; 2] {
+        // The width scales the difference between the sides
+        let mid = (left + right) / 2.0;
+        let side = (left - right) / 2.0 * self.width;
+        // The layers are panned in the DSP, the balance never boosts a side
+        [
+            (mid + side) * (1.0 - self.balance).min(1.0),
+            (mid - side) * (1.0 + self.balance).min(1.0),
+        ]
+    }
+
+    /// Write interleaved left and right samples to interleaved frames of any
+    /// number of channels
+    ///
+    /// A single channel gets the mix of both sides, the channels after the
+    /// first two stay silent.
+    pub fn write_frames(&self, sides: &[f32], frames: &mut [f32], channels: usize) {
+        for (frame, side) in frames
+            .chunks_exact_mut(channels.max(1))
+            .zip(sides.chunks_exact(2))
+        {
+            let [left, right] = self.process([side[0], side[1]]);
+            match frame {
+                [mono] => *mono = (left + right) / 2.0,
+                [first, second, rest @ ..] => {
+                    *first = left;
+                    *second = right;
+                    rest.fill(0.0);
+                }
+                [] => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0.0, [1.0, 1.0])]
+    #[case(-0.5, [1.0, 0.5])]
+    #[case(-1.0, [1.0, 0.0])]
+    #[case(1.0, [0.0, 1.0])]
+    fn balancing(#[case] balance: f32, #[case] expected: [f32; 2]) {
+        let mut stereo = Stereo::new();
+        stereo.set(balance, 1.0);
+        assert_eq!(expected, stereo.process([1.0, 1.0]));
+    }
+
+    #[rstest]
+    #[case(0.0, [0.5, 0.5])]
+    #[case(1.0, [1.0, 0.0])]
+    #[case(2.0, [1.5, -0.5])]
+    fn width_scales_the_side(#[case] width: f32, #[case] expected: [f32; 2]) {
+        let mut stereo = Stereo::new();
+        stereo.set(0.0, width);
+        assert_eq!(expected, stereo.process([1.0, 0.0]));
+    }
+
+    #[rstest]
+    #[case(1, vec![0.5, 0.25])]
+    #[case(2, vec![0.5, 0.5, 0.5, 0.0])]
+    #[case(4, vec![0.5, 0.5, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0])]
+    fn output_channels(#[case] channels: usize, #[case] expected: Vec<f32>) {
+        let stereo = Stereo::new();
+        let mut frames = vec![1.0; 2 * channels];
+        stereo.write_frames(&[0.5, 0.5, 0.5, 0.0], &mut frames, channels);
+        assert_eq!(expected, frames);
+    }
+}
@@ -0,0 +1,171 @@
+use std::ops::RangeInclusive;
+
+use crate::settings::{DivisionFeel, NoteDivision, Tempo};
+
+/// Default tempo (beats per minute)
+pub const DEFAULT_BPM: f32 = 120.0;
+
+/// Slowest and fastest tempos (beats per minute)
+pub const BPM_RANGE: RangeInclusive<f32> = 30.0..=300.0;
+
+/// Longest time between two taps of the same tempo (s)
+const TAP_TIMEOUT: f64 = 2.0;
+
+/// Number of taps averaged
+const TAP_COUNT: usize = 5;
+
+/// Note values offered for the echo sync
+pub const NOTE_DIVISIONS: [(&str, NoteDivision); 6] = [
+    ("1/4", NoteDivision::new(4, DivisionFeel::Straight)),
+    ("1/8.", NoteDivision::new(8, DivisionFeel::Dotted)),
+    ("1/4T", NoteDivision::new(4, DivisionFeel::Triplet)),
+    ("1/8", NoteDivision::new(8, DivisionFeel::Straight)),
+    ("1/8T", NoteDivision::new(8, DivisionFeel::Triplet)),
+    ("1/16", NoteDivision::new(16, DivisionFeel::Straight)),
+];
+
+/// Time signatures offered in the UI
+pub const TIME_SIGNATURES: [(u32, u32); 4] = [(2, 4), (3, 4), (4, 4), (6, 8)];
+
+impl NoteDivision {
+    pub const fn new(fraction: u32, feel: DivisionFeel) -> Self {
+        Self { fraction, feel }
+    }
+
+    /// Length in whole notes
+    pub fn whole_notes(&self) -> f32 {
+        let straight = 1.0 / self.fraction.max(1) as f32;
+        match self.feel {
+            DivisionFeel::Straight => straight,
+            DivisionFeel::Dotted => straight * 1.5,
+            DivisionFeel::Triplet => straight * 2.0 / 3.0,
+        }
+    }
+}
+
+impl Tempo {
+    /// Duration of a beat (s)
+    pub fn beat_duration(&self) -> f32 {
+        60.0 / self.bpm.clamp(*BPM_RANGE.start(), *BPM_RANGE.end())
+    }
+
+    /// Number of beats of a note value
+    pub fn beats(&self, division: &NoteDivision) -> f32 {
+        division.whole_notes() * self.beat_unit.max(1) as f32
+    }
+
+    /// Duration of a note value (s)
+    pub fn duration(&self, division: &NoteDivision) -> f32 {
+        self.beats(division) * self.beat_duration()
+    }
+}
+
+/// Position in the beats of the tempo
+#[derive(Debug, Clone)]
+pub struct Clock {
+    /// Beats per minute
+    bpm: f32,
+    /// Time of the first beat (s)
+    origin: f64,
+}
+
+impl Clock {
+    pub fn new(bpm: f32) -> Self {
+        Self { bpm, origin: 0.0 }
+    }
+
+    /// Beats since the first one at a time (s)
+    pub fn beats(&self, time: f64) -> f64 {
+        (time - self.origin) * self.bpm as f64 / 60.0
+    }
+
+    /// Change the tempo, keeping the current beat position
+    pub fn set_bpm(&mut self, bpm: f32, time: f64) {
+        if bpm == self.bpm || bpm <= 0.0 {
+            return;
+        }
+        let beats = self.beats(time);
+        self.bpm = bpm;
+        self.origin = time - beats * 60.0 / bpm as f64;
+    }
+
+    /// Start a beat at a time (s)
+    pub fn restart(&mut self, time: f64) {
+        self.origin = time;
+    }
+}
+
+/// Tempo measured from the time between taps
+#[derive(Debug, Default)]
+pub struct TapTempo {
+    /// Time of the last taps (s)
+    taps: Vec<f64>,
+}
+
+impl TapTempo {
+    /// Add a tap at a time (s), returns the tempo from the second tap
+    pub fn tap(&mut self, time: f64) -> Option<f32> {
+        if let Some(last) = self.taps.last() {
+            if time - last > TAP_TIMEOUT || time < *last {
+                self.taps.clear();
+            }
+        }
+        self.taps.push(time);
+        if self.taps.len() > TAP_COUNT {
+            self.taps.remove(0);
+        }
+        let first = self.taps.first()?;
+        let last = self.taps.last()?;
+        if self.taps.len() < 2 {
+            return None;
+        }
+        let interval = (last - first) / (self.taps.len() - 1) as f64;
+        let bpm = (60.0 / interval) as f32;
+        Some(bpm.clamp(*BPM_RANGE.start(), *BPM_RANGE.end()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("1/4", 4, 0.5)]
+    #[case("1/8.", 4, 0.375)]
+    #[case("1/8", 4, 0.25)]
+    #[case("1/4T", 4, 1.0 / 3.0)]
+    #[case("1/8", 8, 0.5)]
+    fn durations(#[case] name: &str, #[case] beat_unit: u32, #[case] expected: f32) {
+        let (_, division) = NOTE_DIVISIONS.iter().find(|(n, _)| *n == name).unwrap();
+        let tempo = Tempo {
+            bpm: 120.0,
+            beat_unit,
+            ..Default::default()
+        };
+        assert!((expected - tempo.duration(division)).abs() < 1e-5);
+    }
+
+    #[rstest]
+    fn tap_tempo() {
+        let mut tap_tempo = TapTempo::default();
+        assert_eq!(None, tap_tempo.tap(10.0));
+        assert_eq!(Some(120.0), tap_tempo.tap(10.5));
+        assert_eq!(Some(96.0), tap_tempo.tap(11.25));
+        // Too long after the last tap, a new tempo starts
+        assert_eq!(None, tap_tempo.tap(20.0));
+        assert_eq!(Some(60.0), tap_tempo.tap(21.0));
+    }
+
+    #[rstest]
+    fn clock_keeps_beat() {
+        let mut clock = Clock::new(60.0);
+        assert_eq!(2.0, clock.beats(2.0));
+        clock.set_bpm(120.0, 2.0);
+        assert_eq!(2.0, clock.beats(2.0));
+        assert_eq!(3.0, clock.beats(2.5));
+        clock.restart(5.0);
+        assert_eq!(0.0, clock.beats(5.0));
+    }
+}
@@ -1,19 +1,51 @@
-use std::{cmp::Ordering, f32::consts::PI, thread};
+use std::{
+    cmp::Ordering,
+    f32::consts::PI,
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
 
 use itertools::Itertools;
 use nalgebra::Vector2;
-use staff::{midi::Octave, Interval, Pitch};
-use std::sync::mpsc::{Receiver, Sender};
+use serde::{Deserialize, Serialize};
+use staff::{
+    midi::{MidiNote, Octave},
+    scale::ScaleIntervals,
+    Interval, Pitch,
+};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
 
 use crate::{
-    controls,
-    settings::{Handedness, NamedScale, Preset, Settings},
-    thread_dsp, thread_leap, thread_ui, HandMessage, {IntervalF, Volume},
+    arpeggiator::Arpeggio,
+    audio::{self, AudioDevice},
+    calibration::Calibration,
+    capture, controls,
+    lead_sheet::{self, ChartChord},
+    looper::{LooperCommand, LooperStatus},
+    progression::ProgressionPosition,
+    script::Script,
+    session::Recorder,
+    settings::{
+        Arpeggiator, ArpeggioPattern, ChordDuration, ChordTarget, Curve, Geometry, Handedness,
+        InputSource, JustIntonation, JustLimit, JustReference, LeadSheet, ModulationDestination,
+        ModulationHand, NamedScale, PitchLayout, Preset, ProgressionAdvance, Settings,
+        SettingsSaver, TrackingSourceKind, Voicing,
+    },
+    smoothing::Smoothing,
+    solfege::{MelodicDirection, DIRECTION_HYSTERESIS},
+    tempo::{self, Clock, TapTempo},
+    thread_dsp, thread_leap, thread_ui,
+    tracking::{PointerEvent, PointerHand, TrackingEvent},
+    tuning, voicing, HandMessage, HandType, MidiNoteF, {IntervalF, OctaveInterval, Volume},
 };
 
 const HALF_PI: f32 = PI / 2.0;
 
-#[derive(Debug)]
+/// Longest wait for a message before following the tempo
+const TICK_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TrackingStatus {
     Error(String),
     Warning(String),
@@ -33,14 +65,86 @@ pub enum Msg {
     DroneClicked(i32),
     RootClicked(i32),
     ScaleClicked(i32),
+    /// Note of the descending scale toggled in the scale editor
+    DescendingScaleClicked(i32),
+    /// Play the same scale when the melody goes up and down
+    SameScaleBothWays,
     LeadOctave(i32),
     GuitarOctave(i32),
     DroneOctave(i32),
+    /// Number of octaves played by the pitch hand
+    OctaveRange(i32),
     FullscreenClicked,
     LHClicked,
     RHClicked,
     HighPriorityClicked,
     OnScreenKeyboardClicked,
+    TrackingSource(TrackingSourceKind),
+    /// Interaction with the virtual hands
+    Pointer(PointerEvent),
+    CalibrationStart,
+    CalibrationCapture,
+    CalibrationCancel,
+    CalibrationReset,
+    /// Layout of the notes around the pitch antenna
+    PitchLayout(PitchLayout),
+    /// Response curve selected for a hand mapping
+    MappingCurve(i32, Curve),
+    /// Common voicing added to or removed from the preset
+    VoicingClicked(i32),
+    /// Tuning selected among the UI choices
+    TuningClicked(i32),
+    /// Highest prime factor of the just intonation, 0 for equal temperament
+    JustLimit(i32),
+    /// Switch the just intonation between the root and the played note
+    JustReferenceClicked,
+    /// Pattern of the arpeggiator, the chords are played together when none
+    ArpeggioPattern(Option<ArpeggioPattern>),
+    /// Frequency of the A4 (Hz)
+    ConcertPitch(f32),
+    /// Fine transposition of all the notes (cents)
+    TuningCents(f32),
+    /// Tempo (beats per minute)
+    Bpm(f32),
+    /// Tap of the tempo button
+    TapTempo,
+    /// Time signature selected among the UI choices
+    TimeSignature(i32),
+    /// Note value of the echo selected among the UI choices, free when -1
+    EchoSync(i32),
+    /// Insert a copy of the current drone chord after it
+    AddDroneChord,
+    /// Remove the current drone chord
+    RemoveDroneChord,
+    /// Move to the next drone chord
+    NextDroneChord,
+    /// Length of the current drone chord (bars)
+    DroneChordBars(i32),
+    /// Switch the drone progression between the tempo and the next chord button
+    DroneFollowTempoClicked,
+    /// Chord chart of the lead sheet, none when empty
+    LeadSheetChart(String),
+    /// Switch the lead sheet between the chord tones and the chord scale
+    LeadSheetScaleClicked,
+    /// Audio output device, the default one when none
+    AudioDevice(Option<AudioDevice>),
+    /// Sample rate of the audio output (Hz), the default one when 0
+    AudioSampleRate(i32),
+    /// Frames per buffer of the audio output, the default size when 0
+    AudioBufferSize(i32),
+    /// Sample format of the audio output among the UI choices, the default
+    /// one when -1
+    AudioSampleFormat(i32),
+    /// Audio input device, no input when absent
+    InputDevice(Option<AudioDevice>),
+    /// Action on the looper
+    Looper(LooperCommand),
+    /// Looper state update from the DSP thread
+    LooperStatus(LooperStatus),
+    /// Start or stop recording the audio output
+    RecordClicked,
+    /// File being recorded by the DSP thread, if any
+    RecordingStatus(Option<PathBuf>),
     SelectScale(i32),
     DeleteScale(i32),
     SaveScale(String),
@@ -51,6 +155,19 @@ pub enum Msg {
     GuitarVolume(Volume),
     DroneVolume(Volume),
     MasterVolume(Volume),
+    /// Position of the lead synth from the left (-1) to the right (1)
+    LeadPan(f32),
+    /// Position of the guitar from the left (-1) to the right (1)
+    GuitarPan(f32),
+    /// Position of the drone from the left (-1) to the right (1)
+    DronePan(f32),
+    /// Balance of the output from the left (-1) to the right (1)
+    MixBalance(f32),
+    /// Stereo width of the output (0-2)
+    MixWidth(f32),
+    /// Volume of the audio input heard
+    MixInput(Volume),
+    VocoderClicked,
     EchoAmount(Volume),
     EchoDuration(f32),
     EchoFeedback(f32),
@@ -69,23 +186,49 @@ pub fn run(
     dsp_tx: Sender<thread_dsp::Msg>,
     ui_tx: Sender<thread_ui::Msg>,
     leap_tx: Sender<thread_leap::Msg>,
+    recorder: Option<Recorder>,
 ) -> thread::JoinHandle<()> {
     thread::Builder::new()
         .name("conductor".to_string())
         .spawn(move || {
-            let mut conductor = Conductor {
-                settings,
-                controls,
-                dsp_tx,
-                ui_tx,
-                leap_tx,
-                play_state: PlayState::default(),
-            };
+            let mut conductor =
+                Conductor::new(settings, controls, dsp_tx, ui_tx, leap_tx, recorder).unwrap();
             conductor.run(rx).unwrap();
         })
         .expect("Failed to spawn the conductor thread")
 }
 
+/// Conductor following the time of a session rendered offline
+///
+/// The settings changed while rendering are not saved.
+pub struct OfflineConductor(Conductor);
+
+impl OfflineConductor {
+    pub fn new(
+        settings: Settings,
+        controls: controls::Controls,
+        dsp_tx: Sender<thread_dsp::Msg>,
+        ui_tx: Sender<thread_ui::Msg>,
+        leap_tx: Sender<thread_leap::Msg>,
+    ) -> anyhow::Result<Self> {
+        let mut conductor = Conductor::new(settings, controls, dsp_tx, ui_tx, leap_tx, None)?;
+        conductor.render_time = Some(0.0);
+        Ok(Self(conductor))
+    }
+
+    /// Handle the message, if any, and follow the tempo at the time of the
+    /// session (s)
+    pub fn update(&mut self, time: f64, msg: Option<Msg>) -> anyhow::Result<()> {
+        self.0.render_time = Some(time);
+        if let Some(msg) = msg {
+            self.0.on_conductor_message(msg)?;
+        }
+        self.0.follow_arpeggio()?;
+        self.0.follow_progression()?;
+        self.0.follow_lead_sheet()
+    }
+}
+
 /// The conductor interprets and transmits the messages between
 /// the threads.
 struct Conductor {
@@ -106,6 +249,36 @@ struct Conductor {
 
     /// Stateful playing state
     pub play_state: PlayState,
+
+    /// Recording of the tracking session, if requested
+    pub recorder: Option<Recorder>,
+
+    /// Filtering of the hand tracking data
+    pub smoothing: Smoothing,
+
+    /// Start of the conductor, time reference of the filters
+    pub start: Instant,
+
+    /// Time of the session rendered offline (s), replacing the elapsed time
+    pub render_time: Option<f64>,
+
+    /// Calibration in progress, if any
+    pub calibration: Option<Calibration>,
+
+    /// Script of the current preset, if any
+    pub script: Option<Script>,
+
+    /// Last status of the hand tracking
+    pub tracking_status: TrackingStatus,
+
+    /// File of the audio output being recorded, if any
+    pub recording: Option<PathBuf>,
+
+    /// Error or message of the script, shown over the tracking status
+    pub script_status: Option<TrackingStatus>,
+
+    /// Writes the changed settings, off the hand path
+    pub saver: Option<SettingsSaver>,
 }
 
 /// Stateful part of the playing interactions that are not part of the DSP
@@ -113,6 +286,33 @@ struct PlayState {
     pub guitar_gates: [bool; 4],
     pub drone_grab_state: Option<(f32, f32)>,
     pub drone_state: f32,
+    /// Index of the voicing selected by gesture
+    pub voicing: usize,
+    /// Direction of the melody, selecting the scale notes
+    pub direction: MelodicDirection,
+    /// Steps of the arpeggiator
+    pub arpeggio: Arpeggio,
+    /// Chord of the pitch hand and the volumes of its voices played together,
+    /// stepped by the arpeggiator between the hand updates
+    pub lead: Option<([Option<MidiNoteF>; 4], [f32; 4])>,
+    /// Volumes of the lead voices at the last step of the arpeggiator
+    pub arpeggio_volumes: [f32; 4],
+    /// Beats of the tempo
+    pub clock: Clock,
+    /// Tempo measured from the taps
+    pub tap_tempo: TapTempo,
+    /// Whether the tap gesture is over its threshold
+    pub tapping: bool,
+    /// Current chord of the drone progression
+    pub progression: ProgressionPosition,
+    /// Whether the next drone chord gesture is over its threshold
+    pub next_chord_gesture: bool,
+    /// Chords of the lead sheet chart
+    pub lead_sheet_chords: Vec<ChartChord>,
+    /// Current chord of the lead sheet
+    pub lead_sheet: ProgressionPosition,
+    /// Preset played over the current chord of the lead sheet, if followed
+    pub chord_preset: Option<Preset>,
 }
 
 impl Default for PlayState {
@@ -121,19 +321,105 @@ impl Default for PlayState {
             guitar_gates: [false, false, false, false],
             drone_grab_state: None,
             drone_state: 0.0,
+            voicing: 0,
+            direction: MelodicDirection::Ascending,
+            arpeggio: Default::default(),
+            lead: None,
+            arpeggio_volumes: [0.0; 4],
+            clock: Clock::new(tempo::DEFAULT_BPM),
+            tap_tempo: Default::default(),
+            tapping: false,
+            progression: Default::default(),
+            next_chord_gesture: false,
+            lead_sheet_chords: vec![],
+            lead_sheet: Default::default(),
+            chord_preset: None,
         }
     }
 }
 
 impl Conductor {
+    fn new(
+        settings: Settings,
+        controls: controls::Controls,
+        dsp_tx: Sender<thread_dsp::Msg>,
+        ui_tx: Sender<thread_ui::Msg>,
+        leap_tx: Sender<thread_leap::Msg>,
+        recorder: Option<Recorder>,
+    ) -> anyhow::Result<Self> {
+        let smoothing = Smoothing::new(&settings.system.smoothing);
+        let script = preset_script(&settings.current_preset);
+        let play_state = PlayState {
+            clock: Clock::new(settings.current_preset.tempo.bpm),
+            ..Default::default()
+        };
+        let mut conductor = Conductor {
+            settings,
+            controls,
+            dsp_tx,
+            ui_tx,
+            leap_tx,
+            smoothing,
+            start: Instant::now(),
+            render_time: None,
+            calibration: None,
+            script,
+            tracking_status: TrackingStatus::Ok,
+            recording: None,
+            script_status: None,
+            saver: None,
+            play_state,
+            recorder,
+        };
+        let preset = conductor.settings.current_preset.clone();
+        conductor.load_lead_sheet(&preset)?;
+        conductor.send_lead_sheet_chord()?;
+        Ok(conductor)
+    }
+
     pub fn run(&mut self, rx: Receiver<Msg>) -> anyhow::Result<()> {
-        for msg in rx.iter() {
-            let exit = self.on_conductor_message(msg)?;
-            if exit {
-                return Ok(());
+        loop {
+            match rx.recv_timeout(TICK_INTERVAL) {
+                Ok(msg) => {
+                    self.record(&msg);
+                    let exit = self.on_conductor_message(msg)?;
+                    if exit {
+                        return Ok(());
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
             }
+            self.follow_arpeggio()?;
+            self.follow_progression()?;
+            self.follow_lead_sheet()?;
+        }
+    }
+
+    /// Time reference of the filters and the tempo (s)
+    fn time(&self) -> f64 {
+        self.render_time
+            .unwrap_or_else(|| self.start.elapsed().as_secs_f64())
+    }
+
+    /// Store the tracking messages in the session recording, if any
+    fn record(&mut self, msg: &Msg) {
+        let Some(recorder) = &mut self.recorder else {
+            return;
+        };
+        let event = match msg {
+            Msg::HandUpdate(h) => TrackingEvent::Hand(h.clone()),
+            Msg::VisibleHands { left, right } => TrackingEvent::VisibleHands {
+                left: *left,
+                right: *right,
+            },
+            Msg::TrackingStatus(status) => TrackingEvent::Status(status.clone()),
+            _ => return,
+        };
+        if let Err(err) = recorder.record(event) {
+            log::error!("Failed to record the session, stopping: {err:#}");
+            self.recorder = None;
         }
-        Ok(())
     }
 
     fn on_conductor_message(&mut self, msg: Msg) -> anyhow::Result<bool> {
@@ -150,23 +436,239 @@ impl Conductor {
                 self.dsp_tx.send(thread_dsp::Msg::Exit)?;
                 self.ui_tx.send(thread_ui::Msg::Exit)?;
                 self.leap_tx.send(thread_leap::Msg::Exit)?;
+                if let Some(recorder) = &mut self.recorder {
+                    if let Err(err) = recorder.finish() {
+                        log::error!("Failed to save the recorded session: {err:#}");
+                    }
+                }
                 return Ok(true);
             }
             Msg::TrackingStatus(status) => {
-                self.ui_tx.send(thread_ui::Msg::Status(status))?;
+                self.tracking_status = status;
+                self.send_status()?;
             }
             Msg::HandUpdate(h) => {
+                let h = self.smoothing.filter(h, self.time());
+                if let Some(calibration) = &mut self.calibration {
+                    calibration.on_hand(h.position_from_body(), h.hand_type == pitch_hand_type);
+                }
+                // The hands play over the current chord of the lead sheet
+                let chord_preset = self.play_state.chord_preset.take();
+                let playing = chord_preset.as_ref().unwrap_or(preset);
+                if h.hand_type == pitch_hand_type {
+                    self.on_pitch_hand(h.clone(), playing)?;
+                } else if h.hand_type == volume_hand_type {
+                    self.on_volume_hand(h.clone(), playing)?;
+                }
+                self.play_state.chord_preset = chord_preset;
                 if h.hand_type == pitch_hand_type {
-                    self.on_pitch_hand(h, preset)?;
+                    self.run_script(&h, ModulationHand::Pitch)?;
+                    self.tap_gesture(&h, ModulationHand::Pitch, preset);
+                    self.next_chord_gesture(&h, ModulationHand::Pitch, preset)?;
                 } else if h.hand_type == volume_hand_type {
-                    self.on_volume_hand(h, preset)?;
+                    self.run_script(&h, ModulationHand::Volume)?;
+                    self.tap_gesture(&h, ModulationHand::Volume, preset);
+                    self.next_chord_gesture(&h, ModulationHand::Volume, preset)?;
                 }
             }
+            Msg::Looper(command) => {
+                self.dsp_tx.send(thread_dsp::Msg::Looper(command))?;
+            }
+            Msg::LooperStatus(status) => {
+                self.ui_tx.send(thread_ui::Msg::Looper(status))?;
+            }
+            Msg::RecordClicked => match &self.recording {
+                Some(_) => self.dsp_tx.send(thread_dsp::Msg::Record(None))?,
+                None => match capture::recording_path(&settings.current_preset.name) {
+                    Ok(path) => self.dsp_tx.send(thread_dsp::Msg::Record(Some(path)))?,
+                    // Playing goes on without recording
+                    Err(err) => {
+                        log::error!("Failed to start the recording: {err:#}");
+                        let message = format!("Failed to start the recording: {err}");
+                        self.ui_tx
+                            .send(thread_ui::Msg::Status(TrackingStatus::Error(message)))?;
+                    }
+                },
+            },
+            Msg::RecordingStatus(path) => {
+                self.ui_tx.send(thread_ui::Msg::Recording(path.clone()))?;
+                self.recording = path;
+            }
             Msg::VisibleHands { left, right } => {
+                if let Some(calibration) = &mut self.calibration {
+                    let visible = |hand_type| match hand_type {
+                        HandType::Left => left,
+                        HandType::Right => right,
+                    };
+                    calibration
+                        .on_visible_hands(visible(pitch_hand_type), visible(volume_hand_type));
+                }
                 self.ui_tx.send(thread_ui::Msg::HasHands(left, right))?;
             }
+            Msg::Pointer(event) => {
+                let hand_type = match event.hand() {
+                    PointerHand::Pitch => pitch_hand_type,
+                    PointerHand::Volume => volume_hand_type,
+                };
+                self.leap_tx.send(thread_leap::Msg::Pointer(
+                    hand_type,
+                    event,
+                    self.settings.system.geometry.clone(),
+                ))?;
+            }
+            Msg::CalibrationStart => {
+                self.calibration = Some(Calibration::default());
+                self.send_calibration(None)?;
+            }
+            Msg::CalibrationCapture => {
+                if let Some(calibration) = &mut self.calibration {
+                    let note_range = preset.note_range_f();
+                    let semitones = (*note_range.end() - *note_range.start()).semitones();
+                    match calibration.capture(semitones, &settings.system.geometry.pitch_layout) {
+                        Ok(None) => self.send_calibration(None)?,
+                        Ok(Some(geometry)) => {
+                            settings.system.geometry = geometry;
+                            self.calibration = None;
+                            self.send_calibration(None)?;
+                        }
+                        Err(err) => self.send_calibration(Some(err.to_string()))?,
+                    }
+                }
+            }
+            Msg::CalibrationCancel => {
+                self.calibration = None;
+                self.send_calibration(None)?;
+            }
+            Msg::CalibrationReset => {
+                settings.system.geometry = Geometry {
+                    pitch_layout: settings.system.geometry.pitch_layout.clone(),
+                    ..Default::default()
+                };
+            }
+            Msg::PitchLayout(layout) => {
+                let current = &mut settings.system.geometry.pitch_layout;
+                // Keep the parameters when the same layout is selected
+                if std::mem::discriminant(current) != std::mem::discriminant(&layout) {
+                    *current = layout;
+                }
+            }
+            Msg::MappingCurve(index, curve) => {
+                if let Some(current) = preset.mapping_curve_mut(index as usize) {
+                    // Keep the parameters when the same type of curve is selected
+                    if std::mem::discriminant(current) != std::mem::discriminant(&curve) {
+                        *current = curve;
+                    }
+                }
+            }
+            Msg::VoicingClicked(index) => {
+                if let Some((_, voicing)) = voicing::COMMON_VOICINGS.get(index as usize) {
+                    if let Some(position) = preset.voicings.iter().position(|v| v == voicing) {
+                        // Keep at least one voicing
+                        if preset.voicings.len() > 1 {
+                            preset.voicings.remove(position);
+                        }
+                    } else {
+                        preset.voicings.push(voicing.clone());
+                    }
+                }
+            }
+            Msg::TuningClicked(index) => {
+                if let Some((_, tuning)) = tuning::tuning_choices(&preset.tuning)
+                    .into_iter()
+                    .nth(index as usize)
+                {
+                    preset.tuning = tuning;
+                }
+            }
+            Msg::JustLimit(limit) => {
+                let limit = match limit {
+                    5 => Some(JustLimit::FiveLimit),
+                    7 => Some(JustLimit::SevenLimit),
+                    _ => None,
+                };
+                preset.just_intonation = limit.map(|limit| JustIntonation {
+                    limit,
+                    ..preset.just_intonation.unwrap_or_default()
+                });
+            }
+            Msg::JustReferenceClicked => {
+                if let Some(just_intonation) = &mut preset.just_intonation {
+                    just_intonation.reference = match just_intonation.reference {
+                        JustReference::Root => JustReference::PlayedNote,
+                        JustReference::PlayedNote => JustReference::Root,
+                    };
+                }
+            }
             Msg::DroneClicked(note_index) => {
-                toggle_drone(preset, note_index);
+                toggle_drone(preset, self.play_state.progression.chord, note_index);
+            }
+            Msg::AddDroneChord => {
+                let chord = preset.add_drone_chord(self.play_state.progression.chord);
+                self.jump_to_drone_chord(preset, chord)?;
+            }
+            Msg::RemoveDroneChord => {
+                preset.remove_drone_chord(self.play_state.progression.chord);
+                self.jump_to_drone_chord(preset, self.play_state.progression.chord)?;
+            }
+            Msg::NextDroneChord => {
+                self.jump_to_drone_chord(preset, self.play_state.progression.chord + 1)?;
+            }
+            Msg::DroneChordBars(bars) => {
+                let chord = self.play_state.progression.chord;
+                if let Some(progression) = &mut preset.drone.progression {
+                    let len = progression.chords.len().max(1);
+                    if let Some(chord) = progression.chords.get_mut(chord % len) {
+                        chord.duration = ChordDuration::Bars { bars: bars as f32 };
+                    }
+                }
+            }
+            Msg::DroneFollowTempoClicked => {
+                if let Some(progression) = &mut preset.drone.progression {
+                    progression.advance = match progression.advance {
+                        ProgressionAdvance::Tempo => ProgressionAdvance::Manual { gesture: None },
+                        ProgressionAdvance::Manual { .. } => ProgressionAdvance::Tempo,
+                    };
+                }
+            }
+            Msg::AudioDevice(device) => {
+                let audio = &mut settings.system.audio;
+                audio.host = device.as_ref().map(|device| device.host.clone());
+                audio.device = device.map(|device| device.name);
+            }
+            Msg::AudioSampleRate(rate) => {
+                settings.system.audio.sample_rate = u32::try_from(rate).ok().filter(|r| *r > 0);
+            }
+            Msg::AudioBufferSize(frames) => {
+                settings.system.audio.buffer_size = u32::try_from(frames).ok().filter(|f| *f > 0);
+            }
+            Msg::AudioSampleFormat(index) => {
+                settings.system.audio.sample_format = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| audio::SAMPLE_FORMATS.get(index).copied());
+            }
+            Msg::InputDevice(device) => {
+                let input = &mut settings.system.input;
+                input.source = match device {
+                    Some(_) => InputSource::Device,
+                    None => InputSource::None,
+                };
+                input.host = device.as_ref().map(|device| device.host.clone());
+                input.device = device.map(|device| device.name);
+            }
+            Msg::LeadSheetChart(chart) => {
+                let lead_sheet = preset.lead_sheet.take().unwrap_or_default();
+                preset.lead_sheet = (!chart.trim().is_empty()).then_some(LeadSheet {
+                    chart,
+                    ..lead_sheet
+                });
+            }
+            Msg::LeadSheetScaleClicked => {
+                if let Some(lead_sheet) = &mut preset.lead_sheet {
+                    lead_sheet.target = match lead_sheet.target {
+                        ChordTarget::ChordTones => ChordTarget::ChordScale,
+                        ChordTarget::ChordScale => ChordTarget::ChordTones,
+                    };
+                }
             }
             Msg::RootClicked(p) => {
                 let pitch = Pitch::from_byte((p % 12) as u8);
@@ -174,7 +676,14 @@ impl Conductor {
                 tracing::debug!("Root note {} clicked, pitch: {}", p, preset.pitch);
             }
             Msg::ScaleClicked(note_index) => {
-                toggle_scale_note(preset, note_index);
+                toggle_scale_note(&mut preset.scale, preset.pitch, note_index);
+            }
+            Msg::DescendingScaleClicked(note_index) => {
+                let scale = preset.descending_scale.get_or_insert(preset.scale);
+                toggle_scale_note(scale, preset.pitch, note_index);
+            }
+            Msg::SameScaleBothWays => {
+                preset.descending_scale = None;
             }
             Msg::FullscreenClicked => {
                 settings.system.fullscreen = !settings.system.fullscreen;
@@ -191,6 +700,9 @@ impl Conductor {
             Msg::OnScreenKeyboardClicked => {
                 settings.system.force_touchscreen = !settings.system.force_touchscreen;
             }
+            Msg::TrackingSource(source) => {
+                settings.system.tracking.source = source;
+            }
             Msg::LeadOctave(o) => {
                 preset.lead_octave = Octave::new_unchecked(o as i8);
             }
@@ -200,21 +712,59 @@ impl Conductor {
             Msg::DroneOctave(o) => {
                 preset.drone_octave = Octave::new_unchecked(o as i8);
             }
+            Msg::OctaveRange(o) => {
+                preset.octave_range = OctaveInterval(o.clamp(1, 4) as u8);
+            }
+            Msg::ArpeggioPattern(pattern) => {
+                preset.arpeggiator = pattern.map(|pattern| Arpeggiator {
+                    pattern,
+                    ..preset.arpeggiator.clone().unwrap_or_default()
+                });
+            }
+            Msg::ConcertPitch(a4) => match &mut preset.reference_pitch {
+                Some(reference_pitch) => reference_pitch.a4 = a4,
+                None => settings.system.reference_pitch.a4 = a4,
+            },
+            Msg::TuningCents(cents) => match &mut preset.reference_pitch {
+                Some(reference_pitch) => reference_pitch.cents = cents,
+                None => settings.system.reference_pitch.cents = cents,
+            },
+            Msg::Bpm(bpm) => {
+                preset.tempo.bpm = bpm
+                    .round()
+                    .clamp(*tempo::BPM_RANGE.start(), *tempo::BPM_RANGE.end())
+            }
+            Msg::TapTempo => self.tap_tempo(preset),
+            Msg::TimeSignature(index) => {
+                if let Some((beats_per_bar, beat_unit)) = tempo::TIME_SIGNATURES.get(index as usize)
+                {
+                    preset.tempo.beats_per_bar = *beats_per_bar;
+                    preset.tempo.beat_unit = *beat_unit;
+                }
+            }
+            Msg::EchoSync(index) => {
+                preset.fx.echo.sync = tempo::NOTE_DIVISIONS
+                    .get(index as usize)
+                    .map(|(_, division)| *division);
+            }
             Msg::SelectScale(id) => {
                 if let Some((scale, _)) = settings
                     .system_and_user_scales()
                     .find(|(s, _)| s.id() == id)
                 {
                     settings.current_preset.scale = scale.scale;
+                    settings.current_preset.descending_scale = scale.descending;
                 }
             }
             Msg::DeleteScale(id) => {
                 settings.scales.retain(|s| s.id() != id);
             }
             Msg::SaveScale(name) => {
-                settings
-                    .scales
-                    .push(NamedScale::new(name, settings.current_preset.scale));
+                settings.scales.push(NamedScale::new(
+                    name,
+                    settings.current_preset.scale,
+                    settings.current_preset.descending_scale,
+                ));
             }
             Msg::SelectPreset(id) => {
                 let preset = settings
@@ -237,8 +787,18 @@ impl Conductor {
             Msg::GuitarVolume(v) => preset.mix.guitar = v,
             Msg::DroneVolume(v) => preset.mix.drone = v,
             Msg::MasterVolume(v) => preset.mix.master = v,
+            Msg::LeadPan(v) => preset.mix.lead_pan = v,
+            Msg::GuitarPan(v) => preset.mix.guitar_pan = v,
+            Msg::DronePan(v) => preset.mix.drone_pan = v,
+            Msg::MixBalance(v) => preset.mix.balance = v,
+            Msg::MixWidth(v) => preset.mix.width = v,
+            Msg::MixInput(v) => preset.mix.input = v,
+            Msg::VocoderClicked => preset.mix.vocoder = !preset.mix.vocoder,
             Msg::EchoAmount(v) => preset.fx.echo.mix = v,
-            Msg::EchoDuration(v) => preset.fx.echo.duration = v,
+            Msg::EchoDuration(v) => {
+                preset.fx.echo.duration = v;
+                preset.fx.echo.sync = None;
+            }
             Msg::EchoFeedback(v) => preset.fx.echo.feedback = v,
             Msg::ReverbAmount(v) => preset.fx.reverb.mix = v,
             Msg::ReverbTime(v) => preset.fx.reverb.time = v,
@@ -250,56 +810,416 @@ impl Conductor {
 
         if settings != self.settings {
             tracing::debug!("Settings were updated");
+            if settings.system.smoothing != *self.smoothing.settings() {
+                self.smoothing = Smoothing::new(&settings.system.smoothing);
+            }
+            if settings.current_preset != self.settings.current_preset {
+                match &mut self.script {
+                    Some(script)
+                        if settings.current_preset.script.as_deref() == Some(script.path()) =>
+                    {
+                        if let Err(err) = script.set_preset(&settings.current_preset) {
+                            log::error!("Failed to update the script preset: {err}");
+                        }
+                    }
+                    _ => {
+                        self.script = preset_script(&settings.current_preset);
+                        self.script_status = None;
+                        self.send_status()?;
+                    }
+                }
+            }
+            if settings.system.audio != self.settings.system.audio {
+                self.dsp_tx
+                    .send(thread_dsp::Msg::Audio(settings.system.audio.clone()))?;
+            }
+            if settings.system.input != self.settings.system.input {
+                self.dsp_tx
+                    .send(thread_dsp::Msg::Input(settings.system.input.clone()))?;
+            }
+            if settings.system.tracking != self.settings.system.tracking {
+                self.leap_tx
+                    .send(thread_leap::Msg::Settings(settings.system.tracking.clone()))?;
+            }
+            self.play_state
+                .clock
+                .set_bpm(settings.current_preset.tempo.bpm, self.time());
+            if settings.current_preset.lead_sheet != self.settings.current_preset.lead_sheet {
+                self.load_lead_sheet(&settings.current_preset)?;
+            }
+            self.play_state.chord_preset = self.chord_preset(&settings.current_preset);
+            let mut playing = settings.clone();
+            if let Some(chord_preset) = &self.play_state.chord_preset {
+                playing.current_preset = chord_preset.clone();
+            }
+            playing.current_preset.send_to_dsp(
+                &settings.played_reference_pitch(),
+                self.play_state.progression.chord,
+                &self.controls,
+                &self.dsp_tx,
+            )?;
             self.ui_tx
-                .send(thread_ui::Msg::Settings(settings.clone()))?;
-            settings
-                .current_preset
-                .send_to_dsp(&self.controls, &self.dsp_tx)?;
+                .send(thread_ui::Msg::Settings(Box::new(playing)))?;
             self.settings = settings;
-            self.settings.save()?;
+            if self.render_time.is_none() {
+                self.saver
+                    .get_or_insert_with(|| SettingsSaver::new(Settings::save))
+                    .save(self.settings.clone());
+            }
         }
 
         Ok(false)
     }
 
+    /// Show the script status if any, otherwise the tracking status
+    fn send_status(&self) -> anyhow::Result<()> {
+        let status = self
+            .script_status
+            .clone()
+            .unwrap_or_else(|| self.tracking_status.clone());
+        self.ui_tx.send(thread_ui::Msg::Status(status))?;
+        Ok(())
+    }
+
+    /// Run the script of the preset, if any, after the mappings of a hand
+    fn run_script(&mut self, h: &HandMessage, hand: ModulationHand) -> anyhow::Result<()> {
+        let Some(script) = &mut self.script else {
+            return Ok(());
+        };
+        let status = match script.on_hand(h, hand) {
+            Ok(output) => {
+                for (destination, value) in output.controls {
+                    let control = destination.control(&self.controls);
+                    control.send(&self.dsp_tx, control.get_scaled(value, &(0.0..=1.0)))?;
+                }
+                if let Some(strum_ready) = output.strum_ready {
+                    self.ui_tx.send(thread_ui::Msg::StrumReady(strum_ready))?;
+                }
+                if let Some(autotune) = output.autotune {
+                    self.ui_tx.send(thread_ui::Msg::AutotuneAmount(autotune))?;
+                }
+                // A message stays until the script replaces it, errors until a successful call
+                match output.message {
+                    Some(message) if message.is_empty() => None,
+                    Some(message) => Some(TrackingStatus::Warning(message)),
+                    None => self
+                        .script_status
+                        .clone()
+                        .filter(|status| !matches!(status, TrackingStatus::Error(_))),
+                }
+            }
+            Err(err) => Some(TrackingStatus::Error(err.to_string())),
+        };
+        if status != self.script_status {
+            self.script_status = status;
+            self.send_status()?;
+        }
+        Ok(())
+    }
+
+    /// Show the current calibration step
+    fn send_calibration(&self, error: Option<String>) -> anyhow::Result<()> {
+        let prompt = self.calibration.as_ref().and_then(|calibration| {
+            Some(thread_ui::CalibrationPrompt {
+                step: calibration.step_index(),
+                instruction: calibration.step()?.instruction(),
+                error,
+            })
+        });
+        self.ui_tx.send(thread_ui::Msg::Calibration(prompt))?;
+        Ok(())
+    }
+
+    /// Switch the voicing when the hand does the selection gesture
+    fn select_voicing(&mut self, h: &HandMessage, hand: ModulationHand, preset: &Preset) {
+        let count = preset.voicings.len();
+        if let Some(index) =
+            preset
+                .voicing_selector
+                .select(h, hand, &self.settings.system.geometry, count)
+        {
+            self.play_state.voicing = index;
+        }
+        if self.play_state.voicing >= count {
+            self.play_state.voicing = 0;
+        }
+    }
+
+    /// Tap the tempo, the beats of the clock start on the tap
+    fn tap_tempo(&mut self, preset: &mut Preset) {
+        let time = self.time();
+        if let Some(bpm) = self.play_state.tap_tempo.tap(time) {
+            preset.tempo.bpm = bpm.round();
+            self.play_state.clock.set_bpm(preset.tempo.bpm, time);
+        }
+        self.play_state.clock.restart(time);
+    }
+
+    /// Tap the tempo when the hand goes over the threshold of the tap gesture
+    fn tap_gesture(&mut self, h: &HandMessage, hand: ModulationHand, preset: &mut Preset) {
+        let Some(tap_gesture) = &preset.tempo.tap_gesture else {
+            return;
+        };
+        if tap_gesture.triggered(
+            h,
+            hand,
+            &self.settings.system.geometry,
+            &mut self.play_state.tapping,
+        ) {
+            self.tap_tempo(preset);
+        }
+    }
+
+    /// Move the drone progression along the tempo
+    /// Send the volumes of the lead voices at the current step of the
+    /// arpeggiator, if any
+    fn follow_arpeggio(&mut self) -> anyhow::Result<()> {
+        let time = self.time();
+        // The hands play over the current chord of the lead sheet
+        let preset = self
+            .play_state
+            .chord_preset
+            .as_ref()
+            .unwrap_or(&self.settings.current_preset);
+        let (Some(arpeggiator), Some((chord, volumes))) =
+            (&preset.arpeggiator, &self.play_state.lead)
+        else {
+            return Ok(());
+        };
+        let volumes = self.play_state.arpeggio.volumes(
+            arpeggiator,
+            &preset.tempo,
+            &self.play_state.clock,
+            time,
+            chord,
+            *volumes,
+        );
+        for (control, value) in self.controls.lead.iter().zip(volumes) {
+            control.volume.send(&self.dsp_tx, value)?;
+        }
+        self.play_state.arpeggio_volumes = volumes;
+        Ok(())
+    }
+
+    fn follow_progression(&mut self) -> anyhow::Result<()> {
+        // The chords of the lead sheet replace the progression
+        let preset = self
+            .play_state
+            .chord_preset
+            .as_ref()
+            .unwrap_or(&self.settings.current_preset);
+        let Some(progression) = &preset.drone.progression else {
+            return Ok(());
+        };
+        let beats = self.play_state.clock.beats(self.time());
+        if self
+            .play_state
+            .progression
+            .follow(&progression.durations(&preset.tempo), beats)
+        {
+            self.send_drone_chord(preset)?;
+        }
+        Ok(())
+    }
+
+    /// Read the chart of the lead sheet, if any, and start it over
+    fn load_lead_sheet(&mut self, preset: &Preset) -> anyhow::Result<()> {
+        let (chords, name) = match preset
+            .lead_sheet
+            .as_ref()
+            .map(|l| lead_sheet::parse_chart(&l.chart))
+        {
+            Some(Ok(chords)) => {
+                let name = chords[0].symbol.name.clone();
+                (chords, name)
+            }
+            Some(Err(err)) => {
+                log::error!("Failed to read the lead sheet: {err}");
+                (vec![], err.to_string())
+            }
+            None => (vec![], String::new()),
+        };
+        let beats = self.play_state.clock.beats(self.time());
+        self.play_state.lead_sheet.jump(chords.len(), 0, beats);
+        self.play_state.lead_sheet_chords = chords;
+        self.ui_tx.send(thread_ui::Msg::LeadSheetChord(name))?;
+        Ok(())
+    }
+
+    /// Preset played over the current chord of the lead sheet, if followed
+    fn chord_preset(&self, preset: &Preset) -> Option<Preset> {
+        let lead_sheet = preset.lead_sheet.as_ref()?;
+        let chord = self
+            .play_state
+            .lead_sheet_chords
+            .get(self.play_state.lead_sheet.chord)?;
+        Some(preset.for_chord(lead_sheet, &chord.symbol))
+    }
+
+    /// Move the lead sheet along the tempo
+    fn follow_lead_sheet(&mut self) -> anyhow::Result<()> {
+        let beats_per_bar = self.settings.current_preset.tempo.beats_per_bar as f64;
+        let durations = self
+            .play_state
+            .lead_sheet_chords
+            .iter()
+            .map(|chord| chord.bars as f64 * beats_per_bar)
+            .collect_vec();
+        let beats = self.play_state.clock.beats(self.time());
+        if self.play_state.lead_sheet.follow(&durations, beats) {
+            self.send_lead_sheet_chord()?;
+        }
+        Ok(())
+    }
+
+    /// Switch the scale and the drone to the current chord of the lead sheet
+    fn send_lead_sheet_chord(&mut self) -> anyhow::Result<()> {
+        self.play_state.chord_preset = self.chord_preset(&self.settings.current_preset);
+        let Some(chord_preset) = &self.play_state.chord_preset else {
+            return Ok(());
+        };
+        if let Some(chord) = self
+            .play_state
+            .lead_sheet_chords
+            .get(self.play_state.lead_sheet.chord)
+        {
+            self.ui_tx
+                .send(thread_ui::Msg::LeadSheetChord(chord.symbol.name.clone()))?;
+        }
+        chord_preset.send_drone_notes(
+            self.play_state.progression.chord,
+            &self.settings.played_reference_pitch(),
+            &self.controls,
+            &self.dsp_tx,
+        )?;
+        let mut playing = self.settings.clone();
+        playing.current_preset = chord_preset.clone();
+        self.ui_tx
+            .send(thread_ui::Msg::Settings(Box::new(playing)))?;
+        Ok(())
+    }
+
+    /// Play a chord of the drone progression now
+    fn jump_to_drone_chord(&mut self, preset: &Preset, chord: usize) -> anyhow::Result<()> {
+        let beats = self.play_state.clock.beats(self.time());
+        match &preset.drone.progression {
+            Some(progression) => {
+                self.play_state
+                    .progression
+                    .jump(progression.chords.len(), chord, beats)
+            }
+            None => self.play_state.progression = Default::default(),
+        }
+        self.send_drone_chord(preset)
+    }
+
+    /// Send the current drone chord to the DSP and the UI
+    fn send_drone_chord(&self, preset: &Preset) -> anyhow::Result<()> {
+        let chord = self.play_state.progression.chord;
+        let preset = self.play_state.chord_preset.as_ref().unwrap_or(preset);
+        preset.send_drone_notes(
+            chord,
+            &self.settings.played_reference_pitch(),
+            &self.controls,
+            &self.dsp_tx,
+        )?;
+        self.ui_tx.send(thread_ui::Msg::DroneChord(chord))?;
+        Ok(())
+    }
+
+    /// Move to the next drone chord when the hand does the gesture
+    fn next_chord_gesture(
+        &mut self,
+        h: &HandMessage,
+        hand: ModulationHand,
+        preset: &Preset,
+    ) -> anyhow::Result<()> {
+        let Some(ProgressionAdvance::Manual {
+            gesture: Some(gesture),
+        }) = preset.drone.progression.as_ref().map(|p| &p.advance)
+        else {
+            return Ok(());
+        };
+        if gesture.triggered(
+            h,
+            hand,
+            &self.settings.system.geometry,
+            &mut self.play_state.next_chord_gesture,
+        ) {
+            self.jump_to_drone_chord(preset, self.play_state.progression.chord + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Follow the arpeggiator rate when it is driven by the hand
+    fn measure_arpeggio_rate(&mut self, h: &HandMessage, hand: ModulationHand, preset: &Preset) {
+        if let Some(arpeggiator) = &preset.arpeggiator {
+            self.play_state.arpeggio.measure_rate(
+                arpeggiator,
+                h,
+                hand,
+                &self.settings.system.geometry,
+            );
+        }
+    }
+
     fn on_pitch_hand(&mut self, h: HandMessage, preset: &Preset) -> anyhow::Result<()> {
-        let dsp_tx = &mut self.dsp_tx;
-        let ui_tx = &mut self.ui_tx;
+        self.select_voicing(&h, ModulationHand::Pitch, preset);
+        self.measure_arpeggio_rate(&h, ModulationHand::Pitch, preset);
+        let dsp_tx = &self.dsp_tx;
+        let ui_tx = &self.ui_tx;
 
-        let full_scale_window = preset.full_scale_floating_window();
-        let restricted_scale_window = preset.restricted_scale_floating_window();
         let note_range = preset.note_range_f();
-        let antenna_coord = Vector2::new(400.0, -200.0);
+        let geometry = &self.settings.system.geometry;
         let position_from_body = h.position_from_body();
-        let pitch_coord_mm =
-            antenna_coord - Vector2::new(position_from_body.x, position_from_body.z);
-        let pitch_coord_semitones = pitch_coord_mm / 15.0;
-        let pitch_coord_semitones = Vector2::new(-pitch_coord_semitones.x, pitch_coord_semitones.y);
-        let pitch_distance_semitones = IntervalF(pitch_coord_semitones.norm());
+        let pitch_coord = geometry.pitch_coord(&position_from_body);
+        let span = (*note_range.end() - *note_range.start()).semitones();
+        let pitch_speed = geometry.pitch_speed(&position_from_body, &h.velocity_from_body(), span);
+        let direction = self
+            .play_state
+            .direction
+            .follow(pitch_speed, DIRECTION_HYSTERESIS);
+        self.play_state.direction = direction;
+        let full_scale_window = preset.full_scale_floating_window(direction);
+        let restricted_scale_window = preset.restricted_scale_floating_window(direction);
+        let pitch_distance_semitones = IntervalF(geometry.pitch_semitones(pitch_coord, span));
         let raw_note = (*note_range.end() - pitch_distance_semitones)
             .clamp(*note_range.start(), *note_range.end());
-        let note_number_height =
-            controls::convert_range(position_from_body.y, &(350.0..=500.0), &(1.0..=4.0));
+        let note_number_height = preset.curves.chords.convert(
+            position_from_body.y,
+            &geometry.chords_height,
+            &(1.0..=4.0),
+        );
         let lead_volumes =
             [0.0, 1.0, 2.0, 3.0].map(|v| (note_number_height.clamp(1.0, 4.0) - v).clamp(0.0, 1.0));
         self.play_state.guitar_gates = lead_volumes.map(|v| v > 0.0);
-        let autotune = controls::convert_range(h.pinch, &(0.0..=1.0), &(0.0..=5.0)) as usize;
+        let autotune = preset
+            .curves
+            .autotune
+            .convert(h.pinch, &(0.0..=1.0), &(0.0..=5.0)) as usize;
         let note = restricted_scale_window.autotune(raw_note, autotune);
-        let chord = full_scale_window.autochord(note, &[0, 2, 4, 7]);
-        let lead_offset = preset.lead_interval_f();
-        let pluck_offset = preset.pluck_interval_f();
-        let velocity_from_body = h.velocity_from_body();
-        let pitch_bend = self.controls.pitch_bend.get_scaled(
-            velocity_from_body.x + velocity_from_body.z,
-            &(-300.0..=300.0),
-        );
-        let trumpet = self
-            .controls
-            .drone_trumpet
-            .get_scaled(velocity_from_body.y.abs(), &(0.0..=250.0));
-        for (control, value) in self.controls.lead.iter().zip(lead_volumes) {
-            control.volume.send(dsp_tx, value)?;
-        }
+        let chord = match preset.voicings.get(self.play_state.voicing) {
+            Some(voicing) => voicing.chord(&full_scale_window, note),
+            None => Voicing::default().chord(&full_scale_window, note),
+        };
+        let chord = match preset.active_just_intonation() {
+            Some(just_intonation) => just_intonation.retune_chord(preset.root_note_f(), chord),
+            None => chord,
+        };
+        self.play_state.lead = Some((chord, lead_volumes));
+        let lead_volumes = match &preset.arpeggiator {
+            // Stepped on the ticks of the conductor
+            Some(_) => self.play_state.arpeggio_volumes,
+            None => {
+                for (control, value) in self.controls.lead.iter().zip(lead_volumes) {
+                    control.volume.send(dsp_tx, value)?;
+                }
+                lead_volumes
+            }
+        };
+        let tuning_offset = self.settings.played_reference_pitch().offset();
+        let lead_offset = preset.lead_interval_f() + tuning_offset;
+        let pluck_offset = preset.pluck_interval_f() + tuning_offset;
         for (i, note) in chord.iter().enumerate() {
             if let Some(note) = note {
                 self.controls.lead[i].send_note(dsp_tx, &(*note + lead_offset))?;
@@ -310,8 +1230,12 @@ impl Conductor {
             dsp_tx,
             &(preset.root_note_f() + pluck_offset + IntervalF(12.0)),
         )?;
-        self.controls.pitch_bend.send(dsp_tx, pitch_bend)?;
-        self.controls.drone_trumpet.send(dsp_tx, trumpet)?;
+        let modulated = self.modulate(&h, ModulationHand::Pitch, preset)?;
+        let trumpet = modulated
+            .iter()
+            .rev()
+            .find(|(destination, _)| *destination == ModulationDestination::DroneTrumpet)
+            .map_or(0.0, |(_, value)| *value);
         let lead_chord = chord
             .into_iter()
             .map(|c| c.unwrap_or_default())
@@ -328,18 +1252,17 @@ impl Conductor {
                 self.play_state.drone_state = (init_drone_volume + offset).clamp(0.0, 5.0);
                 let drone_volumes = [0.0, 1.0, 2.0, 3.0]
                     .map(|v| (self.play_state.drone_state.clamp(0.0, 4.0) - v).clamp(0.0, 1.0));
-                let drone_interval = preset.drone_interval();
+                let reference_pitch = self.settings.played_reference_pitch();
                 for ((control, drone), volume) in self
                     .controls
                     .drone_notes
                     .iter()
-                    .zip(preset.drone_notes())
+                    .zip(preset.drone_notes(self.play_state.progression.chord))
                     .zip(drone_volumes)
                 {
                     if let Some(drone) = drone {
-                        control
-                            .note
-                            .send(dsp_tx, ((drone + drone_interval).into_byte()) as f32)?;
+                        let note = preset.drone_note_f(drone, &reference_pitch);
+                        control.note.send(dsp_tx, note.note())?;
                         control.volume.send(dsp_tx, volume)?;
                     } else {
                         control.volume.send(dsp_tx, 0.0)?;
@@ -351,12 +1274,10 @@ impl Conductor {
         }
         ui_tx.send(thread_ui::Msg::DroneNumber(self.play_state.drone_state))?;
         ui_tx.send(thread_ui::Msg::AutotuneAmount(autotune))?;
+        ui_tx.send(thread_ui::Msg::MelodicDirection(direction))?;
         ui_tx.send(thread_ui::Msg::Lead(
             lead_chord,
-            Vector2::new(
-                pitch_coord_semitones.x * h.x_factor(),
-                pitch_coord_semitones.y,
-            ),
+            Vector2::new(pitch_coord.x * h.x_factor(), pitch_coord.y),
         ))?;
         ui_tx.send(thread_ui::Msg::RawNote(raw_note))?;
         ui_tx.send(thread_ui::Msg::ChordsNumber(note_number_height))?;
@@ -365,8 +1286,10 @@ impl Conductor {
     }
 
     fn on_volume_hand(&mut self, h: HandMessage, preset: &Preset) -> anyhow::Result<()> {
-        let dsp_tx = &mut self.dsp_tx;
-        let ui_tx = &mut self.ui_tx;
+        self.select_voicing(&h, ModulationHand::Volume, preset);
+        self.measure_arpeggio_rate(&h, ModulationHand::Volume, preset);
+        let dsp_tx = &self.dsp_tx;
+        let ui_tx = &self.ui_tx;
 
         let strum_ready = h.pinch > 0.9;
         if let Some(rotation) = h.rotation_from_body() {
@@ -382,66 +1305,95 @@ impl Conductor {
                     .pluck
                     .send(dsp_tx, preset.drone.pluck_drone && rotation > HALF_PI + 0.3);
             }
-
-            let pluck_mute = self
-                .controls
-                .pluck_mute
-                .get_scaled(rotation, &(0.0..=(HALF_PI - 0.2)));
-            self.controls.pluck_mute.send(dsp_tx, pluck_mute)?;
         }
+        let modulated = self.modulate(&h, ModulationHand::Volume, preset)?;
+        let lead_volume = modulated
+            .iter()
+            .rev()
+            .find(|(destination, _)| *destination == ModulationDestination::LeadVolume)
+            .map(|(_, value)| *value);
+
+        // The plots show the position of the hand in the playing geometry
         let position_from_body = h.position_from_body();
-        let cutoff_note_norm =
-            controls::convert_range(position_from_body.x, &(50.0..=200.0), &(-1.0..=1.0))
-                .clamp(-1.0, 1.0);
-        let cutoff_note = self
-            .controls
-            .cutoff_note
-            .get_scaled(cutoff_note_norm, &(-1.0..=1.0));
-        let resonance_norm =
-            controls::convert_range(position_from_body.z, &(100.0..=-100.0), &(0.0..=1.0))
-                .clamp(0.0, 1.0);
-        let resonance = self
-            .controls
-            .resonance
-            .get_scaled(resonance_norm, &(0.0..=1.0));
-        let lead_volume = self
-            .controls
-            .lead_volume
-            .get_scaled(position_from_body.y, &(300.0..=400.0));
-        self.controls.cutoff_note.send(dsp_tx, cutoff_note)?;
-        self.controls.lead_volume.send(dsp_tx, lead_volume)?;
-        self.controls.resonance.send(dsp_tx, resonance)?;
+        let geometry = &self.settings.system.geometry;
+        let cutoff_note_norm = controls::convert_range(
+            position_from_body.x,
+            &geometry.cutoff_distance,
+            &(-1.0..=1.0),
+        )
+        .clamp(-1.0, 1.0);
+        let resonance_norm = controls::convert_range(
+            position_from_body.z,
+            &geometry.resonance_depth,
+            &(0.0..=1.0),
+        )
+        .clamp(0.0, 1.0);
         ui_tx.send(thread_ui::Msg::Filter(
             cutoff_note_norm * h.x_factor(),
             resonance_norm,
         ))?;
-        ui_tx.send(thread_ui::Msg::LeadVolume(lead_volume))?;
+        // The volume sent to the DSP, unchanged when this hand does not map it
+        if let Some(lead_volume) = lead_volume {
+            ui_tx.send(thread_ui::Msg::LeadVolume(lead_volume))?;
+        }
         ui_tx.send(thread_ui::Msg::StrumReady(strum_ready))?;
         Ok(())
     }
+
+    /// Send the values of the modulations driven by a hand
+    ///
+    /// Returns the values sent to each destination, in the order of the
+    /// modulations: the last one of a destination is the effective one.
+    fn modulate(
+        &self,
+        h: &HandMessage,
+        hand: ModulationHand,
+        preset: &Preset,
+    ) -> anyhow::Result<Vec<(ModulationDestination, f32)>> {
+        let geometry = &self.settings.system.geometry;
+        let mut values = vec![];
+        for modulation in preset.modulations.iter().filter(|m| m.hand == hand) {
+            if let Some(value) = modulation.evaluate(h, geometry) {
+                let control = modulation.destination.control(&self.controls);
+                let value = control.get_scaled(value, &(0.0..=1.0));
+                control.send(&self.dsp_tx, value)?;
+                values.push((modulation.destination, value));
+            }
+        }
+        Ok(values)
+    }
 }
 
-fn toggle_scale_note(preset: &mut Preset, note_index: i32) {
-    let root_index = preset.root_note().into_byte() as i32;
+/// Script of a preset, if any
+fn preset_script(preset: &Preset) -> Option<Script> {
+    let mut script = Script::new(preset.script.as_ref()?);
+    if let Err(err) = script.set_preset(preset) {
+        log::error!("Failed to pass the preset to the script: {err}");
+    }
+    Some(script)
+}
+
+fn toggle_scale_note(scale: &mut ScaleIntervals, pitch: Pitch, note_index: i32) {
+    let root_index = MidiNote::new(pitch, Octave::NEGATIVE_ONE).into_byte() as i32;
     let interval = note_index - root_index;
     if interval >= 0 {
         let interval = Interval::new((interval % 12) as u8);
-        if preset.scale.contains(interval) {
-            preset.scale.remove(interval);
+        if (*scale).contains(interval) {
+            scale.remove(interval);
         } else {
-            preset.scale.push(interval);
+            scale.push(interval);
         }
 
         tracing::debug!("Scale {} clicked", note_index);
     }
 }
 
-fn toggle_drone(preset: &mut Preset, note_index: i32) {
+fn toggle_drone(preset: &mut Preset, drone_chord: usize, note_index: i32) {
     let root_index = preset.root_note().into_byte() as i32;
     let interval = note_index - root_index;
     if (0..=(12 * 3)).contains(&interval) {
         let interval = Interval::new(interval as u8);
-        let drone_intervals = &mut preset.drone.intervals;
+        let drone_intervals = preset.drone_intervals_mut(drone_chord);
         if let Some(existing_drone) = drone_intervals
             .iter_mut()
             .find(|n| n.iter().any(|n| *n == interval))
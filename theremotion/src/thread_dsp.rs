@@ -1,134 +1,580 @@
-use std::{slice, thread};
-
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use faust_state::{DspHandle, StateHandle};
-use faust_types::FaustDsp;
-use std::sync::mpsc::Receiver;
-
-pub enum Msg {
-    Exit,
-    ParameterUpdate(ParameterUpdate),
-}
-
-/// Parameter update message
-pub struct ParameterUpdate {
-    idx: i32,
-    value: f32,
-}
-
-impl ParameterUpdate {
-    /// Creates a new [`ParameterUpdate`].
-    pub fn new(idx: i32, value: f32) -> Self {
-        Self { idx, value }
-    }
-}
-
-/// Run the DSP thread
-pub fn run<T>(dsp: DspHandle<T>, state: StateHandle, rx: Receiver<Msg>) -> thread::JoinHandle<()>
-where
-    T: FaustDsp<T = f32> + 'static + Send,
-{
-    // Init cpal client
-    let host = cpal::default_host();
-    let device = host
-        .default_output_device()
-        .expect("no output device available");
-    let config = device
-        .default_output_config()
-        .expect("No default output config");
-
-    if config.sample_format() != cpal::SampleFormat::F32 {
-        // TODO config selection
-        panic!("Only F32 sample rate is supported");
-    }
-
-    run_stream(config.into(), dsp, device, rx, state)
-}
-
-fn run_stream<T>(
-    config: cpal::StreamConfig,
-    mut dsp: DspHandle<T>,
-    device: cpal::Device,
-    rx: Receiver<Msg>,
-    mut state: StateHandle,
-) -> thread::JoinHandle<()>
-where
-    T: FaustDsp<T = f32> + 'static + Send,
-{
-    // no way of knowing the buffer size in advance?
-    let mut buffer_size: usize = 0;
-    // Get number of inputs and ouputs
-    let num_inputs = dsp.num_inputs();
-    let num_outputs = dsp.num_outputs();
-    // Init DSP with a given sample rate
-    let sample_rate = config.sample_rate.0;
-    dsp.init(sample_rate as i32);
-    // Init output buffers
-    let mut inputs: Vec<Vec<f32>> = vec![vec![0_f32; buffer_size]; num_inputs];
-    let mut outputs: Vec<Vec<f32>> = vec![vec![0_f32; buffer_size]; num_outputs];
-
-    thread::Builder::new()
-        .name("dsp_controller".to_string())
-        .spawn(move || {
-            let stream = device
-                .build_output_stream(
-                    &config,
-                    move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                        // Ensure the exchange buffers are large enough
-                        let len = data.len();
-                        if len > buffer_size {
-                            for input in &mut inputs {
-                                input.resize(len, 0.0);
-                            }
-                            for output in &mut outputs {
-                                output.resize(len, 0.0);
-                            }
-                            buffer_size = len;
-                        }
-
-                        // Compute the DSP
-                        // Map our Vec<Vec<f32>> to a Vec<&f[32]> to create a buffer for the faust lib
-                        let buffer_input: Vec<&[f32]> = inputs
-                            .iter()
-                            .map(|input| unsafe {
-                                slice::from_raw_parts(input.as_ptr(), buffer_size)
-                            })
-                            .collect();
-                        // Map our Vec<Vec<f32>> to a Vec<&f[32]> to create a buffer for the faust lib
-                        let mut buffer_output: Vec<&mut [f32]> = outputs
-                            .iter_mut()
-                            .map(|output| unsafe {
-                                slice::from_raw_parts_mut(output.as_mut_ptr(), buffer_size)
-                            })
-                            .collect();
-                        dsp.update_and_compute(
-                            len as i32,
-                            &buffer_input[..],
-                            &mut buffer_output[..],
-                        );
-                        // Send to audio buffer
-                        for (out, dsp_sample) in data.iter_mut().zip(&outputs[0]) {
-                            *out = *dsp_sample;
-                        }
-                    },
-                    |err| log::error!("an error occurred on the output audio stream: {err}"),
-                    None,
-                )
-                .unwrap();
-            stream.play().expect("Failed to play stream");
-
-            loop {
-                // Retrieve the parameter updates
-                for msg in rx.try_iter() {
-                    match msg {
-                        Msg::Exit => return,
-                        Msg::ParameterUpdate(parameter) => {
-                            state.set_param(parameter.idx, parameter.value)
-                        }
-                    }
-                }
-                state.send();
-            }
-        })
-        .expect("Failed to spawn the DSP controller")
-}
+use std::{
+    path::PathBuf,
+    slice,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+use faust_state::{DspHandle, StateHandle};
+use faust_types::FaustDsp;
+use std::sync::mpsc::{Receiver, Sender};
+
+use crate::{
+    audio,
+    capture::{self, Capture},
+    input::{self, InputReader},
+    looper::{self, Looper, LooperCommand, LooperStatus},
+    settings::{AudioSettings, InputSettings},
+    stereo::Stereo,
+    thread_conductor,
+    vocoder::Vocoder,
+};
+
+pub enum Msg {
+    Exit,
+    ParameterUpdate(ParameterUpdate),
+    /// Action on the looper of the output
+    Looper(LooperCommand),
+    /// Switch the audio output
+    Audio(AudioSettings),
+    /// Stereo image of the output
+    Stereo {
+        balance: f32,
+        width: f32,
+    },
+    /// Start recording the output to a file, or stop when absent
+    Record(Option<PathBuf>),
+    /// Switch the audio input
+    Input(InputSettings),
+}
+
+/// Change of the processing done in the audio callback
+enum EngineCommand {
+    /// With the buffer of the layer it may start, allocated out of the callback
+    Looper {
+        command: LooperCommand,
+        layer: Option<Vec<f32>>,
+    },
+    Stereo {
+        balance: f32,
+        width: f32,
+    },
+    Capture(Option<Capture>),
+    Input(Option<InputReader>),
+}
+
+/// Parameter update message
+pub struct ParameterUpdate {
+    idx: i32,
+    value: f32,
+}
+
+impl ParameterUpdate {
+    /// Creates a new [`ParameterUpdate`].
+    pub fn new(idx: i32, value: f32) -> Self {
+        Self { idx, value }
+    }
+}
+
+/// Run the DSP thread
+pub fn run<T>(
+    dsp: DspHandle<T>,
+    state: StateHandle,
+    audio: AudioSettings,
+    input: InputSettings,
+    rx: Receiver<Msg>,
+    co_tx: Sender<thread_conductor::Msg>,
+) -> thread::JoinHandle<()>
+where
+    T: FaustDsp<T = f32> + 'static + Send,
+{
+    run_stream(audio, input, dsp, rx, co_tx, state)
+}
+
+/// DSP and looper computed in the audio callback
+///
+/// It outlives the audio streams, moving to the new one when the output is
+/// switched.
+struct Engine<T> {
+    dsp: DspHandle<T>,
+    sample_rate: u32,
+    /// Channels of the frames of the audio stream
+    channels: usize,
+    inputs: Vec<Vec<f32>>,
+    outputs: Vec<Vec<f32>>,
+    /// Interleaved left and right outputs of the DSP, given to the looper
+    sides: Vec<f32>,
+    /// Interleaved frames of the stream, before the sample conversion
+    frames: Vec<f32>,
+    // no way of knowing the buffer size in advance?
+    buffer_size: usize,
+    stereo: Stereo,
+    /// Recording of the output, if any
+    capture: Option<Capture>,
+    /// Audio input, if any
+    input: Option<InputReader>,
+    /// Samples of the audio input, given to the inputs of the DSP
+    input_buffer: Vec<f32>,
+    /// Vocoder of the lead, with the audio input as modulator
+    vocoder: Vocoder,
+    looper: Looper,
+    looper_status: LooperStatus,
+    commands_rx: Receiver<EngineCommand>,
+    status_tx: Sender<LooperStatus>,
+    /// Buffers of the removed layers, freed out of the callback
+    released_tx: Sender<Vec<f32>>,
+    /// Replaced audio inputs, freed out of the callback
+    released_input_tx: Sender<InputReader>,
+}
+
+impl<T> Engine<T>
+where
+    T: FaustDsp<T = f32>,
+{
+    fn new(
+        dsp: DspHandle<T>,
+        commands_rx: Receiver<EngineCommand>,
+        status_tx: Sender<LooperStatus>,
+        released_tx: Sender<Vec<f32>>,
+        released_input_tx: Sender<InputReader>,
+    ) -> Self {
+        // Get number of inputs and ouputs
+        let num_inputs = dsp.num_inputs();
+        let num_outputs = dsp.num_outputs();
+        let looper = Looper::new();
+        Self {
+            dsp,
+            sample_rate: 0,
+            channels: 1,
+            inputs: vec![vec![]; num_inputs],
+            outputs: vec![vec![]; num_outputs],
+            sides: vec![],
+            frames: vec![],
+            buffer_size: 0,
+            stereo: Stereo::new(),
+            capture: None,
+            input: None,
+            input_buffer: vec![],
+            vocoder: Vocoder::new(0),
+            looper_status: looper.status(),
+            looper,
+            commands_rx,
+            status_tx,
+            released_tx,
+            released_input_tx,
+        }
+    }
+
+    /// Init the DSP and the looper with the configuration of a new stream
+    fn set_config(&mut self, config: &cpal::StreamConfig) {
+        let sample_rate = config.sample_rate.0;
+        if sample_rate != self.sample_rate {
+            self.dsp.init(sample_rate as i32);
+            self.looper = Looper::new();
+            self.vocoder = Vocoder::new(sample_rate);
+            self.sample_rate = sample_rate;
+        }
+        self.channels = config.channels.max(1) as usize;
+    }
+
+    /// Compute the next buffer of the output
+    fn process<S>(&mut self, data: &mut [S])
+    where
+        S: SizedSample + FromSample<f32>,
+    {
+        // Ensure the exchange buffers are large enough
+        let len = data.len() / self.channels;
+        for command in self.commands_rx.try_iter() {
+            match command {
+                EngineCommand::Looper { command, layer } => {
+                    self.looper.command(command, layer, |layer| {
+                        self.released_tx.send(layer).ok();
+                    })
+                }
+                EngineCommand::Stereo { balance, width } => self.stereo.set(balance, width),
+                EngineCommand::Capture(capture) => self.capture = capture,
+                EngineCommand::Input(input) => {
+                    if let Some(replaced) = std::mem::replace(&mut self.input, input) {
+                        self.released_input_tx.send(replaced).ok();
+                    }
+                }
+            }
+        }
+        if len > self.buffer_size {
+            for input in &mut self.inputs {
+                input.resize(len, 0.0);
+            }
+            for output in &mut self.outputs {
+                output.resize(len, 0.0);
+            }
+            self.input_buffer.resize(len, 0.0);
+            self.sides.resize(2 * len, 0.0);
+            self.buffer_size = len;
+        }
+        if data.len() > self.frames.len() {
+            self.frames.resize(data.len(), 0.0);
+        }
+
+        // Read the audio input, processed by the DSP when it has inputs
+        let input = &mut self.input_buffer[..len];
+        match &mut self.input {
+            Some(reader) => reader.read(input),
+            None => input.fill(0.0),
+        }
+        for dsp_input in &mut self.inputs {
+            dsp_input[..len].copy_from_slice(input);
+        }
+
+        // Compute the DSP
+        // Map our Vec<Vec<f32>> to a Vec<&f[32]> to create a buffer for the faust lib
+        let buffer_input: Vec<&[f32]> = self
+            .inputs
+            .iter()
+            .map(|input| unsafe { slice::from_raw_parts(input.as_ptr(), self.buffer_size) })
+            .collect();
+        // Map our Vec<Vec<f32>> to a Vec<&f[32]> to create a buffer for the faust lib
+        let mut buffer_output: Vec<&mut [f32]> = self
+            .outputs
+            .iter_mut()
+            .map(|output| unsafe {
+                slice::from_raw_parts_mut(output.as_mut_ptr(), self.buffer_size)
+            })
+            .collect();
+        self.dsp
+            .update_and_compute(len as i32, &buffer_input[..], &mut buffer_output[..]);
+        // The DSP mixes the input with the instrument, the lead carrying the
+        // vocoder comes out apart. The DSP crossfades it with the lead of the
+        // mix, so it always goes through the vocoder and is silent when off.
+        let [left, right, lead_left, lead_right] = &mut self.outputs[..] else {
+            unreachable!("the DSP has 4 outputs");
+        };
+        let input = &self.input_buffer[..len];
+        self.vocoder
+            .process([&mut lead_left[..len], &mut lead_right[..len]], input);
+        // The left and right sides are looped together
+        let sides = &mut self.sides[..2 * len];
+        let mix = left.iter().zip(right.iter());
+        let lead = lead_left.iter().zip(lead_right.iter());
+        for (side, ((left, right), (lead_left, lead_right))) in
+            sides.chunks_exact_mut(2).zip(mix.zip(lead))
+        {
+            side[0] = left + lead_left;
+            side[1] = right + lead_right;
+        }
+        self.looper.process(sides, |layer| {
+            self.released_tx.send(layer).ok();
+        });
+        if self.looper.status() != self.looper_status {
+            self.looper_status = self.looper.status();
+            self.status_tx.send(self.looper_status).ok();
+        }
+        let frames = &mut self.frames[..data.len()];
+        self.stereo.write_frames(sides, frames, self.channels);
+        if let Some(capture) = &self.capture {
+            capture.push(frames);
+        }
+        // Send to audio buffer, in the sample format of the device
+        for (out, sample) in data.iter_mut().zip(frames.iter()) {
+            *out = S::from_sample(*sample);
+        }
+    }
+}
+
+/// DSP computed without audio device, as fast as possible
+pub struct OfflineEngine<T> {
+    engine: Engine<T>,
+    state: StateHandle,
+    commands_tx: Sender<EngineCommand>,
+    released_rx: Receiver<Vec<f32>>,
+}
+
+impl<T> OfflineEngine<T>
+where
+    T: FaustDsp<T = f32>,
+{
+    pub fn new(dsp: DspHandle<T>, state: StateHandle, sample_rate: u32, channels: u16) -> Self {
+        let (commands_tx, commands_rx) = std::sync::mpsc::channel();
+        // Nobody follows the looper status, nor switches the input
+        let (status_tx, _) = std::sync::mpsc::channel();
+        let (released_input_tx, _) = std::sync::mpsc::channel();
+        let (released_tx, released_rx) = std::sync::mpsc::channel();
+        let mut engine = Engine::new(dsp, commands_rx, status_tx, released_tx, released_input_tx);
+        engine.set_config(&cpal::StreamConfig {
+            channels,
+            sample_rate: cpal::SampleRate(sample_rate),
+            buffer_size: cpal::BufferSize::Default,
+        });
+        Self {
+            engine,
+            state,
+            commands_tx,
+            released_rx,
+        }
+    }
+
+    /// Apply a message sent to the DSP thread, the audio output is ignored
+    pub fn update(&mut self, msg: Msg) {
+        match msg {
+            Msg::ParameterUpdate(parameter) => self.state.set_param(parameter.idx, parameter.value),
+            Msg::Looper(command) => self.command(EngineCommand::Looper {
+                command,
+                layer: new_layer(
+                    command,
+                    &self.engine.looper.status(),
+                    Some(self.engine.sample_rate),
+                ),
+            }),
+            Msg::Stereo { balance, width } => {
+                self.command(EngineCommand::Stereo { balance, width })
+            }
+            // The input is opened by the caller, from its settings
+            Msg::Exit | Msg::Audio(_) | Msg::Record(_) | Msg::Input(_) => {}
+        }
+    }
+
+    fn command(&self, command: EngineCommand) {
+        // The engine owns the receiver
+        self.commands_tx.send(command).unwrap();
+    }
+
+    /// Compute the next interleaved frames
+    pub fn process(&mut self, data: &mut [f32]) {
+        self.state.send();
+        self.engine.process(data);
+        self.released_rx.try_iter().for_each(drop);
+    }
+}
+
+/// Buffer of the layer a looper command may start in the last status, at the
+/// sample rate of the output
+fn new_layer(
+    command: LooperCommand,
+    status: &LooperStatus,
+    sample_rate: Option<u32>,
+) -> Option<Vec<f32>> {
+    match command {
+        LooperCommand::Record => looper::new_layer(status, sample_rate?),
+        LooperCommand::Undo | LooperCommand::Clear => None,
+    }
+}
+
+/// Audio stream playing the engine
+struct Output {
+    // Plays until dropped
+    _stream: cpal::Stream,
+    config: cpal::StreamConfig,
+}
+
+/// Open the output of the settings, the default output if it fails
+fn open_output<T>(audio: &AudioSettings, engine: &Arc<Mutex<Engine<T>>>) -> Option<Output>
+where
+    T: FaustDsp<T = f32> + 'static + Send,
+{
+    match build_stream(audio, engine) {
+        Ok(output) => return Some(output),
+        Err(err) => log::error!("Failed to open the audio output: {err:#}"),
+    }
+    if *audio == AudioSettings::default() {
+        return None;
+    }
+    match build_stream(&AudioSettings::default(), engine) {
+        Ok(output) => Some(output),
+        Err(err) => {
+            log::error!("Failed to open the default audio output: {err:#}");
+            None
+        }
+    }
+}
+
+/// Start an audio stream computing the engine, in the sample format of the
+/// device
+fn build_stream<T>(audio: &AudioSettings, engine: &Arc<Mutex<Engine<T>>>) -> anyhow::Result<Output>
+where
+    T: FaustDsp<T = f32> + 'static + Send,
+{
+    let device = audio::output_device(audio)?;
+    let (config, sample_format) = audio::stream_config(&device, audio)?;
+    log::info!(
+        "Audio output: {}, {} Hz, {:?}, {sample_format}",
+        device.name().unwrap_or_default(),
+        config.sample_rate.0,
+        config.buffer_size
+    );
+    engine.lock().unwrap().set_config(&config);
+    let stream = match sample_format {
+        cpal::SampleFormat::F32 => build_typed_stream::<T, f32>(&device, &config, engine),
+        cpal::SampleFormat::I16 => build_typed_stream::<T, i16>(&device, &config, engine),
+        cpal::SampleFormat::U16 => build_typed_stream::<T, u16>(&device, &config, engine),
+        cpal::SampleFormat::I32 => build_typed_stream::<T, i32>(&device, &config, engine),
+        format => anyhow::bail!("Unsupported sample format {format}"),
+    }?;
+    stream.play()?;
+    Ok(Output {
+        _stream: stream,
+        config,
+    })
+}
+
+fn build_typed_stream<T, S>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    engine: &Arc<Mutex<Engine<T>>>,
+) -> anyhow::Result<cpal::Stream>
+where
+    T: FaustDsp<T = f32> + 'static + Send,
+    S: SizedSample + FromSample<f32>,
+{
+    let engine = engine.clone();
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [S], _: &cpal::OutputCallbackInfo| {
+            // The engine is only held elsewhere while switching the output
+            match engine.try_lock() {
+                Ok(mut engine) => engine.process(data),
+                Err(_) => data.fill(S::EQUILIBRIUM),
+            }
+        },
+        |err| log::error!("an error occurred on the output audio stream: {err}"),
+        None,
+    )?;
+    Ok(stream)
+}
+
+/// Open the input of the settings at the sample rate of the output
+fn open_input(
+    output: Option<&Output>,
+    settings: &InputSettings,
+) -> (Option<InputReader>, Option<cpal::Stream>) {
+    let Some(output) = output else {
+        return (None, None);
+    };
+    match input::open(settings, output.config.sample_rate.0) {
+        Ok(Some(input)) => (Some(input.reader), input.stream),
+        Ok(None) => (None, None),
+        Err(err) => {
+            log::error!("Failed to open the audio input: {err:#}");
+            (None, None)
+        }
+    }
+}
+
+fn run_stream<T>(
+    audio: AudioSettings,
+    mut input_settings: InputSettings,
+    dsp: DspHandle<T>,
+    rx: Receiver<Msg>,
+    co_tx: Sender<thread_conductor::Msg>,
+    mut state: StateHandle,
+) -> thread::JoinHandle<()>
+where
+    T: FaustDsp<T = f32> + 'static + Send,
+{
+    // The looper runs in the audio callback, its commands and status go
+    // through the controller
+    let (commands_tx, commands_rx) = std::sync::mpsc::channel();
+    let (status_tx, status_rx) = std::sync::mpsc::channel();
+    let (released_tx, released_rx) = std::sync::mpsc::channel();
+    let (released_input_tx, released_input_rx) = std::sync::mpsc::channel();
+    let engine = Arc::new(Mutex::new(Engine::new(
+        dsp,
+        commands_rx,
+        status_tx,
+        released_tx,
+        released_input_tx,
+    )));
+
+    thread::Builder::new()
+        .name("dsp_controller".to_string())
+        .spawn(move || {
+            let mut output = open_output(&audio, &engine);
+            // Stream of the input device, capturing until dropped
+            let (reader, mut _input_stream) = open_input(output.as_ref(), &input_settings);
+            commands_tx.send(EngineCommand::Input(reader)).unwrap();
+            // Thread writing the recording of the output, if any
+            let mut recorder: Option<thread::JoinHandle<()>> = None;
+            // Last status of the looper, sizing the next layer
+            let mut looper_status = LooperStatus::default();
+
+            loop {
+                // Retrieve the parameter updates
+                for msg in rx.try_iter() {
+                    match msg {
+                        Msg::Exit => {
+                            // Finish the file of the recording before leaving
+                            engine.lock().unwrap().capture = None;
+                            if let Some(recorder) = recorder.take() {
+                                recorder.join().ok();
+                            }
+                            return;
+                        }
+                        Msg::ParameterUpdate(parameter) => {
+                            state.set_param(parameter.idx, parameter.value)
+                        }
+                        Msg::Looper(command) => {
+                            let sample_rate =
+                                output.as_ref().map(|output| output.config.sample_rate.0);
+                            commands_tx
+                                .send(EngineCommand::Looper {
+                                    command,
+                                    layer: new_layer(command, &looper_status, sample_rate),
+                                })
+                                .unwrap()
+                        }
+                        Msg::Stereo { balance, width } => commands_tx
+                            .send(EngineCommand::Stereo { balance, width })
+                            .unwrap(),
+                        Msg::Record(path) => {
+                            let recording = path.and_then(|path| {
+                                let config = &output.as_ref()?.config;
+                                match capture::start(&path, config.channels, config.sample_rate.0) {
+                                    Ok((capture, writer)) => {
+                                        commands_tx
+                                            .send(EngineCommand::Capture(Some(capture)))
+                                            .unwrap();
+                                        recorder = Some(writer);
+                                        Some(path)
+                                    }
+                                    Err(err) => {
+                                        log::error!("Failed to start the recording: {err:#}");
+                                        None
+                                    }
+                                }
+                            });
+                            if recording.is_none() {
+                                commands_tx.send(EngineCommand::Capture(None)).unwrap();
+                            }
+                            co_tx
+                                .send(thread_conductor::Msg::RecordingStatus(recording))
+                                .ok();
+                        }
+                        Msg::Audio(audio) => {
+                            // The previous stream is closed before opening the device again
+                            drop(output.take());
+                            // The recording stops with the stream, its format changes
+                            if engine.lock().unwrap().capture.take().is_some() {
+                                co_tx
+                                    .send(thread_conductor::Msg::RecordingStatus(None))
+                                    .ok();
+                            }
+                            output = open_output(&audio, &engine);
+                            // The input follows the sample rate of the output
+                            drop(_input_stream.take());
+                            let (reader, stream) = open_input(output.as_ref(), &input_settings);
+                            _input_stream = stream;
+                            commands_tx.send(EngineCommand::Input(reader)).unwrap();
+                        }
+                        Msg::Input(settings) => {
+                            // The previous device is closed before opening it again
+                            drop(_input_stream.take());
+                            let (reader, stream) = open_input(output.as_ref(), &settings);
+                            _input_stream = stream;
+                            commands_tx.send(EngineCommand::Input(reader)).unwrap();
+                            input_settings = settings;
+                        }
+                    }
+                }
+                for status in status_rx.try_iter() {
+                    looper_status = status;
+                    co_tx.send(thread_conductor::Msg::LooperStatus(status)).ok();
+                }
+                // The removed layers and inputs are freed here rather than in
+                // the callback
+                released_rx.try_iter().for_each(drop);
+                released_input_rx.try_iter().for_each(drop);
+                state.send();
+            }
+        })
+        .expect("Failed to spawn the DSP controller")
+}
@@ -1,19 +1,43 @@
-use std::{collections::HashSet, rc::Rc};
+use std::{collections::HashSet, path::PathBuf, rc::Rc};
 
 use itertools::Itertools;
 use nalgebra::Vector2;
 use slint::*;
-use staff::midi::MidiNote;
+use staff::{midi::MidiNote, Interval};
 use std::sync::mpsc::{Receiver, Sender};
 use theremotion_ui::MainWindow;
 
 use crate::{
+    audio::{self, AudioDevice},
+    calibration::STEPS,
     controls::Controls,
-    settings::{Handedness, Settings},
+    curve,
+    looper::{LooperCommand, LooperPhase, LooperStatus},
+    pitch_layout,
+    settings::{
+        ArpeggioPattern, AudioSettings, ChordDuration, ChordTarget, Curve, DroneChord, Handedness,
+        InputSettings, InputSource, JustLimit, JustReference, PitchLayout, Preset,
+        ProgressionAdvance, Settings, TrackingSourceKind,
+    },
+    solfege::MelodicDirection,
+    tempo,
     thread_conductor::{Msg as CM, TrackingStatus},
-    {MidiNoteF, Volume},
+    tracking::{PointerEvent, PointerHand},
+    tuning, voicing, {MidiNoteF, Volume},
 };
 
+/// Number of samples of the curve previews
+const CURVE_PREVIEW_SAMPLES: usize = 64;
+
+/// Space shown around the notes on the pitch plot (semitones)
+const PITCH_PLOT_MARGIN: f32 = 2.0;
+
+/// Name of the default audio output in the device list
+const DEFAULT_AUDIO_DEVICE: &str = "Default output";
+
+/// Name of the absent audio input in the device list
+const NO_AUDIO_INPUT: &str = "No input";
+
 /// Message to update externally the UI
 #[derive(Debug)]
 pub enum Msg {
@@ -36,6 +60,8 @@ pub enum Msg {
     Filter(f32, f32),
     /// Amount of autotune
     AutotuneAmount(usize),
+    /// Direction of the melody, selecting the scale notes
+    MelodicDirection(MelodicDirection),
     /// Visible hands
     HasHands(bool, bool),
     /// Volume hand is pinching and the guitar sound can be activated
@@ -43,7 +69,28 @@ pub enum Msg {
     /// Trumpet string strength (0-1)
     TrumpetStrength(f32),
     /// Settings update from leap
-    Settings(Settings),
+    Settings(Box<Settings>),
+    /// Current calibration step, or `None` when not calibrating
+    Calibration(Option<CalibrationPrompt>),
+    /// Looper state
+    Looper(LooperStatus),
+    /// File of the audio output being recorded, if any
+    Recording(Option<PathBuf>),
+    /// Current chord of the drone progression
+    DroneChord(usize),
+    /// Current chord of the lead sheet, or the error of its chart
+    LeadSheetChord(String),
+}
+
+/// Calibration step displayed to the player
+#[derive(Debug)]
+pub struct CalibrationPrompt {
+    /// Index of the step, from 0
+    pub step: usize,
+    /// What the player should do
+    pub instruction: &'static str,
+    /// Issue with the last capture
+    pub error: Option<String>,
 }
 
 pub fn run(
@@ -61,7 +108,15 @@ pub fn run(
     // Send the min/max set in the DSP
     set_ui_controls(&window.global::<theremotion_ui::UIState<'_>>(), controls);
 
-    update_ui_from_settings(&window.global::<theremotion_ui::UIState<'_>>(), &settings);
+    // The audio devices are listed once, after the default output or the
+    // absent input
+    let audio_devices = audio::output_devices();
+    let input_devices = audio::input_devices();
+    let ui = window.global::<theremotion_ui::UIState<'_>>();
+    ui.set_audio_devices(device_model(DEFAULT_AUDIO_DEVICE, &audio_devices));
+    ui.set_input_devices(device_model(NO_AUDIO_INPUT, &input_devices));
+
+    update_ui_from_settings(&ui, &settings);
 
     /// Helper to connect slint callbacks to events sent on a channel
     struct Connector(Sender<CM>);
@@ -116,29 +171,102 @@ pub fn run(
     // Play tab
     ui.on_drone_clicked(c.send(CM::DroneClicked));
 
+    // Virtual hands
+    ui.on_pointer_moved({
+        let tx = tx.clone();
+        move |hand, x, y, pressed, pinch, grab| {
+            tx.send(CM::Pointer(PointerEvent::Move {
+                hand: hand.into(),
+                x,
+                y,
+                pressed,
+                pinch,
+                grab,
+            }))
+            .unwrap()
+        }
+    });
+    ui.on_pointer_scrolled({
+        let tx = tx.clone();
+        move |hand, delta, grab| {
+            tx.send(CM::Pointer(PointerEvent::Scroll {
+                hand: hand.into(),
+                delta,
+                grab,
+            }))
+            .unwrap()
+        }
+    });
+    ui.on_pointer_height({
+        let tx = tx.clone();
+        move |hand, value| {
+            tx.send(CM::Pointer(PointerEvent::Height {
+                hand: hand.into(),
+                value,
+            }))
+            .unwrap()
+        }
+    });
+
     // Root tab
     ui.on_root_pitch_clicked(c.send(CM::RootClicked));
     ui.on_lead_octave_clicked(c.send(CM::LeadOctave));
     ui.on_guitar_octave_clicked(c.send(CM::GuitarOctave));
     ui.on_drone_octave_clicked(c.send(CM::DroneOctave));
+    ui.on_octave_range_clicked(c.send(CM::OctaveRange));
+    ui.on_add_drone_chord_clicked(c.send2(|| CM::AddDroneChord));
+    ui.on_remove_drone_chord_clicked(c.send2(|| CM::RemoveDroneChord));
+    ui.on_next_drone_chord_clicked(c.send2(|| CM::NextDroneChord));
+    ui.on_drone_chord_bars_clicked(c.send(CM::DroneChordBars));
+    ui.on_drone_follow_tempo_clicked(c.send2(|| CM::DroneFollowTempoClicked));
+    ui.on_arpeggio_pattern_clicked({
+        let tx = tx.clone();
+        move |pattern| {
+            tx.send(CM::ArpeggioPattern(arpeggio_pattern(pattern)))
+                .unwrap()
+        }
+    });
 
     // Scale tab
     ui.on_scale_clicked(c.send(CM::ScaleClicked));
+    ui.on_descending_scale_clicked(c.send(CM::DescendingScaleClicked));
+    ui.on_same_scale_both_ways(c.send2(|| CM::SameScaleBothWays));
+    ui.on_lead_sheet_chart_changed(c.send(CM::LeadSheetChart));
+    ui.on_lead_sheet_scale_clicked(c.send2(|| CM::LeadSheetScaleClicked));
     ui.on_select_scale(c.send(CM::SelectScale));
     ui.on_delete_scale(c.send(CM::DeleteScale));
     ui.on_save_scale(c.send(CM::SaveScale));
+    ui.on_voicing_clicked(c.send(CM::VoicingClicked));
+    ui.on_tuning_clicked(c.send(CM::TuningClicked));
+    ui.on_just_limit_clicked(c.send(CM::JustLimit));
+    ui.on_just_played_note_clicked(c.send2(|| CM::JustReferenceClicked));
 
     // Mix tab
     ui.on_mix_lead_changed(c.send(CM::LeadVolume));
     ui.on_mix_pluck_changed(c.send(CM::GuitarVolume));
     ui.on_mix_drone_changed(c.send(CM::DroneVolume));
     ui.on_mix_master_changed(c.send(CM::MasterVolume));
+    ui.on_mix_lead_pan_changed(c.send(CM::LeadPan));
+    ui.on_mix_pluck_pan_changed(c.send(CM::GuitarPan));
+    ui.on_mix_drone_pan_changed(c.send(CM::DronePan));
+    ui.on_mix_balance_changed(c.send(CM::MixBalance));
+    ui.on_mix_width_changed(c.send(CM::MixWidth));
+    ui.on_mix_input_changed(c.send(CM::MixInput));
+    ui.on_vocoder_clicked(c.send2(|| CM::VocoderClicked));
     ui.on_guitar_drone_clicked(c.send2(|| CM::GuitarDroneClicked));
+    ui.on_looper_record_clicked(c.send2(|| CM::Looper(LooperCommand::Record)));
+    ui.on_looper_undo_clicked(c.send2(|| CM::Looper(LooperCommand::Undo)));
+    ui.on_looper_clear_clicked(c.send2(|| CM::Looper(LooperCommand::Clear)));
+    ui.on_record_clicked(c.send2(|| CM::RecordClicked));
 
     // Effects tab
     ui.on_echo_mix_changed(c.send(CM::EchoAmount));
     ui.on_echo_duration_changed(c.send(CM::EchoDuration));
     ui.on_echo_feedback_changed(c.send(CM::EchoFeedback));
+    ui.on_echo_sync_clicked(c.send(CM::EchoSync));
+    ui.on_bpm_changed(c.send(CM::Bpm));
+    ui.on_tap_tempo_clicked(c.send2(|| CM::TapTempo));
+    ui.on_time_signature_clicked(c.send(CM::TimeSignature));
     ui.on_reverb_mix_changed(c.send(CM::ReverbAmount));
     ui.on_reverb_time_changed(c.send(CM::ReverbTime));
     ui.on_reverb_damp_changed(c.send(CM::ReverbDamp));
@@ -150,6 +278,12 @@ pub fn run(
     ui.on_delete_preset(c.send(CM::DeletePreset));
     ui.on_save_preset(c.send(CM::SavePreset));
 
+    // Mappings tab
+    ui.on_mapping_curve_clicked({
+        let tx = tx.clone();
+        move |index, curve| tx.send(CM::MappingCurve(index, curve.into())).unwrap()
+    });
+
     // Settings tab
     ui.on_fullscreen_clicked(c.send2(|| CM::FullscreenClicked));
     ui.on_fullscreen_clicked(c.send2(|| CM::FullscreenClicked));
@@ -157,6 +291,35 @@ pub fn run(
     ui.on_lh_clicked(c.send2(|| CM::LHClicked));
     ui.on_rh_clicked(c.send2(|| CM::RHClicked));
     ui.on_high_priority_clicked(c.send2(|| CM::HighPriorityClicked));
+    ui.on_tracking_source_clicked(c.send(CM::TrackingSource));
+    ui.on_calibration_start(c.send2(|| CM::CalibrationStart));
+    ui.on_calibration_capture(c.send2(|| CM::CalibrationCapture));
+    ui.on_calibration_cancel(c.send2(|| CM::CalibrationCancel));
+    ui.on_calibration_reset(c.send2(|| CM::CalibrationReset));
+    ui.on_pitch_layout_clicked(c.send(CM::PitchLayout));
+    ui.on_concert_pitch_clicked(c.send(CM::ConcertPitch));
+    ui.on_tuning_cents_changed(c.send(CM::TuningCents));
+    ui.on_audio_device_clicked({
+        let tx = tx.clone();
+        move |index| {
+            let device = usize::try_from(index)
+                .ok()
+                .and_then(|index| audio_devices.get(index).cloned());
+            tx.send(CM::AudioDevice(device)).unwrap()
+        }
+    });
+    ui.on_input_device_clicked({
+        let tx = tx.clone();
+        move |index| {
+            let device = usize::try_from(index)
+                .ok()
+                .and_then(|index| input_devices.get(index).cloned());
+            tx.send(CM::InputDevice(device)).unwrap()
+        }
+    });
+    ui.on_audio_sample_rate_clicked(c.send(CM::AudioSampleRate));
+    ui.on_audio_buffer_size_clicked(c.send(CM::AudioBufferSize));
+    ui.on_audio_sample_format_clicked(c.send(CM::AudioSampleFormat));
 
     let window_timer = slint::Timer::default();
 
@@ -179,8 +342,14 @@ fn read_updates(
     window: &theremotion_ui::MainWindow,
 ) {
     for event in ui_rx.try_iter() {
-        let restricted_scale_window = settings.current_preset.restricted_scale_floating_window();
         let ui = window.global::<theremotion_ui::UIState<'_>>();
+        let direction = match ui.get_descending() {
+            true => MelodicDirection::Descending,
+            false => MelodicDirection::Ascending,
+        };
+        let restricted_scale_window = settings
+            .current_preset
+            .restricted_scale_floating_window(direction);
 
         match event {
             Msg::Exit => {
@@ -204,6 +373,9 @@ fn read_updates(
                 let coords_direction = coords.normalize();
                 let range_end = *settings.current_preset.note_range_f().end();
                 ui.set_tuner_note_tuned(notes[0].0 .0);
+                let lead_note = notes[0].0 + settings.current_preset.lead_interval_f();
+                let frequency = settings.played_reference_pitch().frequency(lead_note);
+                ui.set_tuner_frequency(std::format!("{frequency:.1} Hz").into());
                 // Lead for dots
                 ui.set_notes(
                     notes
@@ -245,6 +417,25 @@ fn read_updates(
                 for (index, lead) in leads.into_iter().enumerate() {
                     kb_leads.set_row_data(index, lead);
                 }
+                // Lead for the degrees of a non twelve-tone tuning
+                let preset = &settings.current_preset;
+                let pitch_classes = ui.get_pitch_classes();
+                let mut class_leads = vec![0.0; pitch_classes.row_count()];
+                for (note, volume) in notes {
+                    if let Some(lead) = preset
+                        .tuning
+                        .closest_step(preset.tuning_root(), note)
+                        .and_then(|step| class_leads.get_mut(step))
+                    {
+                        *lead += volume.0;
+                    }
+                }
+                for (index, lead) in class_leads.into_iter().enumerate() {
+                    if let Some(mut class) = pitch_classes.row_data(index) {
+                        class.lead = lead.min(1.0);
+                        pitch_classes.set_row_data(index, class);
+                    }
+                }
             }
             Msg::ChordsNumber(c) => ui.set_chords_number(c),
             Msg::DroneNumber(d) => ui.set_drone_number(d),
@@ -256,15 +447,43 @@ fn read_updates(
                 ui.set_filter_cutoff(c);
                 ui.set_filter_resonance(r);
             }
+            Msg::MelodicDirection(d) => ui.set_descending(d == MelodicDirection::Descending),
             Msg::AutotuneAmount(a) => ui.set_autotune_amount(a.try_into().unwrap_or_default()),
             Msg::HasHands(l, r) => {
                 ui.set_has_left_hand(l);
                 ui.set_has_right_hand(r);
             }
             Msg::StrumReady(s) => ui.set_strum_ready(s),
+            Msg::DroneChord(chord) => {
+                ui.set_drone_chord(chord as i32);
+                update_ui_drones(&ui, &settings.current_preset);
+            }
+            Msg::LeadSheetChord(chord) => ui.set_lead_sheet_chord(chord.into()),
+            Msg::Looper(status) => {
+                ui.set_looper_phase(status.phase.into());
+                ui.set_looper_layers(status.layers as i32);
+            }
+            Msg::Recording(path) => {
+                ui.set_recording(path.is_some());
+                let file = path
+                    .as_ref()
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                ui.set_recording_file(file.into());
+            }
             Msg::TrumpetStrength(_) => {} // todo?
+            Msg::Calibration(prompt) => {
+                ui.set_calibrating(prompt.is_some());
+                if let Some(prompt) = prompt {
+                    ui.set_calibration_step(prompt.step as i32 + 1);
+                    ui.set_calibration_steps(STEPS.len() as i32);
+                    ui.set_calibration_instruction(prompt.instruction.into());
+                    ui.set_calibration_error(prompt.error.unwrap_or_default().into());
+                }
+            }
             Msg::Settings(s) => {
-                *settings = s;
+                *settings = *s;
                 update_ui_from_settings(&ui, settings);
             }
         }
@@ -286,28 +505,158 @@ fn set_ui_controls(ui: &theremotion_ui::UIState<'_>, controls: Controls) {
     ui.set_mix_pluck_control(ui_control(&controls.mix_pluck_volume));
 }
 
+/// List the audio devices after the default choice
+fn device_model(default: &str, devices: &[AudioDevice]) -> ModelRc<theremotion_ui::Selectable> {
+    let default = theremotion_ui::Selectable {
+        id: -1,
+        name: default.into(),
+        selected: false,
+        removable: false,
+    };
+    let devices =
+        VecModel::from(
+            std::iter::once(default)
+                .chain(devices.iter().enumerate().map(|(index, device)| {
+                    theremotion_ui::Selectable {
+                        id: index as i32,
+                        name: device.to_string().into(),
+                        selected: false,
+                        removable: false,
+                    }
+                }))
+                .collect_vec(),
+        );
+    ModelRc::from(Rc::new(devices))
+}
+
+/// Select the device of the given name in a list
+fn select_device(devices: &ModelRc<theremotion_ui::Selectable>, selected: &str) {
+    for index in 0..devices.row_count() {
+        if let Some(mut device) = devices.row_data(index) {
+            device.selected = device.name == selected;
+            devices.set_row_data(index, device);
+        }
+    }
+}
+
+/// Show the selected audio input
+fn update_ui_input(ui: &theremotion_ui::UIState<'_>, input: &InputSettings) {
+    let selected = match input.source {
+        InputSource::None => NO_AUDIO_INPUT.to_string(),
+        InputSource::Device => input
+            .input_device()
+            .map(|device| device.to_string())
+            .unwrap_or_default(),
+        // Only set in the settings file
+        InputSource::File => String::new(),
+    };
+    select_device(&ui.get_input_devices(), &selected);
+}
+
+/// Show the selected audio output
+fn update_ui_audio(ui: &theremotion_ui::UIState<'_>, audio: &AudioSettings) {
+    let selected = audio
+        .output_device()
+        .map_or(DEFAULT_AUDIO_DEVICE.to_string(), |device| {
+            device.to_string()
+        });
+    select_device(&ui.get_audio_devices(), &selected);
+    ui.set_audio_sample_rate(audio.sample_rate.unwrap_or_default() as i32);
+    ui.set_audio_buffer_size(audio.buffer_size.unwrap_or_default() as i32);
+    ui.set_audio_sample_format(
+        audio
+            .sample_format
+            .and_then(|format| audio::SAMPLE_FORMATS.iter().position(|f| *f == format))
+            .map_or(-1, |index| index as i32),
+    );
+}
+
 fn update_ui_from_settings(ui: &theremotion_ui::UIState<'_>, settings: &Settings) {
     ui.set_handedness(settings.system.handedness.into());
     ui.set_fullscreen(settings.system.fullscreen);
     ui.set_high_priority(settings.system.high_priority_process);
     ui.set_use_on_screen_keyboard(settings.system.force_touchscreen);
+    ui.set_tracking_source(settings.system.tracking.source.into());
+    ui.set_pitch_layout((&settings.system.geometry.pitch_layout).into());
+    ui.set_linear_pitch(settings.system.geometry.pitch_layout == PitchLayout::Linear);
+    let reference_pitch = settings.reference_pitch();
+    ui.set_concert_pitch(reference_pitch.a4);
+    ui.set_tuning_cents(reference_pitch.cents);
+    update_ui_audio(ui, &settings.system.audio);
+    update_ui_input(ui, &settings.system.input);
 
     let preset = &settings.current_preset;
     ui.set_lead_octave(preset.lead_octave.into_i8() as i32);
     ui.set_guitar_octave(preset.guitar_octave.into_i8() as i32);
     ui.set_drone_octave(preset.drone_octave.into_i8() as i32);
-    ui.set_highest_note(preset.note_range().end().into_byte() as i32);
+    ui.set_octave_range(preset.octave_range.octaves().into());
+    ui.set_arpeggio_pattern(arpeggio_pattern_type(
+        preset.arpeggiator.as_ref().map(|a| a.pattern),
+    ));
+    ui.set_just_limit(match preset.just_intonation.map(|j| j.limit) {
+        None => 0,
+        Some(JustLimit::FiveLimit) => 5,
+        Some(JustLimit::SevenLimit) => 7,
+    });
+    ui.set_just_played_note(
+        preset.just_intonation.map(|j| j.reference) == Some(JustReference::PlayedNote),
+    );
 
     ui.set_mix_lead(preset.mix.lead.0);
     ui.set_mix_pluck(preset.mix.guitar.0);
     ui.set_mix_drone(preset.mix.drone.0);
     ui.set_mix_master(preset.mix.master.0);
+    ui.set_mix_lead_pan(preset.mix.lead_pan);
+    ui.set_mix_pluck_pan(preset.mix.guitar_pan);
+    ui.set_mix_drone_pan(preset.mix.drone_pan);
+    ui.set_mix_balance(preset.mix.balance);
+    ui.set_mix_width(preset.mix.width);
+    ui.set_mix_input(preset.mix.input.0);
+    ui.set_vocoder(preset.mix.vocoder);
 
     ui.set_enable_guitar_drone(preset.drone.pluck_drone);
 
     ui.set_echo_mix(preset.fx.echo.mix.0);
-    ui.set_echo_duration(preset.fx.echo.duration);
+    ui.set_echo_duration(preset.fx.echo.effective_duration(&preset.tempo));
     ui.set_echo_feedback(preset.fx.echo.feedback);
+    let echo_syncs = VecModel::from(
+        std::iter::once(theremotion_ui::Selectable {
+            id: -1,
+            selected: preset.fx.echo.sync.is_none(),
+            name: "Free".into(),
+            removable: false,
+        })
+        .chain(
+            tempo::NOTE_DIVISIONS
+                .iter()
+                .enumerate()
+                .map(|(id, (name, division))| theremotion_ui::Selectable {
+                    id: id as i32,
+                    selected: preset.fx.echo.sync == Some(*division),
+                    name: (*name).into(),
+                    removable: false,
+                }),
+        )
+        .collect_vec(),
+    );
+    ui.set_echo_syncs(ModelRc::from(Rc::new(echo_syncs)));
+    ui.set_bpm(preset.tempo.bpm);
+    let time_signatures = VecModel::from(
+        tempo::TIME_SIGNATURES
+            .iter()
+            .enumerate()
+            .map(
+                |(id, (beats_per_bar, beat_unit))| theremotion_ui::Selectable {
+                    id: id as i32,
+                    selected: preset.tempo.beats_per_bar == *beats_per_bar
+                        && preset.tempo.beat_unit == *beat_unit,
+                    name: std::format!("{beats_per_bar}/{beat_unit}").into(),
+                    removable: false,
+                },
+            )
+            .collect_vec(),
+    );
+    ui.set_time_signatures(ModelRc::from(Rc::new(time_signatures)));
     ui.set_reverb_mix(preset.fx.reverb.mix.0);
     ui.set_reverb_time(preset.fx.reverb.time);
     ui.set_reverb_damp(preset.fx.reverb.damp);
@@ -321,13 +670,60 @@ fn update_ui_from_settings(ui: &theremotion_ui::UIState<'_>, settings: &Settings
             .system_and_user_scales()
             .map(|(scale, user)| theremotion_ui::Selectable {
                 id: scale.id(),
-                selected: scale.scale == preset.scale,
+                selected: scale.scale == preset.scale
+                    && scale.descending == preset.descending_scale,
                 name: scale.name.into(),
                 removable: user,
             })
             .collect_vec(),
     );
     ui.set_scale_presets(ModelRc::from(Rc::new(scales)));
+    let lead_sheet = preset.lead_sheet.clone().unwrap_or_default();
+    ui.set_lead_sheet_chart(lead_sheet.chart.into());
+    ui.set_lead_sheet_scale(lead_sheet.target == ChordTarget::ChordScale);
+
+    let voicings = VecModel::from(
+        voicing::COMMON_VOICINGS
+            .iter()
+            .enumerate()
+            .map(|(id, (name, voicing))| theremotion_ui::Selectable {
+                id: id as i32,
+                selected: preset.voicings.contains(voicing),
+                name: (*name).into(),
+                removable: false,
+            })
+            .collect_vec(),
+    );
+    ui.set_voicings(ModelRc::from(Rc::new(voicings)));
+
+    let tunings = VecModel::from(
+        tuning::tuning_choices(&preset.tuning)
+            .into_iter()
+            .enumerate()
+            .map(|(id, (name, tuning))| theremotion_ui::Selectable {
+                id: id as i32,
+                selected: tuning == preset.tuning,
+                name: name.into(),
+                removable: false,
+            })
+            .collect_vec(),
+    );
+    ui.set_tunings(ModelRc::from(Rc::new(tunings)));
+
+    let pitch_classes = VecModel::from(
+        preset
+            .tuning
+            .step_names()
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| theremotion_ui::PitchClass {
+                name: name.into(),
+                lead: 0.0,
+                root: index == 0,
+            })
+            .collect_vec(),
+    );
+    ui.set_pitch_classes(ModelRc::from(Rc::new(pitch_classes)));
 
     let presets = VecModel::from(
         settings
@@ -342,18 +738,81 @@ fn update_ui_from_settings(ui: &theremotion_ui::UIState<'_>, settings: &Settings
     );
     ui.set_presets(ModelRc::from(Rc::new(presets)));
 
-    let scale: HashSet<MidiNote> = preset.restricted_scale().into_iter().collect();
-    let ui_scale = ui.get_scale_notes();
+    let mappings = VecModel::from(
+        preset
+            .mapping_curves()
+            .into_iter()
+            .map(|(name, curve)| theremotion_ui::Mapping {
+                name: name.into(),
+                curve: curve.into(),
+                preview: curve_preview(curve),
+            })
+            .collect_vec(),
+    );
+    ui.set_mappings(ModelRc::from(Rc::new(mappings)));
+
+    let descending_scale: HashSet<MidiNote> = preset
+        .restricted_scale(MelodicDirection::Descending)
+        .into_iter()
+        .collect();
+    let ui_descending_scale = ui.get_descending_scale_notes();
     for index in 0..(12 * 4) {
-        ui_scale.set_row_data(index, scale.contains(&MidiNote::from_byte(index as u8)));
+        let note = MidiNote::from_byte(index as u8);
+        ui_descending_scale.set_row_data(index, descending_scale.contains(&note));
     }
+    ui.set_has_descending_scale(preset.descending_scale.is_some());
 
-    let drone_notes: HashSet<MidiNote> = settings
-        .current_preset
-        .drone_notes()
+    let scale: HashSet<MidiNote> = preset
+        .restricted_scale(MelodicDirection::Ascending)
         .into_iter()
-        .flatten()
         .collect();
+    let ui_scale = ui.get_scale_notes();
+    for index in 0..(12 * 4) {
+        ui_scale.set_row_data(index, scale.contains(&MidiNote::from_byte(index as u8)));
+    }
+
+    // Notes of the pitch plot, from the highest one at the antenna
+    let geometry = &settings.system.geometry;
+    let highest_note = *preset.note_range().end();
+    let span = preset.octave_range.semitones();
+    let pitch_grid = (0..=span)
+        .filter_map(|semitones| {
+            let note = highest_note - Interval::new(semitones);
+            Some(theremotion_ui::PitchGridLine {
+                distance: geometry.note_distance(semitones.into(), span.into())?,
+                in_scale: scale.contains(&note),
+                root: note.pitch() == preset.pitch,
+            })
+        })
+        .collect_vec();
+    let lowest_distance = pitch_grid.last().map_or(0.0, |line| line.distance);
+    ui.set_pitch_extent(lowest_distance + PITCH_PLOT_MARGIN);
+    ui.set_pitch_grid(ModelRc::from(Rc::new(VecModel::from(pitch_grid))));
+
+    update_ui_drones(ui, preset);
+}
+
+/// Show the notes of the current drone chord and the progression
+fn update_ui_drones(ui: &theremotion_ui::UIState<'_>, preset: &Preset) {
+    let progression = preset.drone.progression.as_ref();
+    let chords = progression.map_or(0, |progression| progression.chords.len());
+    let chord = (ui.get_drone_chord().max(0) as usize) % chords.max(1);
+    ui.set_drone_chord(chord as i32);
+    ui.set_drone_chords(chords as i32);
+    ui.set_drone_follow_tempo(
+        progression.is_some_and(|progression| progression.advance == ProgressionAdvance::Tempo),
+    );
+    ui.set_drone_chord_bars(
+        match progression.and_then(|progression| progression.chords.get(chord)) {
+            Some(DroneChord {
+                duration: ChordDuration::Bars { bars },
+                ..
+            }) => *bars,
+            _ => 0.0,
+        },
+    );
+
+    let drone_notes: HashSet<MidiNote> = preset.drone_notes(chord).into_iter().flatten().collect();
 
     let ui_drones = ui.get_drones();
 
@@ -389,6 +848,150 @@ impl From<Handedness> for theremotion_ui::Handedness {
     }
 }
 
+impl From<theremotion_ui::TrackingSource> for TrackingSourceKind {
+    fn from(value: theremotion_ui::TrackingSource) -> Self {
+        match value {
+            theremotion_ui::TrackingSource::Leap => TrackingSourceKind::Leap,
+            theremotion_ui::TrackingSource::Replay => TrackingSourceKind::Replay,
+            theremotion_ui::TrackingSource::Synthetic => TrackingSourceKind::Synthetic,
+            theremotion_ui::TrackingSource::Pointer => TrackingSourceKind::Pointer,
+            theremotion_ui::TrackingSource::Osc => TrackingSourceKind::Osc,
+        }
+    }
+}
+
+impl From<TrackingSourceKind> for theremotion_ui::TrackingSource {
+    fn from(value: TrackingSourceKind) -> Self {
+        match value {
+            TrackingSourceKind::Leap => theremotion_ui::TrackingSource::Leap,
+            TrackingSourceKind::Replay => theremotion_ui::TrackingSource::Replay,
+            TrackingSourceKind::Synthetic => theremotion_ui::TrackingSource::Synthetic,
+            TrackingSourceKind::Pointer => theremotion_ui::TrackingSource::Pointer,
+            TrackingSourceKind::Osc => theremotion_ui::TrackingSource::Osc,
+        }
+    }
+}
+
+impl From<theremotion_ui::PointerHand> for PointerHand {
+    fn from(value: theremotion_ui::PointerHand) -> Self {
+        match value {
+            theremotion_ui::PointerHand::Pitch => PointerHand::Pitch,
+            theremotion_ui::PointerHand::Volume => PointerHand::Volume,
+        }
+    }
+}
+
+impl From<theremotion_ui::CurveType> for Curve {
+    fn from(value: theremotion_ui::CurveType) -> Self {
+        match value {
+            theremotion_ui::CurveType::Linear => Curve::Linear,
+            theremotion_ui::CurveType::Exponential => Curve::Exponential {
+                steepness: curve::DEFAULT_STEEPNESS,
+            },
+            theremotion_ui::CurveType::Logarithmic => Curve::Logarithmic {
+                steepness: curve::DEFAULT_STEEPNESS,
+            },
+            theremotion_ui::CurveType::Sigmoid => Curve::Sigmoid {
+                steepness: curve::DEFAULT_SIGMOID_STEEPNESS,
+            },
+            theremotion_ui::CurveType::Stepped => Curve::Stepped {
+                steps: curve::DEFAULT_STEPS,
+            },
+            theremotion_ui::CurveType::Breakpoints => Curve::Breakpoints {
+                points: curve::DEFAULT_BREAKPOINTS.to_vec(),
+            },
+        }
+    }
+}
+
+impl From<&Curve> for theremotion_ui::CurveType {
+    fn from(value: &Curve) -> Self {
+        match value {
+            Curve::Linear => theremotion_ui::CurveType::Linear,
+            Curve::Exponential { .. } => theremotion_ui::CurveType::Exponential,
+            Curve::Logarithmic { .. } => theremotion_ui::CurveType::Logarithmic,
+            Curve::Sigmoid { .. } => theremotion_ui::CurveType::Sigmoid,
+            Curve::Stepped { .. } => theremotion_ui::CurveType::Stepped,
+            Curve::Breakpoints { .. } => theremotion_ui::CurveType::Breakpoints,
+        }
+    }
+}
+
+impl From<LooperPhase> for theremotion_ui::LooperPhase {
+    fn from(value: LooperPhase) -> Self {
+        match value {
+            LooperPhase::Empty => theremotion_ui::LooperPhase::Empty,
+            LooperPhase::Recording => theremotion_ui::LooperPhase::Recording,
+            LooperPhase::Playing => theremotion_ui::LooperPhase::Playing,
+            LooperPhase::Overdubbing => theremotion_ui::LooperPhase::Overdubbing,
+        }
+    }
+}
+
+impl From<theremotion_ui::PitchLayoutType> for PitchLayout {
+    fn from(value: theremotion_ui::PitchLayoutType) -> Self {
+        match value {
+            theremotion_ui::PitchLayoutType::Circular => PitchLayout::Circular,
+            theremotion_ui::PitchLayoutType::Linear => PitchLayout::Linear,
+            theremotion_ui::PitchLayoutType::VerticalRod => PitchLayout::VerticalRod {
+                offset: pitch_layout::DEFAULT_ROD_OFFSET,
+            },
+            theremotion_ui::PitchLayoutType::Octaves => PitchLayout::Octaves {
+                widths: pitch_layout::DEFAULT_OCTAVE_WIDTHS.to_vec(),
+            },
+        }
+    }
+}
+
+impl From<&PitchLayout> for theremotion_ui::PitchLayoutType {
+    fn from(value: &PitchLayout) -> Self {
+        match value {
+            PitchLayout::Circular => theremotion_ui::PitchLayoutType::Circular,
+            PitchLayout::Linear => theremotion_ui::PitchLayoutType::Linear,
+            PitchLayout::VerticalRod { .. } => theremotion_ui::PitchLayoutType::VerticalRod,
+            PitchLayout::Octaves { .. } => theremotion_ui::PitchLayoutType::Octaves,
+        }
+    }
+}
+
+/// Pattern of the arpeggiator selected in the UI
+fn arpeggio_pattern(value: theremotion_ui::ArpeggioPatternType) -> Option<ArpeggioPattern> {
+    match value {
+        theremotion_ui::ArpeggioPatternType::Off => None,
+        theremotion_ui::ArpeggioPatternType::Up => Some(ArpeggioPattern::Up),
+        theremotion_ui::ArpeggioPatternType::Down => Some(ArpeggioPattern::Down),
+        theremotion_ui::ArpeggioPatternType::UpDown => Some(ArpeggioPattern::UpDown),
+        theremotion_ui::ArpeggioPatternType::Random => Some(ArpeggioPattern::Random),
+        theremotion_ui::ArpeggioPatternType::AsPlayed => Some(ArpeggioPattern::AsPlayed),
+    }
+}
+
+/// Pattern of the arpeggiator shown in the UI
+fn arpeggio_pattern_type(value: Option<ArpeggioPattern>) -> theremotion_ui::ArpeggioPatternType {
+    match value {
+        None => theremotion_ui::ArpeggioPatternType::Off,
+        Some(ArpeggioPattern::Up) => theremotion_ui::ArpeggioPatternType::Up,
+        Some(ArpeggioPattern::Down) => theremotion_ui::ArpeggioPatternType::Down,
+        Some(ArpeggioPattern::UpDown) => theremotion_ui::ArpeggioPatternType::UpDown,
+        Some(ArpeggioPattern::Random) => theremotion_ui::ArpeggioPatternType::Random,
+        Some(ArpeggioPattern::AsPlayed) => theremotion_ui::ArpeggioPatternType::AsPlayed,
+    }
+}
+
+/// SVG path of a curve in a unit viewbox, the y axis pointing down
+fn curve_preview(curve: &Curve) -> SharedString {
+    curve
+        .preview(CURVE_PREVIEW_SAMPLES)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (x, y))| {
+            let command = if i == 0 { "M" } else { "L" };
+            std::format!("{command} {x:.3} {:.3}", 1.0 - y)
+        })
+        .join(" ")
+        .into()
+}
+
 fn ui_control(control: &crate::controls::Control) -> theremotion_ui::DspControl {
     theremotion_ui::DspControl {
         min: *control.input.range.start(),
@@ -0,0 +1,112 @@
+#[cfg(feature = "leap")]
+mod leap;
+mod osc;
+mod pointer;
+mod replay;
+mod synthetic;
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    settings::{Geometry, TrackingSettings, TrackingSourceKind},
+    thread_conductor::{self, TrackingStatus},
+    HandMessage, HandType,
+};
+
+#[cfg(feature = "leap")]
+pub use leap::LeapSource;
+pub use osc::OscSource;
+pub use pointer::{PointerEvent, PointerHand, PointerSource};
+pub use replay::ReplaySource;
+pub use synthetic::SyntheticSource;
+
+/// Maximum time a source should block when polled
+pub const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Event produced by the hand tracking
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum TrackingEvent {
+    /// Position of a visible hand
+    Hand(HandMessage),
+    /// List of visible hands
+    VisibleHands { left: bool, right: bool },
+    /// Status of the tracking device
+    Status(TrackingStatus),
+}
+
+impl From<TrackingEvent> for thread_conductor::Msg {
+    fn from(value: TrackingEvent) -> Self {
+        match value {
+            TrackingEvent::Hand(h) => thread_conductor::Msg::HandUpdate(h),
+            TrackingEvent::VisibleHands { left, right } => {
+                thread_conductor::Msg::VisibleHands { left, right }
+            }
+            TrackingEvent::Status(status) => thread_conductor::Msg::TrackingStatus(status),
+        }
+    }
+}
+
+/// Provider of hand tracking events
+pub trait TrackingSource {
+    /// Wait for the next tracking events.
+    ///
+    /// Implementations should not block for much longer than [`POLL_TIMEOUT`]
+    /// so that the tracking thread stays responsive.
+    fn poll(&mut self) -> Vec<TrackingEvent>;
+
+    /// Pointer interaction on the playing tab, ignored by most sources
+    fn pointer(&mut self, _hand_type: HandType, _event: PointerEvent, _geometry: &Geometry) {}
+}
+
+/// Open the tracking source selected in the settings
+pub fn open(settings: &TrackingSettings) -> Box<dyn TrackingSource> {
+    log::debug!("Opening the {:?} tracking source", settings.source);
+    match settings.source {
+        #[cfg(feature = "leap")]
+        TrackingSourceKind::Leap => match LeapSource::new() {
+            Ok(source) => Box::new(source),
+            Err(err) => Box::new(UnavailableSource::error(err.to_string())),
+        },
+        #[cfg(not(feature = "leap"))]
+        TrackingSourceKind::Leap => Box::new(UnavailableSource::error(
+            "Built without Leap Motion support".to_string(),
+        )),
+        TrackingSourceKind::Replay => match ReplaySource::new(&settings.replay) {
+            Ok(source) => Box::new(source),
+            Err(err) => Box::new(UnavailableSource::error(format!("{err:#}"))),
+        },
+        TrackingSourceKind::Synthetic => Box::new(SyntheticSource::default()),
+        TrackingSourceKind::Pointer => Box::new(PointerSource::default()),
+        TrackingSourceKind::Osc => match OscSource::new(&settings.osc) {
+            Ok(source) => Box::new(source),
+            Err(err) => Box::new(UnavailableSource::error(format!("{err:#}"))),
+        },
+    }
+}
+
+/// Source reporting why the tracking is not available
+struct UnavailableSource {
+    /// Status to report on the next poll
+    status: Option<TrackingStatus>,
+}
+
+impl UnavailableSource {
+    fn error(message: String) -> Self {
+        log::error!("Tracking unavailable: {message}");
+        Self {
+            status: Some(TrackingStatus::Error(message)),
+        }
+    }
+}
+
+impl TrackingSource for UnavailableSource {
+    fn poll(&mut self) -> Vec<TrackingEvent> {
+        if let Some(status) = self.status.take() {
+            return vec![TrackingEvent::Status(status)];
+        }
+        std::thread::sleep(POLL_TIMEOUT);
+        vec![]
+    }
+}
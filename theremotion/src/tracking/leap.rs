@@ -0,0 +1,94 @@
+use leaprs::{Connection, ConnectionConfig, Error, EventRef};
+
+use crate::thread_conductor::TrackingStatus;
+
+use super::{TrackingEvent, TrackingSource, POLL_TIMEOUT};
+
+/// Hand tracking from a Leap Motion device
+pub struct LeapSource {
+    connection: Connection,
+}
+
+impl LeapSource {
+    /// Connect to the Leap Motion service
+    pub fn new() -> Result<Self, Error> {
+        let mut connection = Connection::create(ConnectionConfig::default())?;
+        connection.open()?;
+        Ok(Self { connection })
+    }
+}
+
+impl TrackingSource for LeapSource {
+    fn poll(&mut self) -> Vec<TrackingEvent> {
+        match self.connection.poll(POLL_TIMEOUT.as_millis() as _) {
+            Ok(message) => match message.event() {
+                EventRef::Tracking(e) => {
+                    // List of visible hands
+                    let hands = e.hands();
+
+                    let mut events: Vec<TrackingEvent> = hands
+                        .iter()
+                        .map(|hand| TrackingEvent::Hand(crate::HandMessage::from(*hand)))
+                        .collect();
+
+                    events.push(TrackingEvent::VisibleHands {
+                        left: hands
+                            .iter()
+                            .any(|h| h.hand_type() == leaprs::HandType::Left),
+                        right: hands
+                            .iter()
+                            .any(|h| h.hand_type() == leaprs::HandType::Right),
+                    });
+
+                    events.push(TrackingEvent::Status(TrackingStatus::Ok));
+                    events
+                }
+                EventRef::Connection(_) => vec![TrackingEvent::Status(TrackingStatus::Warning(
+                    "No device".to_string(),
+                ))],
+                EventRef::ConnectionLost(_) => vec![TrackingEvent::Status(TrackingStatus::Error(
+                    "Connection lost".to_string(),
+                ))],
+                EventRef::Device(_) => vec![TrackingEvent::Status(TrackingStatus::Ok)],
+                EventRef::DeviceFailure(_) => vec![TrackingEvent::Status(TrackingStatus::Error(
+                    "Device failure".to_string(),
+                ))],
+                EventRef::DeviceLost => vec![TrackingEvent::Status(TrackingStatus::Error(
+                    "Device disconnected".to_string(),
+                ))],
+                _ => vec![],
+            },
+            Err(err) => match err {
+                Error::Timeout => vec![], // spammey without any device
+                Error::NotConnected => vec![TrackingEvent::Status(TrackingStatus::Warning(
+                    err.to_string(),
+                ))],
+                _ => vec![TrackingEvent::Status(TrackingStatus::Error(
+                    err.to_string(),
+                ))],
+            },
+        }
+    }
+}
+
+impl From<leaprs::HandRef<'_>> for crate::HandMessage {
+    fn from(value: leaprs::HandRef<'_>) -> Self {
+        crate::HandMessage {
+            hand_type: value.hand_type().into(),
+            position: value.palm().position().into(),
+            velocity: value.palm().velocity().into(),
+            rotation: value.arm().rotation().into(),
+            pinch: value.pinch_strength,
+            grab: value.grab_strength,
+        }
+    }
+}
+
+impl From<leaprs::HandType> for crate::HandType {
+    fn from(value: leaprs::HandType) -> Self {
+        match value {
+            leaprs::HandType::Left => crate::HandType::Left,
+            leaprs::HandType::Right => crate::HandType::Right,
+        }
+    }
+}
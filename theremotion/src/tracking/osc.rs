@@ -0,0 +1,495 @@
+use std::{
+    collections::HashMap,
+    net::UdpSocket,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+use nalgebra::{Quaternion, UnitQuaternion, Vector3};
+
+use crate::{settings::OscSettings, thread_conductor::TrackingStatus, HandMessage, HandType};
+
+use super::{TrackingEvent, TrackingSource, POLL_TIMEOUT};
+
+/// Prefix of the hand messages addresses
+const ADDRESS_PREFIX: &str = "/theremotion/hand/";
+
+/// A hand without update for this long is not visible anymore
+const HAND_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Without any packet for this long, the tracker is considered disconnected
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Most packets handled by a poll, so that a flood of packets cannot stall
+/// the tracking
+const MAX_PACKETS_PER_POLL: usize = 256;
+
+/// Hand tracking received over Open Sound Control
+///
+/// The hands are updated with messages such as `/theremotion/hand/right/position x y z`
+/// or `/theremotion/hand/right "position" x y z`. The fields are `position` and
+/// `velocity` (mm, mm/s), `rotation` (euler angles in rad, or a `w x y z` quaternion),
+/// `pinch` and `grab` (0 to 1).
+pub struct OscSource {
+    socket: UdpSocket,
+    /// Last known state of the hands
+    hands: HashMap<HandType, OscHand>,
+    /// Time of the last received packet
+    last_packet: Option<Instant>,
+}
+
+/// Hand state built from the OSC messages
+#[derive(Debug, Clone)]
+struct OscHand {
+    message: HandMessage,
+    /// Time of the last update of this hand
+    last_update: Instant,
+    /// Time of the last position, the other fields being updated in between
+    last_position: Option<Instant>,
+    /// The velocity is sent by the tracker instead of being computed
+    has_velocity: bool,
+    /// The hand changed since the last poll
+    dirty: bool,
+}
+
+impl OscSource {
+    /// Listen for OSC packets
+    pub fn new(settings: &OscSettings) -> Result<Self> {
+        let socket = UdpSocket::bind(&settings.address)
+            .with_context(|| format!("Failed to listen for OSC on {}", settings.address))?;
+        socket.set_read_timeout(Some(POLL_TIMEOUT))?;
+        log::info!("Listening for OSC on {}", settings.address);
+        Ok(Self {
+            socket,
+            hands: HashMap::new(),
+            last_packet: None,
+        })
+    }
+
+    /// Wait for a packet, then handle all the ones already received so that
+    /// the hands do not lag behind the tracker
+    fn receive(&mut self) -> std::io::Result<()> {
+        let mut buf = [0; 1536];
+        let mut size = match self.socket.recv(&mut buf) {
+            Ok(size) => size,
+            Err(err) if is_timeout(&err) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        self.socket.set_nonblocking(true)?;
+        let mut drained = Ok(());
+        for _ in 0..MAX_PACKETS_PER_POLL {
+            self.on_packet(&buf[..size]);
+            match self.socket.recv(&mut buf) {
+                Ok(next) => size = next,
+                Err(err) => {
+                    if !is_timeout(&err) {
+                        drained = Err(err);
+                    }
+                    break;
+                }
+            }
+        }
+        self.socket.set_nonblocking(false)?;
+        drained
+    }
+
+    fn on_packet(&mut self, packet: &[u8]) {
+        let now = Instant::now();
+        self.last_packet = Some(now);
+        match decode_packet(packet) {
+            Ok(messages) => {
+                for message in messages {
+                    self.on_message(message, now);
+                }
+            }
+            Err(err) => log::warn!("Invalid OSC packet: {err:#}"),
+        }
+    }
+
+    /// Update the hands from a decoded message, received at `now`
+    fn on_message(&mut self, message: OscMessage, now: Instant) {
+        let Some(path) = message.address.strip_prefix(ADDRESS_PREFIX) else {
+            log::debug!("Ignoring OSC message {}", message.address);
+            return;
+        };
+
+        // The field is either the end of the address or the first argument
+        let mut args = message.args.as_slice();
+        let (side, field) = match path.split_once('/') {
+            Some((side, field)) => (side, field),
+            None => match args.split_first() {
+                Some((OscArg::String(field), rest)) => {
+                    args = rest;
+                    (path, field.as_str())
+                }
+                _ => {
+                    log::debug!("Missing field in OSC message {}", message.address);
+                    return;
+                }
+            },
+        };
+        let hand_type = match side {
+            "left" => HandType::Left,
+            "right" => HandType::Right,
+            _ => {
+                log::debug!("Unknown hand in OSC message {}", message.address);
+                return;
+            }
+        };
+        let values: Vec<f32> = args.iter().filter_map(OscArg::as_f32).collect();
+
+        let hand = self.hands.entry(hand_type).or_insert_with(|| OscHand {
+            message: HandMessage {
+                hand_type,
+                position: Vector3::zeros(),
+                velocity: Vector3::zeros(),
+                rotation: UnitQuaternion::identity(),
+                pinch: 0.0,
+                grab: 0.0,
+            },
+            last_update: now,
+            last_position: None,
+            has_velocity: false,
+            dirty: false,
+        });
+
+        match (field, values.as_slice()) {
+            ("position", &[x, y, z]) => {
+                let position = Vector3::new(x, y, z);
+                if let (false, Some(last_position)) = (hand.has_velocity, hand.last_position) {
+                    let dt = now.duration_since(last_position).as_secs_f32();
+                    if dt > 0.0 {
+                        hand.message.velocity = (position - hand.message.position) / dt;
+                    }
+                }
+                hand.message.position = position;
+                hand.last_position = Some(now);
+            }
+            ("velocity", &[x, y, z]) => {
+                hand.has_velocity = true;
+                hand.message.velocity = Vector3::new(x, y, z);
+            }
+            ("rotation", &[roll, pitch, yaw]) => {
+                hand.message.rotation = UnitQuaternion::from_euler_angles(roll, pitch, yaw);
+            }
+            ("rotation", &[w, i, j, k]) => {
+                hand.message.rotation =
+                    UnitQuaternion::from_quaternion(Quaternion::new(w, i, j, k));
+            }
+            ("pinch", &[pinch]) => hand.message.pinch = pinch.clamp(0.0, 1.0),
+            ("grab", &[grab]) => hand.message.grab = grab.clamp(0.0, 1.0),
+            _ => {
+                log::debug!(
+                    "Unexpected OSC message {} {:?}",
+                    message.address,
+                    message.args
+                );
+                return;
+            }
+        }
+        hand.last_update = now;
+        hand.dirty = true;
+    }
+}
+
+impl TrackingSource for OscSource {
+    fn poll(&mut self) -> Vec<TrackingEvent> {
+        if let Err(err) = self.receive() {
+            return vec![TrackingEvent::Status(TrackingStatus::Error(format!(
+                "OSC: {err}"
+            )))];
+        }
+
+        let mut events = vec![];
+        for hand in self.hands.values_mut() {
+            if hand.dirty {
+                hand.dirty = false;
+                events.push(TrackingEvent::Hand(hand.message.clone()));
+            }
+        }
+        let visible = |hand_type| {
+            self.hands
+                .get(&hand_type)
+                .is_some_and(|h| h.last_update.elapsed() < HAND_TIMEOUT)
+        };
+        events.push(TrackingEvent::VisibleHands {
+            left: visible(HandType::Left),
+            right: visible(HandType::Right),
+        });
+
+        let status = match self.last_packet {
+            Some(last) if last.elapsed() < CONNECTION_TIMEOUT => TrackingStatus::Ok,
+            Some(_) => TrackingStatus::Warning("OSC packets stopped".to_string()),
+            None => TrackingStatus::Warning("Waiting for OSC packets".to_string()),
+        };
+        events.push(TrackingEvent::Status(status));
+        events
+    }
+}
+
+/// Nothing was received in time
+fn is_timeout(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    )
+}
+
+/// Decoded OSC message
+#[derive(Debug, Clone, PartialEq)]
+struct OscMessage {
+    address: String,
+    args: Vec<OscArg>,
+}
+
+/// Argument of an OSC message
+#[derive(Debug, Clone, PartialEq)]
+enum OscArg {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    Bool(bool),
+    Blob(Vec<u8>),
+}
+
+impl OscArg {
+    /// Numeric value of the argument
+    fn as_f32(&self) -> Option<f32> {
+        match self {
+            OscArg::Int(v) => Some(*v as f32),
+            OscArg::Long(v) => Some(*v as f32),
+            OscArg::Float(v) => Some(*v),
+            OscArg::Double(v) => Some(*v as f32),
+            _ => None,
+        }
+    }
+}
+
+/// Decode an OSC packet, flattening the bundles
+fn decode_packet(data: &[u8]) -> Result<Vec<OscMessage>> {
+    let mut reader = OscReader { data };
+    if data.starts_with(b"#bundle\0") {
+        reader.take(8)?;
+        reader.take(8)?; // time tag, messages are applied immediately
+        let mut messages = vec![];
+        while !reader.data.is_empty() {
+            let size = reader.read_i32()?;
+            let size = usize::try_from(size).context("Negative bundle element size")?;
+            messages.extend(decode_packet(reader.take(size)?)?);
+        }
+        Ok(messages)
+    } else {
+        let address = reader.read_string()?;
+        if !address.starts_with('/') {
+            bail!("Invalid OSC address {address}");
+        }
+        let tags = reader.read_string()?;
+        let Some(tags) = tags.strip_prefix(',') else {
+            bail!("Missing OSC type tags");
+        };
+        let args = tags
+            .chars()
+            .map(|tag| {
+                Ok(match tag {
+                    'i' => OscArg::Int(reader.read_i32()?),
+                    'h' => OscArg::Long(i64::from_be_bytes(reader.take_array()?)),
+                    'f' => OscArg::Float(f32::from_be_bytes(reader.take_array()?)),
+                    'd' => OscArg::Double(f64::from_be_bytes(reader.take_array()?)),
+                    's' | 'S' => OscArg::String(reader.read_string()?),
+                    'T' => OscArg::Bool(true),
+                    'F' => OscArg::Bool(false),
+                    'b' => {
+                        let size =
+                            usize::try_from(reader.read_i32()?).context("Negative blob size")?;
+                        let blob = reader.take(size)?.to_vec();
+                        reader.take(padding(size))?;
+                        OscArg::Blob(blob)
+                    }
+                    _ => bail!("Unsupported OSC type tag {tag}"),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(vec![OscMessage { address, args }])
+    }
+}
+
+/// Padding needed to align some data on 4 bytes
+fn padding(size: usize) -> usize {
+    (4 - size % 4) % 4
+}
+
+/// Cursor over the bytes of an OSC packet
+struct OscReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> OscReader<'a> {
+    fn take(&mut self, size: usize) -> Result<&'a [u8]> {
+        if size > self.data.len() {
+            bail!("Truncated OSC packet");
+        }
+        let (taken, rest) = self.data.split_at(size);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn read_i32(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.take_array()?))
+    }
+
+    /// Read a null terminated string, padded to 4 bytes
+    fn read_string(&mut self) -> Result<String> {
+        let len = self
+            .data
+            .iter()
+            .position(|b| *b == 0)
+            .context("Unterminated OSC string")?;
+        let string = std::str::from_utf8(self.take(len)?)?.to_string();
+        self.take(1 + padding(len + 1))?;
+        Ok(string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    /// Encode a string the OSC way
+    fn osc_string(s: &str) -> Vec<u8> {
+        let mut data = s.as_bytes().to_vec();
+        data.push(0);
+        data.resize(data.len() + padding(data.len()), 0);
+        data
+    }
+
+    fn osc_message(address: &str, field: Option<&str>, values: &[f32]) -> Vec<u8> {
+        let mut tags = ",".to_string();
+        if field.is_some() {
+            tags.push('s');
+        }
+        tags.extend(values.iter().map(|_| 'f'));
+        let mut data = osc_string(address);
+        data.extend(osc_string(&tags));
+        if let Some(field) = field {
+            data.extend(osc_string(field));
+        }
+        for value in values {
+            data.extend(value.to_be_bytes());
+        }
+        data
+    }
+
+    #[rstest]
+    fn decode_message() {
+        let data = osc_message("/theremotion/hand/right/position", None, &[1.0, 2.0, 3.0]);
+        assert_eq!(
+            vec![OscMessage {
+                address: "/theremotion/hand/right/position".to_string(),
+                args: vec![OscArg::Float(1.0), OscArg::Float(2.0), OscArg::Float(3.0)],
+            }],
+            decode_packet(&data).unwrap()
+        );
+    }
+
+    #[rstest]
+    fn decode_bundle() {
+        let pinch = osc_message("/theremotion/hand/left/pinch", None, &[0.5]);
+        let grab = osc_message("/theremotion/hand/left", Some("grab"), &[1.0]);
+        let mut data = osc_string("#bundle");
+        data.extend([0, 0, 0, 0, 0, 0, 0, 1]);
+        for element in [&pinch, &grab] {
+            data.extend((element.len() as i32).to_be_bytes());
+            data.extend(element);
+        }
+        let messages = decode_packet(&data).unwrap();
+        assert_eq!(2, messages.len());
+        assert_eq!(
+            vec![OscArg::String("grab".to_string()), OscArg::Float(1.0)],
+            messages[1].args
+        );
+    }
+
+    #[rstest]
+    #[case(b"/a\0\0,f\0\0\0".as_slice())]
+    #[case(b"/a\0\0,x\0\0".as_slice())]
+    #[case(b"a\0\0\0,\0\0\0".as_slice())]
+    fn decode_invalid(#[case] data: &[u8]) {
+        assert!(decode_packet(data).is_err());
+    }
+
+    fn source() -> OscSource {
+        OscSource::new(&OscSettings {
+            address: "127.0.0.1:0".to_string(),
+        })
+        .unwrap()
+    }
+
+    #[rstest]
+    fn update_hands() {
+        let mut source = source();
+        let now = Instant::now();
+        for data in [
+            osc_message("/theremotion/hand/right/position", None, &[1.0, 2.0, 3.0]),
+            osc_message("/theremotion/hand/right", Some("pinch"), &[0.8]),
+            osc_message("/theremotion/hand/right/grab", None, &[2.0]),
+        ] {
+            for message in decode_packet(&data).unwrap() {
+                source.on_message(message, now);
+            }
+        }
+        let hand = &source.hands[&HandType::Right].message;
+        assert_eq!(Vector3::new(1.0, 2.0, 3.0), hand.position);
+        assert_eq!(0.8, hand.pinch);
+        assert_eq!(1.0, hand.grab);
+        assert!(!source.hands.contains_key(&HandType::Left));
+    }
+
+    #[rstest]
+    fn velocity_between_positions() {
+        let mut source = source();
+        let start = Instant::now();
+        for (data, ms) in [
+            (
+                osc_message("/theremotion/hand/left/position", None, &[0.0, 0.0, 0.0]),
+                0,
+            ),
+            (osc_message("/theremotion/hand/left/pinch", None, &[0.5]), 9),
+            (
+                osc_message("/theremotion/hand/left/position", None, &[10.0, 0.0, 0.0]),
+                10,
+            ),
+        ] {
+            for message in decode_packet(&data).unwrap() {
+                source.on_message(message, start + Duration::from_millis(ms));
+            }
+        }
+        let velocity = source.hands[&HandType::Left].message.velocity;
+        assert!((velocity.x - 1000.0).abs() < 1e-2, "{velocity}");
+    }
+
+    #[rstest]
+    fn drain_packets() {
+        let mut source = source();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = source.socket.local_addr().unwrap();
+        for x in [1.0, 2.0, 3.0] {
+            let data = osc_message("/theremotion/hand/right/position", None, &[x, 0.0, 0.0]);
+            sender.send_to(&data, address).unwrap();
+        }
+        let events = source.poll();
+        assert_eq!(3.0, source.hands[&HandType::Right].message.position.x);
+        let hands = events
+            .iter()
+            .filter(|event| matches!(event, TrackingEvent::Hand(_)))
+            .count();
+        assert_eq!(1, hands);
+    }
+}
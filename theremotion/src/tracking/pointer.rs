@@ -0,0 +1,257 @@
+use std::time::{Duration, Instant};
+
+use nalgebra::{UnitQuaternion, Vector3};
+
+use crate::{
+    controls::convert_range, settings::Geometry, thread_conductor::TrackingStatus, HandMessage,
+    HandType,
+};
+
+use super::{TrackingEvent, TrackingSource};
+
+/// Interval between two virtual hands updates
+const FRAME_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Height change for one scroll unit (mm)
+const SCROLL_HEIGHT: f32 = 0.5;
+
+/// Rotation change for one scroll unit (rad)
+const SCROLL_ANGLE: f32 = 0.005;
+
+/// Role of the hand driven by the pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerHand {
+    /// Hand driven from the pitch plot
+    Pitch,
+    /// Hand driven from the filter plot
+    Volume,
+}
+
+/// Pointer interaction on the playing tab plots
+#[derive(Debug, Clone, PartialEq)]
+pub enum PointerEvent {
+    /// Position on the pitch or filter plot, in plot coordinates
+    Move {
+        hand: PointerHand,
+        x: f32,
+        y: f32,
+        /// The pointer is pressed
+        pressed: bool,
+        /// Emulate a pinch (control key)
+        pinch: bool,
+        /// Emulate a grab (shift key)
+        grab: bool,
+    },
+    /// Wheel on the pitch or filter plot
+    ///
+    /// Changes the height of the hand, or its rotation when grabbing.
+    Scroll {
+        hand: PointerHand,
+        delta: f32,
+        grab: bool,
+    },
+    /// Position on the chord number or volume gauge, in gauge value
+    Height { hand: PointerHand, value: f32 },
+}
+
+impl PointerEvent {
+    /// Hand driven by this event
+    pub fn hand(&self) -> PointerHand {
+        match self {
+            PointerEvent::Move { hand, .. }
+            | PointerEvent::Scroll { hand, .. }
+            | PointerEvent::Height { hand, .. } => *hand,
+        }
+    }
+}
+
+/// Hand driven by the pointer
+#[derive(Debug, Clone)]
+struct VirtualHand {
+    /// Role of the hand
+    role: PointerHand,
+    /// Position of the hand (mm)
+    position: Vector3<f32>,
+    /// Position at the previous update, to compute the velocity
+    previous_position: Vector3<f32>,
+    /// Rotation of the arm, from the body (rad)
+    angle: f32,
+    pinch: bool,
+    grab: bool,
+    /// The hand was moved at least once
+    active: bool,
+}
+
+impl VirtualHand {
+    fn new(role: PointerHand, geometry: &Geometry) -> Self {
+        let position = match role {
+            PointerHand::Pitch => Vector3::new(200.0, *geometry.chords_height.start(), 0.0),
+            PointerHand::Volume => Vector3::new(125.0, *geometry.volume_height.start(), 0.0),
+        };
+        Self {
+            role,
+            position,
+            previous_position: position,
+            angle: 0.0,
+            pinch: false,
+            grab: false,
+            active: false,
+        }
+    }
+}
+
+/// Virtual hands driven by the mouse or a touchscreen on the playing tab
+pub struct PointerSource {
+    /// Left and right hands
+    hands: [(HandType, VirtualHand); 2],
+    /// Time of the last update
+    last_update: Instant,
+}
+
+impl Default for PointerSource {
+    fn default() -> Self {
+        Self {
+            hands: [
+                (
+                    HandType::Left,
+                    VirtualHand::new(PointerHand::Volume, &Geometry::default()),
+                ),
+                (
+                    HandType::Right,
+                    VirtualHand::new(PointerHand::Pitch, &Geometry::default()),
+                ),
+            ],
+            last_update: Instant::now(),
+        }
+    }
+}
+
+impl PointerSource {
+    fn hand_mut(
+        &mut self,
+        hand_type: HandType,
+        role: PointerHand,
+        geometry: &Geometry,
+    ) -> &mut VirtualHand {
+        let (_, hand) = self
+            .hands
+            .iter_mut()
+            .find(|(t, _)| *t == hand_type)
+            .expect("Both hands exist");
+        if hand.role != role {
+            // The handedness changed, restart from a neutral position
+            *hand = VirtualHand::new(role, geometry);
+        }
+        hand
+    }
+}
+
+/// Position from the body (x/z, mm) matching a point of a plot
+pub fn plot_to_body(role: PointerHand, x: f32, y: f32, geometry: &Geometry) -> (f32, f32) {
+    match role {
+        PointerHand::Pitch => (
+            geometry.antenna_x + x * geometry.mm_per_semitone,
+            geometry.antenna_z - y * geometry.mm_per_semitone,
+        ),
+        PointerHand::Volume => (
+            convert_range(x, &(-1.0..=1.0), &geometry.cutoff_distance),
+            convert_range(y, &(0.0..=1.0), &geometry.resonance_depth),
+        ),
+    }
+}
+
+impl TrackingSource for PointerSource {
+    fn poll(&mut self) -> Vec<TrackingEvent> {
+        std::thread::sleep(FRAME_INTERVAL);
+        let dt = self.last_update.elapsed().as_secs_f32();
+        self.last_update = Instant::now();
+
+        let mut events = vec![];
+        for (hand_type, hand) in &mut self.hands {
+            if !hand.active {
+                continue;
+            }
+            let x_factor = hand_type.x_factor();
+            events.push(TrackingEvent::Hand(HandMessage {
+                hand_type: *hand_type,
+                position: hand.position,
+                velocity: (hand.position - hand.previous_position) / dt,
+                rotation: UnitQuaternion::from_euler_angles(0.0, 0.0, -hand.angle * x_factor),
+                pinch: if hand.pinch { 1.0 } else { 0.0 },
+                grab: if hand.grab { 1.0 } else { 0.0 },
+            }));
+            hand.previous_position = hand.position;
+        }
+        events.push(TrackingEvent::VisibleHands {
+            left: self.hands[0].1.active,
+            right: self.hands[1].1.active,
+        });
+        events.push(TrackingEvent::Status(TrackingStatus::Ok));
+        events
+    }
+
+    fn pointer(&mut self, hand_type: HandType, event: PointerEvent, geometry: &Geometry) {
+        let x_factor = hand_type.x_factor();
+        let role = event.hand();
+        let hand = self.hand_mut(hand_type, role, geometry);
+        hand.active = true;
+        match event {
+            PointerEvent::Move {
+                x,
+                y,
+                pressed,
+                pinch,
+                grab,
+                ..
+            } => {
+                hand.pinch = pinch;
+                hand.grab = grab;
+                if pressed {
+                    // The plots show the positions mirrored for the left hand
+                    let (x, z) = plot_to_body(role, x * x_factor, y, geometry);
+                    hand.position.x = x * x_factor;
+                    hand.position.z = z;
+                }
+            }
+            PointerEvent::Scroll { delta, grab, .. } => {
+                hand.grab = grab;
+                if grab {
+                    hand.angle += delta * SCROLL_ANGLE;
+                } else {
+                    hand.position.y += delta * SCROLL_HEIGHT;
+                }
+            }
+            PointerEvent::Height { value, .. } => {
+                hand.position.y = match role {
+                    PointerHand::Pitch => {
+                        convert_range(value, &(1.0..=4.0), &geometry.chords_height)
+                    }
+                    PointerHand::Volume => {
+                        convert_range(value, &(0.0..=1.0), &geometry.volume_height)
+                    }
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(PointerHand::Pitch, -10.0, -15.0, (250.0, 25.0))]
+    #[case(PointerHand::Pitch, 0.0, 0.0, (400.0, -200.0))]
+    #[case(PointerHand::Volume, 0.0, 0.5, (125.0, 0.0))]
+    #[case(PointerHand::Volume, 1.0, 1.0, (200.0, -100.0))]
+    fn plot_to_body_position(
+        #[case] role: PointerHand,
+        #[case] x: f32,
+        #[case] y: f32,
+        #[case] expected: (f32, f32),
+    ) {
+        assert_eq!(expected, plot_to_body(role, x, y, &Geometry::default()));
+    }
+}
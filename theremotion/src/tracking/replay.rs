@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use crate::{session::Session, settings::ReplaySettings, thread_conductor::TrackingStatus};
+
+use super::{TrackingEvent, TrackingSource, POLL_TIMEOUT};
+
+/// Replay of a recorded session in place of the hand tracking device
+pub struct ReplaySource {
+    /// Session to replay
+    session: Session,
+    /// Playback speed factor (2.0 is twice as fast)
+    speed: f64,
+    /// Restart from the beginning at the end of the session
+    looping: bool,
+    /// Start of the current pass over the session
+    start: Instant,
+    /// Index of the next frame to send
+    next: usize,
+    /// The end of the replay was already reported
+    finished: bool,
+}
+
+impl ReplaySource {
+    /// Load the session to replay
+    pub fn new(settings: &ReplaySettings) -> Result<Self> {
+        let path = settings
+            .path
+            .as_ref()
+            .context("No session file selected for the replay")?;
+        let session = Session::read(path)?;
+        Ok(Self {
+            session,
+            speed: settings.speed,
+            looping: settings.looping,
+            start: Instant::now(),
+            next: 0,
+            finished: false,
+        })
+    }
+
+    /// Time at which a frame is due, relative to the start of the pass
+    fn due(&self, index: usize) -> Duration {
+        Duration::from_secs_f64(self.session.frames[index].time / self.speed)
+    }
+}
+
+impl TrackingSource for ReplaySource {
+    fn poll(&mut self) -> Vec<TrackingEvent> {
+        if self.next >= self.session.frames.len() {
+            if self.looping && !self.session.frames.is_empty() {
+                self.start = Instant::now();
+                self.next = 0;
+            } else if !self.finished {
+                self.finished = true;
+                return vec![TrackingEvent::Status(TrackingStatus::Warning(
+                    "Replay finished".to_string(),
+                ))];
+            } else {
+                std::thread::sleep(POLL_TIMEOUT);
+                return vec![];
+            }
+        }
+
+        // Wait for the next frame, without exceeding the poll timeout
+        let wait = self.due(self.next).saturating_sub(self.start.elapsed());
+        if wait > POLL_TIMEOUT {
+            std::thread::sleep(POLL_TIMEOUT);
+            return vec![];
+        }
+        std::thread::sleep(wait);
+
+        // Send every frame that is due
+        let elapsed = self.start.elapsed();
+        let mut events = vec![];
+        while self.next < self.session.frames.len() && self.due(self.next) <= elapsed {
+            events.push(self.session.frames[self.next].event.clone());
+            self.next += 1;
+        }
+        events
+    }
+}
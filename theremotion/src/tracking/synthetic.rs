@@ -0,0 +1,74 @@
+use std::time::{Duration, Instant};
+
+use nalgebra::{UnitQuaternion, Vector3};
+
+use crate::{thread_conductor::TrackingStatus, HandMessage, HandType};
+
+use super::{TrackingEvent, TrackingSource};
+
+/// Interval between two synthetic frames
+const FRAME_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Deterministic hand motions, useful to try the instrument without any device
+pub struct SyntheticSource {
+    /// Start of the motion
+    start: Instant,
+}
+
+impl Default for SyntheticSource {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl SyntheticSource {
+    /// Hands positions at a given time since the start (seconds)
+    pub fn frame(t: f64) -> Vec<TrackingEvent> {
+        let phase = std::f64::consts::TAU * t;
+        let right = HandMessage {
+            hand_type: HandType::Right,
+            // Slow sweep over the pitch range with a light vibrato
+            position: Vector3::new(
+                (30.0 + 150.0 * (0.1 * phase).sin() + 3.0 * (5.0 * phase).sin()) as f32,
+                250.0,
+                0.0,
+            ),
+            velocity: Vector3::zeros(),
+            rotation: UnitQuaternion::identity(),
+            pinch: 0.0,
+            grab: 0.0,
+        };
+        let left = HandMessage {
+            hand_type: HandType::Left,
+            // Breathing volume and filter
+            position: Vector3::new(
+                -150.0,
+                (250.0 + 100.0 * (0.25 * phase).sin()) as f32,
+                (50.0 * (0.05 * phase).cos()) as f32,
+            ),
+            velocity: Vector3::zeros(),
+            rotation: UnitQuaternion::identity(),
+            pinch: 0.0,
+            grab: 0.0,
+        };
+
+        vec![
+            TrackingEvent::Hand(right),
+            TrackingEvent::Hand(left),
+            TrackingEvent::VisibleHands {
+                left: true,
+                right: true,
+            },
+            TrackingEvent::Status(TrackingStatus::Ok),
+        ]
+    }
+}
+
+impl TrackingSource for SyntheticSource {
+    fn poll(&mut self) -> Vec<TrackingEvent> {
+        std::thread::sleep(FRAME_INTERVAL);
+        Self::frame(self.start.elapsed().as_secs_f64())
+    }
+}
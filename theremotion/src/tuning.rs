@@ -0,0 +1,433 @@
+use std::{ops::RangeInclusive, path::Path};
+
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+
+use crate::{settings::Tuning, MidiNoteF};
+
+/// Equal divisions of the octave offered in the UI
+pub const EDO_DIVISIONS: [u32; 5] = [19, 22, 24, 31, 53];
+
+impl Tuning {
+    /// Played degrees from the root note and period of the tuning (semitones)
+    ///
+    /// None in twelve-tone equal temperament, which uses the scale of the
+    /// preset.
+    pub fn steps(&self) -> Option<(Vec<f32>, f32)> {
+        let (all, period, degrees) = match self {
+            Tuning::TwelveTone => return None,
+            Tuning::Edo { divisions, degrees } => {
+                let divisions = (*divisions).max(1);
+                let all = (0..divisions)
+                    .map(|degree| 12.0 * degree as f32 / divisions as f32)
+                    .collect_vec();
+                (all, 12.0, degrees)
+            }
+            Tuning::Cents { cents, degrees, .. } => {
+                let period = cents.last()? / 100.0;
+                if period <= 0.0 {
+                    return None;
+                }
+                let all = std::iter::once(0.0)
+                    .chain(cents[..cents.len() - 1].iter().map(|c| c / 100.0))
+                    .collect_vec();
+                (all, period, degrees)
+            }
+        };
+        let steps = match degrees {
+            Some(degrees) => degrees
+                .iter()
+                .filter_map(|degree| all.get(*degree).copied())
+                .collect(),
+            None => all,
+        };
+        if steps.is_empty() {
+            return None;
+        }
+        Some((steps, period))
+    }
+
+    /// Notes of the tuning in a range, ordered
+    ///
+    /// None in twelve-tone equal temperament.
+    pub fn notes(
+        &self,
+        root: MidiNoteF,
+        range: RangeInclusive<MidiNoteF>,
+    ) -> Option<Vec<MidiNoteF>> {
+        let (steps, period) = self.steps()?;
+        let first = ((range.start().0 - root.0) / period).floor() as i32 - 1;
+        let last = ((range.end().0 - root.0) / period).ceil() as i32 + 1;
+        let notes = (first..=last)
+            .flat_map(|p| {
+                steps
+                    .iter()
+                    .map(move |step| MidiNoteF(root.0 + p as f32 * period + step))
+            })
+            .filter(|note| range.contains(note))
+            .sorted_by(|a, b| a.0.total_cmp(&b.0))
+            .dedup()
+            .collect();
+        Some(notes)
+    }
+
+    /// Index of the played degree closest to a note, whatever its period
+    pub fn closest_step(&self, root: MidiNoteF, note: MidiNoteF) -> Option<usize> {
+        let (steps, period) = self.steps()?;
+        let offset = (note.0 - root.0).rem_euclid(period);
+        steps
+            .iter()
+            .map(|step| {
+                let distance = (offset - step).rem_euclid(period);
+                distance.min(period - distance)
+            })
+            .position_min_by(|a, b| a.total_cmp(b))
+    }
+
+    /// The tuning sets the frequency of its root note itself, instead of
+    /// following the concert pitch
+    pub fn has_reference_frequency(&self) -> bool {
+        matches!(self, Tuning::Cents { root: Some(_), .. })
+    }
+
+    /// Names of the played degrees, in cents from the root note
+    pub fn step_names(&self) -> Vec<String> {
+        self.steps()
+            .map(|(steps, _)| {
+                steps
+                    .iter()
+                    .map(|step| format!("{:.0}", step * 100.0))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Tunings offered in the UI, including the current one
+pub fn tuning_choices(current: &Tuning) -> Vec<(String, Tuning)> {
+    let mut choices = vec![("12-TET".to_string(), Tuning::TwelveTone)];
+    choices.extend(EDO_DIVISIONS.iter().map(|divisions| {
+        (
+            format!("{divisions}-EDO"),
+            Tuning::Edo {
+                divisions: *divisions,
+                degrees: None,
+            },
+        )
+    }));
+    if !choices.iter().any(|(_, tuning)| tuning == current) {
+        let name = match current {
+            Tuning::TwelveTone => "12-TET".to_string(),
+            Tuning::Edo { divisions, .. } => format!("{divisions}-EDO (custom)"),
+            Tuning::Cents { name, .. } => name.clone(),
+        };
+        choices.push((name, current.clone()));
+    }
+    choices
+}
+
+/// Import a Scala scale file, with an optional keyboard mapping file
+///
+/// Without keyboard mapping, the first degree is the root note of the preset.
+pub fn import_scala(scl: &Path, kbm: Option<&Path>) -> Result<Tuning> {
+    let text = std::fs::read_to_string(scl)
+        .with_context(|| format!("Failed to read the Scala file {}", scl.display()))?;
+    let (name, cents) =
+        parse_scl(&text).with_context(|| format!("Invalid Scala file {}", scl.display()))?;
+    let name = if name.is_empty() {
+        scl.file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default()
+    } else {
+        name
+    };
+    let (degrees, root) = match kbm {
+        Some(kbm) => {
+            let text = std::fs::read_to_string(kbm)
+                .with_context(|| format!("Failed to read the mapping file {}", kbm.display()))?;
+            let mapping = parse_kbm(&text)
+                .with_context(|| format!("Invalid mapping file {}", kbm.display()))?;
+            (mapping.degrees(cents.len()), Some(mapping.root(&cents)?))
+        }
+        None => (None, None),
+    };
+    Ok(Tuning::Cents {
+        name,
+        cents,
+        degrees,
+        root,
+    })
+}
+
+/// Lines of a Scala file without the comments
+fn scala_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().filter(|line| !line.starts_with('!'))
+}
+
+/// Parse a Scala scale, returns its description and the cents of its degrees
+fn parse_scl(text: &str) -> Result<(String, Vec<f32>)> {
+    let mut lines = scala_lines(text);
+    let description = lines.next().context("Missing description")?.trim();
+    let count: usize = lines
+        .next()
+        .context("Missing number of notes")?
+        .trim()
+        .parse()
+        .context("Invalid number of notes")?;
+    let cents = lines
+        .take(count)
+        .map(|line| {
+            let value = line.split_whitespace().next().context("Missing pitch")?;
+            parse_pitch(value).with_context(|| format!("Invalid pitch {value}"))
+        })
+        .collect::<Result<Vec<f32>>>()?;
+    if count == 0 || cents.len() != count {
+        bail!("Expected {count} pitches, found {}", cents.len());
+    }
+    Ok((description.to_string(), cents))
+}
+
+/// Parse a pitch of a Scala scale in cents, a ratio or an integer
+fn parse_pitch(value: &str) -> Result<f32> {
+    if value.contains('.') {
+        return Ok(value.parse()?);
+    }
+    let (numerator, denominator) = value.split_once('/').unwrap_or((value, "1"));
+    let ratio = numerator.parse::<f64>()? / denominator.parse::<f64>()?;
+    if !(ratio > 0.0 && ratio.is_finite()) {
+        bail!("The ratio must be positive");
+    }
+    Ok((1200.0 * ratio.log2()) as f32)
+}
+
+/// Scala keyboard mapping
+#[derive(Debug, PartialEq)]
+struct KeyboardMapping {
+    /// Note where the first degree is mapped
+    middle_note: i64,
+    /// Note with a known frequency
+    reference_note: i64,
+    /// Frequency of the reference note (Hz)
+    reference_frequency: f64,
+    /// Degree of the formal octave
+    octave_degree: i64,
+    /// Degrees of the keys from the middle note, None for unmapped keys
+    mapping: Vec<Option<i64>>,
+}
+
+impl KeyboardMapping {
+    /// Played degrees, None if all of them are
+    fn degrees(&self, count: usize) -> Option<Vec<usize>> {
+        if self.mapping.is_empty() {
+            return None;
+        }
+        Some(
+            self.mapping
+                .iter()
+                .flatten()
+                .map(|degree| degree.rem_euclid(count as i64) as usize)
+                .sorted()
+                .dedup()
+                .collect(),
+        )
+    }
+
+    /// Note of the first degree, from the reference frequency
+    fn root(&self, cents: &[f32]) -> Result<f32> {
+        let offset = self.reference_note - self.middle_note;
+        let degree = if self.mapping.is_empty() {
+            offset
+        } else {
+            let size = self.mapping.len() as i64;
+            let octave_degree = if self.octave_degree == 0 {
+                size
+            } else {
+                self.octave_degree
+            };
+            let degree = self.mapping[offset.rem_euclid(size) as usize]
+                .context("The reference note is not mapped")?;
+            degree + offset.div_euclid(size) * octave_degree
+        };
+        let root_frequency =
+            self.reference_frequency / 2f64.powf(degree_cents(cents, degree) / 1200.0);
+        Ok((69.0 + 12.0 * (root_frequency / 440.0).log2()) as f32)
+    }
+}
+
+/// Cents of a degree of a Scala scale, from its first degree
+fn degree_cents(cents: &[f32], degree: i64) -> f64 {
+    let count = cents.len() as i64;
+    let period = cents[cents.len() - 1] as f64;
+    let index = degree.rem_euclid(count);
+    let in_period = if index == 0 {
+        0.0
+    } else {
+        cents[index as usize - 1] as f64
+    };
+    degree.div_euclid(count) as f64 * period + in_period
+}
+
+/// Parse a Scala keyboard mapping
+fn parse_kbm(text: &str) -> Result<KeyboardMapping> {
+    let mut values = scala_lines(text).filter_map(|line| line.split_whitespace().next());
+    let mut next = |name: &str| values.next().with_context(|| format!("Missing {name}"));
+    let size: usize = next("map size")?.parse()?;
+    let _first_note: i64 = next("first note")?.parse()?;
+    let _last_note: i64 = next("last note")?.parse()?;
+    let middle_note = next("middle note")?.parse()?;
+    let reference_note = next("reference note")?.parse()?;
+    let reference_frequency: f64 = next("reference frequency")?.parse()?;
+    let octave_degree = next("octave degree")?.parse()?;
+    if reference_frequency <= 0.0 {
+        bail!("The reference frequency must be positive");
+    }
+    let mut mapping = Vec::with_capacity(size);
+    for _ in 0..size {
+        // Missing keys at the end are unmapped
+        let degree = match values.next() {
+            None | Some("x") => None,
+            Some(value) => Some(value.parse()?),
+        };
+        mapping.push(degree);
+    }
+    Ok(KeyboardMapping {
+        middle_note,
+        reference_note,
+        reference_frequency,
+        octave_degree,
+        mapping,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    const RAST: &str = "! rast.scl
+!
+Maqam Rast, quarter tones
+ 7
+!
+ 200.0
+ 350.0
+ 500.0
+ 700.0
+ 900.0
+ 1050.0
+ 2/1
+";
+
+    fn rast() -> Tuning {
+        let (name, cents) = parse_scl(RAST).unwrap();
+        Tuning::Cents {
+            name,
+            cents,
+            degrees: None,
+            root: None,
+        }
+    }
+
+    #[rstest]
+    fn scl() {
+        let (name, cents) = parse_scl(RAST).unwrap();
+        assert_eq!("Maqam Rast, quarter tones", name);
+        assert_eq!(
+            vec![200.0, 350.0, 500.0, 700.0, 900.0, 1050.0, 1200.0],
+            cents
+        );
+        assert!(parse_scl("name\n3\n100.0\n").is_err());
+        assert!(parse_scl("name\n1\n-3/2\n").is_err());
+    }
+
+    #[rstest]
+    #[case("100.0", 100.0)]
+    #[case("3/2", 701.955)]
+    #[case("2", 1200.0)]
+    #[case("5/4 major third", 386.3137)]
+    fn pitches(#[case] value: &str, #[case] expected: f32) {
+        let line = format!("\n1\n{value}\n");
+        let (_, cents) = parse_scl(&line).unwrap();
+        assert!((expected - cents[0]).abs() < 1e-3);
+    }
+
+    #[rstest]
+    fn scale_notes() {
+        let notes = rast()
+            .notes(MidiNoteF(60.0), MidiNoteF(59.0)..=MidiNoteF(72.0))
+            .unwrap();
+        let expected = [58.5, 60.0, 62.0, 63.5, 65.0, 67.0, 69.0, 70.5, 72.0];
+        assert_eq!(
+            expected[1..].iter().map(|n| MidiNoteF(*n)).collect_vec(),
+            notes
+        );
+        assert_eq!(
+            Some(2),
+            rast().closest_step(MidiNoteF(60.0), MidiNoteF(75.4))
+        );
+        assert_eq!(None, Tuning::TwelveTone.steps());
+    }
+
+    #[rstest]
+    fn edo() {
+        let tuning = Tuning::Edo {
+            divisions: 24,
+            degrees: Some(vec![0, 4, 7]),
+        };
+        let notes = tuning
+            .notes(MidiNoteF(0.0), MidiNoteF(0.0)..=MidiNoteF(12.0))
+            .unwrap();
+        assert_eq!(
+            vec![
+                MidiNoteF(0.0),
+                MidiNoteF(2.0),
+                MidiNoteF(3.5),
+                MidiNoteF(12.0)
+            ],
+            notes
+        );
+        assert_eq!(vec!["0", "200", "350"], tuning.step_names());
+    }
+
+    #[rstest]
+    fn kbm() {
+        let mapping = parse_kbm(
+            "! 7 degrees on the white keys
+12
+0
+127
+60
+69
+440.0
+7
+! mapping
+0
+x
+1
+x
+2
+3
+x
+4
+x
+5
+x
+6
+",
+        )
+        .unwrap();
+        assert_eq!(Some(vec![0, 1, 2, 3, 4, 5, 6]), mapping.degrees(7));
+        // The A is the 6th degree, 900 cents above the root
+        let (_, cents) = parse_scl(RAST).unwrap();
+        assert!((60.0 - mapping.root(&cents).unwrap()).abs() < 1e-4);
+    }
+
+    #[rstest]
+    fn choices() {
+        assert_eq!(6, tuning_choices(&Tuning::TwelveTone).len());
+        let choices = tuning_choices(&rast());
+        assert_eq!(7, choices.len());
+        assert_eq!("Maqam Rast, quarter tones", choices[6].0);
+    }
+}
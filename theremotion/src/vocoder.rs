@@ -0,0 +1,193 @@
+use std::f32::consts::PI;
+
+/// Bands of the vocoder
+const BANDS: usize = 16;
+
+/// Center frequency of the lowest band (Hz)
+const LOWEST_BAND: f32 = 120.0;
+
+/// Center frequency of the highest band (Hz)
+const HIGHEST_BAND: f32 = 7000.0;
+
+/// Time for the envelope of the input to rise (s)
+const ATTACK: f32 = 0.005;
+
+/// Time for the envelope of the input to fall (s)
+const RELEASE: f32 = 0.04;
+
+/// Highest level of the vocoded lead, at the threshold of the compressor of
+/// the mix (-4 dB)
+const LIMIT: f32 = 0.63;
+
+/// Time for the limiter to let the level rise again (s)
+const LIMIT_RELEASE: f32 = 0.1;
+
+/// Second order band pass filter, with a peak gain of 0 dB
+#[derive(Debug, Clone, Default)]
+struct BandPass {
+    b0: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl BandPass {
+    fn new(frequency: f32, q: f32, sample_rate: f32) -> Self {
+        let w0 = 2.0 * PI * frequency / sample_rate;
+        let alpha = w0.sin() / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: alpha / a0,
+            a1: -2.0 * w0.cos() / a0,
+            a2: (1.0 - alpha) / a0,
+            ..Default::default()
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * (x - self.x2) - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+/// Band of the vocoder: each side of the instrument is filtered by the
+/// envelope of the input in the same band
+#[derive(Debug, Clone)]
+struct Band {
+    carriers: [BandPass; 2],
+    modulator: BandPass,
+    envelope: f32,
+}
+
+/// Channel vocoder, giving the spectral envelope of the input to the
+/// instrument
+///
+/// Its output skips the compressor of the DSP, so it is limited instead.
+#[derive(Debug, Clone)]
+pub struct Vocoder {
+    bands: Vec<Band>,
+    attack: f32,
+    release: f32,
+    /// Peak level of the output, followed by the limiter
+    level: f32,
+    limit_release: f32,
+}
+
+impl Vocoder {
+    pub fn new(sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1) as f32;
+        // Geometric spacing, each band reaching its neighbours
+        let ratio = (HIGHEST_BAND / LOWEST_BAND).powf(1.0 / (BANDS - 1) as f32);
+        let q = ratio.sqrt() / (ratio - 1.0);
+        let bands = (0..BANDS)
+            .map(|band| LOWEST_BAND * ratio.powi(band as i32))
+            .filter(|frequency| *frequency < sample_rate * 0.45)
+            .map(|frequency| Band {
+                carriers: [
+                    BandPass::new(frequency, q, sample_rate),
+                    BandPass::new(frequency, q, sample_rate),
+                ],
+                modulator: BandPass::new(frequency, q, sample_rate),
+                envelope: 0.0,
+            })
+            .collect();
+        Self {
+            bands,
+            attack: (-1.0 / (ATTACK * sample_rate)).exp(),
+            release: (-1.0 / (RELEASE * sample_rate)).exp(),
+            level: 0.0,
+            limit_release: (-1.0 / (LIMIT_RELEASE * sample_rate)).exp(),
+        }
+    }
+
+    /// Replace the left and right sides of the carrier by their bands
+    /// following the envelope of the modulator
+    pub fn process(&mut self, [left, right]: [&mut [f32]; 2], modulator: &[f32]) {
+        let carrier = left.iter_mut().zip(right.iter_mut());
+        for ((left, right), input) in carrier.zip(modulator) {
+            let mut output = [0.0; 2];
+            for band in &mut self.bands {
+                let level = band.modulator.process(*input).abs();
+                let coefficient = if level > band.envelope {
+                    self.attack
+                } else {
+                    self.release
+                };
+                band.envelope = level + coefficient * (band.envelope - level);
+                output[0] += band.carriers[0].process(*left) * band.envelope;
+                output[1] += band.carriers[1].process(*right) * band.envelope;
+            }
+            // Peaks are caught at once, without overshoot
+            let peak = output[0].abs().max(output[1].abs());
+            self.level = if peak > self.level {
+                peak
+            } else {
+                peak + self.limit_release * (self.level - peak)
+            };
+            let gain = if self.level > LIMIT {
+                LIMIT / self.level
+            } else {
+                1.0
+            };
+            [*left, *right] = output.map(|sample| sample * gain);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 48000;
+
+    fn sine(frequency: f32, length: usize) -> Vec<f32> {
+        (0..length)
+            .map(|i| (2.0 * PI * frequency * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |peak, s| s.abs().max(peak))
+    }
+
+    #[rstest]
+    #[case(1000.0, 1000.0, 0.3..1.5)]
+    #[case(1000.0, 0.0, 0.0..0.001)]
+    #[case(200.0, 4000.0, 0.0..0.1)]
+    fn bands(#[case] carrier: f32, #[case] modulator: f32, #[case] expected: std::ops::Range<f32>) {
+        let mut vocoder = Vocoder::new(SAMPLE_RATE);
+        let mut left = sine(carrier, 9600);
+        let mut right = left.clone();
+        vocoder.process([&mut left, &mut right], &sine(modulator, 9600));
+        // After the filters settle
+        let peak = peak(&left[4800..]);
+        assert!(expected.contains(&peak), "{peak}");
+        assert_eq!(left, right);
+    }
+
+    #[rstest]
+    fn limited() {
+        let mut vocoder = Vocoder::new(SAMPLE_RATE);
+        let mut left: Vec<f32> = sine(1000.0, 9600).iter().map(|s| 10.0 * s).collect();
+        let mut right = vec![0.0; 9600];
+        vocoder.process([&mut left, &mut right], &sine(1000.0, 9600));
+        let peak = peak(&left);
+        assert!(peak <= LIMIT + 1e-6, "{peak}");
+        assert!(peak > 0.5 * LIMIT, "{peak}");
+    }
+
+    #[rstest]
+    fn bands_under_nyquist() {
+        assert_eq!(BANDS, Vocoder::new(SAMPLE_RATE).bands.len());
+        assert!(Vocoder::new(8000).bands.len() < BANDS);
+    }
+}
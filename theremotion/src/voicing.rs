@@ -0,0 +1,172 @@
+use crate::{
+    settings::{Geometry, ModulationHand, Voicing, VoicingSelector},
+    solfege::ScaleWindows,
+    HandMessage, IntervalF, MidiNoteF,
+};
+
+/// Voicings offered in the UI
+pub const COMMON_VOICINGS: [(&str, Voicing); 8] = [
+    (
+        "Triad",
+        Voicing::Degrees {
+            degrees: [0, 2, 4, 7],
+        },
+    ),
+    (
+        "Power",
+        Voicing::Degrees {
+            degrees: [0, 4, 7, 11],
+        },
+    ),
+    (
+        "Sus2",
+        Voicing::Degrees {
+            degrees: [0, 1, 4, 7],
+        },
+    ),
+    (
+        "Sus4",
+        Voicing::Degrees {
+            degrees: [0, 3, 4, 7],
+        },
+    ),
+    (
+        "Seventh",
+        Voicing::Degrees {
+            degrees: [0, 2, 4, 6],
+        },
+    ),
+    (
+        "Cluster",
+        Voicing::Degrees {
+            degrees: [0, 1, 2, 3],
+        },
+    ),
+    (
+        "Parallel fifths",
+        Voicing::Intervals {
+            semitones: [0.0, 7.0, 12.0, 19.0],
+        },
+    ),
+    (
+        "Octaves",
+        Voicing::Intervals {
+            semitones: [0.0, 12.0, -12.0, 24.0],
+        },
+    ),
+];
+
+impl Voicing {
+    /// Notes of the four voices for a played note
+    ///
+    /// The degrees follow the scale, the note can slide between two scale
+    /// notes creating a sliding chord.
+    pub fn chord(&self, scale_window: &ScaleWindows, note: MidiNoteF) -> [Option<MidiNoteF>; 4] {
+        match self {
+            Voicing::Degrees { degrees } => scale_window.autochord(note, degrees),
+            Voicing::Intervals { semitones } => {
+                semitones.map(|semitones| Some(note + IntervalF(semitones)))
+            }
+        }
+    }
+}
+
+impl VoicingSelector {
+    /// Index of the voicing selected by a hand among `count` voicings
+    ///
+    /// None if the gesture is not done by this hand or not measured.
+    pub fn select(
+        &self,
+        h: &HandMessage,
+        hand: ModulationHand,
+        geometry: &Geometry,
+        count: usize,
+    ) -> Option<usize> {
+        if hand != self.hand || count == 0 {
+            return None;
+        }
+        let x = self
+            .source
+            .normalized(h, self.hand, self.input.as_ref(), geometry)?;
+        Some(((x * count as f32) as usize).min(count - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{UnitQuaternion, Vector3};
+    use rstest::rstest;
+    use staff::{midi::MidiNote, scale::ScaleIntervals, Pitch};
+
+    use crate::{settings::HandFeature, solfege::build_scale_notes, HandType};
+
+    use super::*;
+
+    fn c_major() -> ScaleWindows {
+        ScaleWindows::from_notes(build_scale_notes(
+            Pitch::C,
+            ScaleIntervals::major(),
+            MidiNote::from_byte(0)..=MidiNote::from_byte(127),
+        ))
+    }
+
+    fn notes(chord: [Option<MidiNoteF>; 4]) -> [f32; 4] {
+        chord.map(|note| note.unwrap().note())
+    }
+
+    #[rstest]
+    #[case("Triad", [60.0, 64.0, 67.0, 72.0])]
+    #[case("Power", [60.0, 67.0, 72.0, 79.0])]
+    #[case("Sus4", [60.0, 65.0, 67.0, 72.0])]
+    #[case("Seventh", [60.0, 64.0, 67.0, 71.0])]
+    #[case("Parallel fifths", [60.0, 67.0, 72.0, 79.0])]
+    fn common_voicings(#[case] name: &str, #[case] expected: [f32; 4]) {
+        let (_, voicing) = COMMON_VOICINGS.iter().find(|(n, _)| *n == name).unwrap();
+        assert_eq!(expected, notes(voicing.chord(&c_major(), MidiNoteF(60.0))));
+    }
+
+    #[rstest]
+    fn intervals_ignore_scale() {
+        let voicing = Voicing::Intervals {
+            semitones: [0.0, 3.0, 6.0, 9.0],
+        };
+        assert_eq!(
+            [61.5, 64.5, 67.5, 70.5],
+            notes(voicing.chord(&c_major(), MidiNoteF(61.5)))
+        );
+    }
+
+    #[rstest]
+    #[case(0.0, Some(0))]
+    #[case(0.4, Some(1))]
+    #[case(0.9, Some(2))]
+    #[case(1.0, Some(2))]
+    fn select_by_grab(#[case] grab: f32, #[case] expected: Option<usize>) {
+        let h = HandMessage {
+            hand_type: HandType::Right,
+            position: Vector3::zeros(),
+            velocity: Vector3::zeros(),
+            rotation: UnitQuaternion::identity(),
+            pinch: 0.0,
+            grab,
+        };
+        let selector = VoicingSelector::default();
+        let geometry = Geometry::default();
+        assert_eq!(
+            expected,
+            selector.select(&h, ModulationHand::Volume, &geometry, 3)
+        );
+        assert_eq!(
+            None,
+            selector.select(&h, ModulationHand::Pitch, &geometry, 3)
+        );
+        let pinch = VoicingSelector {
+            source: HandFeature::Pinch,
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(0),
+            pinch.select(&h, ModulationHand::Volume, &geometry, 3)
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+
+/// Integer samples
+const FORMAT_PCM: u16 = 1;
+
+/// IEEE float samples, the format of the DSP output
+const FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// Format given by the sub format of the extension of the header
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Bytes of a sample
+const SAMPLE_BYTES: u16 = 4;
+
+/// Bytes of the header before the samples
+const HEADER_BYTES: u32 = 58;
+
+/// Writes 32 bits float samples to a WAV file
+///
+/// The sizes of the header are only known and written when finishing.
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    channels: u16,
+    /// Samples written so far, of all the channels
+    samples: u32,
+}
+
+impl WavWriter<BufWriter<File>> {
+    /// Create a WAV file
+    pub fn create(path: &Path, channels: u16, sample_rate: u32) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let f =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        Self::new(BufWriter::new(f), channels, sample_rate)
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn new(mut writer: W, channels: u16, sample_rate: u32) -> Result<Self> {
+        let block_align = channels * SAMPLE_BYTES;
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(HEADER_BYTES - 8).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&18u32.to_le_bytes())?;
+        writer.write_all(&FORMAT_IEEE_FLOAT.to_le_bytes())?;
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&(SAMPLE_BYTES * 8).to_le_bytes())?;
+        writer.write_all(&0u16.to_le_bytes())?;
+        // Frames, required for the formats other than PCM
+        writer.write_all(b"fact")?;
+        writer.write_all(&4u32.to_le_bytes())?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            channels,
+            samples: 0,
+        })
+    }
+
+    /// Write interleaved samples
+    pub fn write(&mut self, samples: &[f32]) -> Result<()> {
+        for sample in samples {
+            self.writer.write_all(&sample.to_le_bytes())?;
+        }
+        self.samples += samples.len() as u32;
+        Ok(())
+    }
+
+    /// Write the sizes in the header
+    pub fn finish(mut self) -> Result<W> {
+        let data_bytes = self.samples * SAMPLE_BYTES as u32;
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer
+            .write_all(&(HEADER_BYTES - 8 + data_bytes).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(46))?;
+        self.writer
+            .write_all(&(self.samples / self.channels as u32).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(54))?;
+        self.writer.write_all(&data_bytes.to_le_bytes())?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Read the samples of a WAV file mixed down to mono, with their sample rate
+pub fn read_mono(path: &Path) -> Result<(Vec<f32>, u32)> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_mono(&bytes).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+fn parse_mono(bytes: &[u8]) -> Result<(Vec<f32>, u32)> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        bail!("Not a WAV file");
+    }
+    // Format, channels, sample rate and bits per sample
+    let mut format = None;
+    let mut chunks = &bytes[12..];
+    while chunks.len() >= 8 {
+        let size = u32_at(chunks, 4) as usize;
+        // The size of the samples is sometimes left unknown by streaming writers
+        let body = &chunks[8..(8 + size).min(chunks.len())];
+        match &chunks[0..4] {
+            b"fmt " => {
+                if body.len() < 16 {
+                    bail!("Truncated format");
+                }
+                let mut tag = u16_at(body, 0);
+                if tag == FORMAT_EXTENSIBLE && body.len() >= 26 {
+                    tag = u16_at(body, 24);
+                }
+                format = Some((tag, u16_at(body, 2), u32_at(body, 4), u16_at(body, 14)));
+            }
+            b"data" => {
+                let (tag, channels, sample_rate, bits) =
+                    format.context("No format before the samples")?;
+                let samples = decode(tag, bits, body)?;
+                let channels = channels.max(1) as usize;
+                let mono = samples
+                    .chunks_exact(channels)
+                    .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+                    .collect();
+                return Ok((mono, sample_rate));
+            }
+            _ => {}
+        }
+        // Chunks are aligned on 2 bytes
+        chunks = &chunks[(8 + size + size % 2).min(chunks.len())..];
+    }
+    bail!("No samples")
+}
+
+/// Convert the samples to float
+fn decode(tag: u16, bits: u16, data: &[u8]) -> Result<Vec<f32>> {
+    let samples = match (tag, bits) {
+        (FORMAT_PCM, 8) => data.iter().map(|b| (*b as f32 - 128.0) / 128.0).collect(),
+        (FORMAT_PCM, 16) => data
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+            .collect(),
+        (FORMAT_PCM, 24) => data
+            .chunks_exact(3)
+            .map(|b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2147483648.0)
+            .collect(),
+        (FORMAT_PCM, 32) => data
+            .chunks_exact(4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2147483648.0)
+            .collect(),
+        (FORMAT_IEEE_FLOAT, 32) => data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+        _ => bail!("Unsupported format {tag} with {bits} bits samples"),
+    };
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    fn header() {
+        let mut wav = WavWriter::new(Cursor::new(vec![]), 2, 48000).unwrap();
+        wav.write(&[0.0, 0.5, -0.5, 1.0]).unwrap();
+        wav.write(&[0.25, 0.25]).unwrap();
+        let bytes = wav.finish().unwrap().into_inner();
+        assert_eq!(58 + 6 * 4, bytes.len());
+        assert_eq!(b"RIFF", &bytes[0..4]);
+        assert_eq!(bytes.len() as u32 - 8, u32_at(&bytes, 4));
+        assert_eq!(48000, u32_at(&bytes, 24));
+        assert_eq!(48000 * 8, u32_at(&bytes, 28));
+        assert_eq!(b"fact", &bytes[38..42]);
+        assert_eq!(3, u32_at(&bytes, 46));
+        assert_eq!(b"data", &bytes[50..54]);
+        assert_eq!(24, u32_at(&bytes, 54));
+        assert_eq!(0.5f32.to_le_bytes(), bytes[62..66]);
+    }
+
+    #[rstest]
+    fn read_written_file() {
+        let mut wav = WavWriter::new(Cursor::new(vec![]), 2, 44100).unwrap();
+        wav.write(&[0.0, 0.5, -0.5, 1.0]).unwrap();
+        let bytes = wav.finish().unwrap().into_inner();
+        let (samples, sample_rate) = parse_mono(&bytes).unwrap();
+        assert_eq!(44100, sample_rate);
+        assert_eq!(vec![0.25, 0.25], samples);
+    }
+
+    #[rstest]
+    fn read_pcm() {
+        let mut bytes = vec![];
+        bytes.extend(b"RIFF");
+        bytes.extend(40u32.to_le_bytes());
+        bytes.extend(b"WAVEfmt ");
+        bytes.extend(16u32.to_le_bytes());
+        for value in [FORMAT_PCM, 1] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(8000u32.to_le_bytes());
+        bytes.extend(16000u32.to_le_bytes());
+        for value in [2u16, 16] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(b"data");
+        bytes.extend(4u32.to_le_bytes());
+        for value in [16384i16, -32768] {
+            bytes.extend(value.to_le_bytes());
+        }
+        let (samples, sample_rate) = parse_mono(&bytes).unwrap();
+        assert_eq!(8000, sample_rate);
+        assert_eq!(vec![0.5, -1.0], samples);
+    }
+
+    #[rstest]
+    #[case(b"RIFF\0\0\0\0AVI ")]
+    #[case(b"RIFF\0\0\0\0WAVEdata\0\0\0\0")]
+    fn invalid_files(#[case] bytes: &[u8]) {
+        assert!(parse_mono(bytes).is_err());
+    }
+}